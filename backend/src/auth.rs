@@ -1,8 +1,96 @@
+use crate::config::Config;
 use crate::db::{hash_key, DbPool};
+use crate::models::ApiError;
 use crate::rate_limit::RateLimiter;
 use rocket::http::Status;
 use rocket::request::{FromRequest, Outcome, Request};
+use rocket::serde::json::Json;
 use rocket::State;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// A single permission an API key can be scoped to, stored in `api_keys`'
+/// `scopes` column as its `serde` rename (comma-separated, e.g.
+/// `"generate,tracked.create"`) and granted to routes via `require`/
+/// `has_scope`. `All` (wire form `"*"`) grants every action — it's what a
+/// freshly-created unscoped key is implicitly treated as (see
+/// `AuthenticatedKey::has_scope`), and replaces the old standalone
+/// `is_admin`-only check for key-management routes with `KeysManage`.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub enum Action {
+    #[serde(rename = "*")]
+    All = 0,
+    #[serde(rename = "generate")]
+    Generate = 1,
+    #[serde(rename = "decode")]
+    Decode = 2,
+    #[serde(rename = "batch_generate")]
+    BatchGenerate = 3,
+    #[serde(rename = "tracked.create")]
+    TrackedCreate = 4,
+    #[serde(rename = "tracked.stats")]
+    TrackedStats = 5,
+    #[serde(rename = "tracked.list")]
+    TrackedList = 6,
+    #[serde(rename = "keys.manage")]
+    KeysManage = 7,
+}
+
+impl Action {
+    /// The `scopes` column's wire form for this action — the same string
+    /// `serde` would produce, hand-written so callers outside a `serde_json`
+    /// round trip (parsing the comma-separated DB column) don't need one.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Action::All => "*",
+            Action::Generate => "generate",
+            Action::Decode => "decode",
+            Action::BatchGenerate => "batch_generate",
+            Action::TrackedCreate => "tracked.create",
+            Action::TrackedStats => "tracked.stats",
+            Action::TrackedList => "tracked.list",
+            Action::KeysManage => "keys.manage",
+        }
+    }
+
+    /// Parses one `scopes` entry. Also accepts the colon-separated names
+    /// this service used before scopes were a typed enum (`qr:generate`,
+    /// `qr:tracked:write`, `qr:tracked:read`, `keys:admin`), so keys created
+    /// before this migration keep working; a legacy `qr:tracked:read` grants
+    /// both of the newer, more specific `TrackedStats`/`TrackedList`.
+    pub fn parse(s: &str) -> Vec<Action> {
+        match s {
+            "*" | "qr:*" => vec![Action::All],
+            "generate" | "qr:generate" => vec![Action::Generate],
+            "decode" | "qr:decode" => vec![Action::Decode],
+            "batch_generate" | "qr:batch" => vec![Action::BatchGenerate],
+            "tracked.create" | "qr:tracked:write" => vec![Action::TrackedCreate],
+            "tracked.stats" => vec![Action::TrackedStats],
+            "tracked.list" => vec![Action::TrackedList],
+            "qr:tracked:read" => vec![Action::TrackedStats, Action::TrackedList],
+            "keys.manage" | "keys:admin" => vec![Action::KeysManage],
+            _ => vec![],
+        }
+    }
+}
+
+/// Parses a comma/space-separated scopes source (the `api_keys.scopes`
+/// column, or a bearer JWT's `scope`/`qr_scopes` claim) into the actions it
+/// grants, plus whether the source had no entries at all once split and
+/// trimmed. The two are kept separate rather than collapsed into "is the
+/// resulting `Vec<Action>` empty" because they mean opposite things: no
+/// entries at all is a legacy/pre-migration key with no scopes data, which
+/// `AuthenticatedKey::has_scope` treats as unrestricted — but a source with
+/// entries that are all unrecognized (a typo'd scope name, for instance)
+/// must grant nothing, even though `Action::parse`-ing it also yields an
+/// empty `Vec<Action>`.
+fn parse_scope_tokens<'a>(tokens: impl Iterator<Item = &'a str>) -> (Vec<Action>, bool) {
+    let tokens: Vec<&str> = tokens.map(str::trim).filter(|s| !s.is_empty()).collect();
+    let source_empty = tokens.is_empty();
+    let scopes = tokens.into_iter().flat_map(Action::parse).collect();
+    (scopes, source_empty)
+}
 
 /// Authenticated API key (kept for tracked QR and admin routes).
 #[derive(Debug)]
@@ -11,6 +99,53 @@ pub struct AuthenticatedKey {
     #[allow(dead_code)]
     pub name: String,
     pub is_admin: bool,
+    /// Parsed `api_keys.scopes` (comma-separated in storage, via
+    /// `Action::parse`). Can be empty either because the scopes source had
+    /// no entries (see `scopes_source_empty`) or because it had entries but
+    /// none were recognized — `has_scope` tells the two apart via
+    /// `scopes_source_empty` rather than by this list alone.
+    pub scopes: Vec<Action>,
+    /// Whether the scopes source (the DB column or JWT claim) had no
+    /// entries at all, as opposed to entries that `Action::parse` just
+    /// didn't recognize. Only the former is treated as an unrestricted,
+    /// pre-migration-style key — see `has_scope`.
+    pub scopes_source_empty: bool,
+    /// `api_keys.rate_limit`, carried alongside the key so a route that
+    /// learns its true request cost only after reading the body (e.g.
+    /// `routes::batch_generate` sizing by `items.len()`) can charge an
+    /// additional `RateLimiter::check_cost` against the same per-key
+    /// capacity the `FromRequest` guard already checked against.
+    pub rate_limit: u64,
+}
+
+impl AuthenticatedKey {
+    /// Whether this key may perform `action`. `is_admin`, a genuinely empty
+    /// scopes source (pre-migration/unrestricted keys — see
+    /// `scopes_source_empty`), and `Action::All` all grant access to
+    /// everything; a scopes source with unrecognized entries grants nothing.
+    pub fn has_scope(&self, action: Action) -> bool {
+        self.is_admin
+            || self.scopes_source_empty
+            || self.scopes.iter().any(|s| *s == Action::All || *s == action)
+    }
+
+    /// `has_scope`, but as a `Result` a handler can propagate with `?` —
+    /// returns the same `Forbidden` `ApiError` shape every other in-handler
+    /// scope check already used before this helper existed.
+    pub fn require(&self, action: Action) -> Result<(), (Status, Json<ApiError>)> {
+        if self.has_scope(action) {
+            Ok(())
+        } else {
+            Err((
+                Status::Forbidden,
+                Json(ApiError {
+                    error: format!("API key is missing the '{}' scope", action.as_str()),
+                    code: "FORBIDDEN".to_string(),
+                    status: 403,
+                }),
+            ))
+        }
+    }
 }
 
 #[rocket::async_trait]
@@ -28,10 +163,72 @@ impl<'r> FromRequest<'r> for AuthenticatedKey {
             _ => return Outcome::Error((Status::InternalServerError, "Rate limiter unavailable")),
         };
 
+        let config = match request.guard::<&State<Config>>().await {
+            Outcome::Success(c) => c,
+            _ => return Outcome::Error((Status::InternalServerError, "Config unavailable")),
+        };
+
         let key = match request.headers().get_one("Authorization") {
             Some(auth) => {
-                if let Some(key) = auth.strip_prefix("Bearer ") {
-                    key.to_string()
+                if let Some(token) = auth.strip_prefix("Bearer ") {
+                    // A JWT always has three dot-separated segments; a static
+                    // API key (`qrs_<uuid>`) never does, so this is enough to
+                    // route between the two without a config flag. JWKS
+                    // verification is only attempted once an issuer is
+                    // actually configured — otherwise a JWT-shaped but
+                    // opaque token just falls through to the (doomed to
+                    // fail) hash lookup below, same as before this guard
+                    // learned about bearer JWTs.
+                    if is_jwt(token) && !config.jwt_bearer_issuer.is_empty() {
+                        let jwks = match request.guard::<&State<JwksCache>>().await {
+                            Outcome::Success(j) => j.inner().clone(),
+                            _ => {
+                                return Outcome::Error((
+                                    Status::InternalServerError,
+                                    "JWKS cache unavailable",
+                                ))
+                            }
+                        };
+                        let token = token.to_string();
+                        let config_owned = config.inner().clone();
+                        // `verify_bearer_jwt` does a blocking JWKS fetch on a
+                        // cache miss (via `reqwest::blocking`), which would
+                        // panic if run directly on the async executor.
+                        let verified =
+                            rocket::tokio::task::spawn_blocking(move || {
+                                verify_bearer_jwt(&token, &config_owned, &jwks)
+                            })
+                            .await;
+                        return match verified {
+                            Ok(Ok(auth_key)) => {
+                                // Keyed on the JWT's `sub`, not a DB id — the
+                                // whole point of accepting tokens from an
+                                // external IdP is not needing an `api_keys`
+                                // row per caller.
+                                let cost = route_cost(request.uri().path().as_str());
+                                let result =
+                                    limiter.check_cost(&auth_key.id, auth_key.rate_limit, cost);
+                                let _ = request.local_cache(|| Some(result.clone()));
+
+                                if !result.allowed {
+                                    Outcome::Error((
+                                        Status::TooManyRequests,
+                                        "Rate limit exceeded. Try again later.",
+                                    ))
+                                } else {
+                                    Outcome::Success(auth_key)
+                                }
+                            }
+                            Ok(Err(_)) => {
+                                Outcome::Error((Status::Unauthorized, "Invalid bearer token"))
+                            }
+                            Err(_) => Outcome::Error((
+                                Status::InternalServerError,
+                                "Bearer verification task panicked",
+                            )),
+                        };
+                    }
+                    token.to_string()
                 } else {
                     return Outcome::Error((
                         Status::Unauthorized,
@@ -51,30 +248,20 @@ impl<'r> FromRequest<'r> for AuthenticatedKey {
         };
 
         let key_hash = hash_key(&key);
-        let conn = db.lock().unwrap();
-
-        match conn.query_row(
-            "SELECT id, name, is_admin, rate_limit FROM api_keys WHERE key_hash = ?1 AND active = 1",
-            rusqlite::params![key_hash],
-            |row| {
-                Ok((
-                    AuthenticatedKey {
-                        id: row.get(0)?,
-                        name: row.get(1)?,
-                        is_admin: row.get::<_, i32>(2)? == 1,
-                    },
-                    row.get::<_, i64>(3)?,
-                ))
-            },
-        ) {
-            Ok((auth_key, rate_limit)) => {
-                let _ = conn.execute(
-                    "UPDATE api_keys SET last_used_at = datetime('now'), requests_count = requests_count + 1 WHERE id = ?1",
-                    rusqlite::params![auth_key.id],
-                );
-                drop(conn);
-
-                let result = limiter.check(&auth_key.id, rate_limit as u64);
+        let pool = db.inner().clone();
+
+        // The lookup + last-used/request-count update hold a SQLite
+        // connection for the duration of the call, so run them on Rocket's
+        // blocking-task pool rather than the async executor — every route
+        // using this guard would otherwise stall behind it under load.
+        let lookup =
+            rocket::tokio::task::spawn_blocking(move || lookup_and_touch_key(&pool, &key_hash))
+                .await;
+
+        match lookup {
+            Ok(Ok(KeyLookup::Valid(auth_key, rate_limit))) => {
+                let cost = route_cost(request.uri().path().as_str());
+                let result = limiter.check_cost(&auth_key.id, rate_limit as u64, cost);
                 let _ = request.local_cache(|| Some(result.clone()));
 
                 if !result.allowed {
@@ -86,11 +273,272 @@ impl<'r> FromRequest<'r> for AuthenticatedKey {
 
                 Outcome::Success(auth_key)
             }
-            Err(_) => Outcome::Error((Status::Unauthorized, "Invalid API key")),
+            Ok(Ok(KeyLookup::NotFound)) => {
+                Outcome::Error((Status::Unauthorized, "Invalid API key"))
+            }
+            Ok(Ok(KeyLookup::NotYetValid)) => Outcome::Error((
+                Status::Unauthorized,
+                "API key is not valid yet (KEY_NOT_YET_VALID)",
+            )),
+            Ok(Ok(KeyLookup::Expired)) => Outcome::Error((
+                Status::Unauthorized,
+                "API key has expired (KEY_EXPIRED)",
+            )),
+            Ok(Err(())) => Outcome::Error((Status::Unauthorized, "Invalid API key")),
+            Err(_) => Outcome::Error((Status::InternalServerError, "Database unavailable")),
         }
     }
 }
 
+/// Whether `token` looks like a JWT (three non-empty, dot-separated
+/// segments) rather than an opaque static API key. Cheap enough to run on
+/// every request with a `Bearer` header, since static keys never contain a
+/// `.`.
+fn is_jwt(token: &str) -> bool {
+    let mut parts = token.split('.');
+    matches!(
+        (parts.next(), parts.next(), parts.next(), parts.next()),
+        (Some(a), Some(b), Some(c), None) if !a.is_empty() && !b.is_empty() && !c.is_empty()
+    )
+}
+
+#[derive(serde::Deserialize)]
+struct Jwks {
+    keys: Vec<jsonwebtoken::jwk::Jwk>,
+}
+
+/// Claims lifted from a verified bearer JWT. `qr_scopes` (comma-separated,
+/// this service's own claim) takes priority over the standard OIDC `scope`
+/// (space-separated) when both are present, since it lets an IdP grant this
+/// service's exact `Action` set without overloading the shared `scope` claim
+/// used by other resource servers on the same token.
+#[derive(serde::Deserialize)]
+struct BearerClaims {
+    sub: String,
+    #[serde(default)]
+    scope: Option<String>,
+    #[serde(default)]
+    qr_scopes: Option<String>,
+}
+
+/// Caches JWKS signing keys by `kid` so a bearer-JWT request doesn't fetch
+/// the JWKS endpoint on every call — only the first time a given `kid` is
+/// seen. A `kid` miss triggers one re-fetch of the whole set (covering key
+/// rotation) before giving up. Cheaply `Clone`-able (an `Arc` around the
+/// real map) so it can be moved into `spawn_blocking` alongside the JWKS
+/// HTTP fetch, the same way `DbPool`/`RateLimiter` already are.
+#[derive(Clone)]
+pub struct JwksCache {
+    keys: std::sync::Arc<Mutex<HashMap<String, jsonwebtoken::jwk::Jwk>>>,
+}
+
+impl JwksCache {
+    pub fn new() -> Self {
+        JwksCache {
+            keys: std::sync::Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn get(&self, kid: &str) -> Option<jsonwebtoken::jwk::Jwk> {
+        self.keys
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(kid)
+            .cloned()
+    }
+
+    fn refresh(&self, jwks_url: &str) -> Result<(), String> {
+        let jwks: Jwks = reqwest::blocking::get(jwks_url)
+            .map_err(|e| format!("Failed to fetch JWKS: {}", e))?
+            .json()
+            .map_err(|e| format!("Invalid JWKS response: {}", e))?;
+
+        let mut keys = self.keys.lock().unwrap_or_else(|e| e.into_inner());
+        keys.clear();
+        for jwk in jwks.keys {
+            if let Some(kid) = jwk.common.key_id.clone() {
+                keys.insert(kid, jwk);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for JwksCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Verifies `token`'s signature against `config.jwt_bearer_jwks_url`
+/// (refreshing `jwks` once on a `kid` miss, to pick up key rotation),
+/// checks `exp`/`iss`/`aud`, and maps its scope claim onto an
+/// `AuthenticatedKey` keyed by `sub` instead of an `api_keys` row.
+fn verify_bearer_jwt(token: &str, config: &Config, jwks: &JwksCache) -> Result<AuthenticatedKey, String> {
+    let header = jsonwebtoken::decode_header(token).map_err(|e| e.to_string())?;
+    let kid = header.kid.ok_or("Bearer JWT is missing a key id")?;
+
+    let jwk = match jwks.get(&kid) {
+        Some(jwk) => jwk,
+        None => {
+            jwks.refresh(&config.jwt_bearer_jwks_url)?;
+            jwks.get(&kid).ok_or("No matching signing key in JWKS")?
+        }
+    };
+
+    let decoding_key = jsonwebtoken::DecodingKey::from_jwk(&jwk).map_err(|e| e.to_string())?;
+    let mut validation = jsonwebtoken::Validation::new(header.alg);
+    validation.set_audience(&[&config.jwt_bearer_audience]);
+    validation.set_issuer(&[&config.jwt_bearer_issuer]);
+    validation.set_required_spec_claims(&["exp", "iss", "aud"]);
+
+    let data = jsonwebtoken::decode::<BearerClaims>(token, &decoding_key, &validation)
+        .map_err(|e| e.to_string())?;
+    let claims = data.claims;
+
+    let scope_source = claims
+        .qr_scopes
+        .as_deref()
+        .map(|s| s.split(',').collect::<Vec<_>>())
+        .unwrap_or_else(|| claims.scope.as_deref().map(|s| s.split(' ').collect()).unwrap_or_default());
+    let (scopes, scopes_source_empty) = parse_scope_tokens(scope_source.into_iter());
+
+    Ok(AuthenticatedKey {
+        id: claims.sub,
+        name: "bearer-jwt".to_string(),
+        is_admin: false,
+        scopes,
+        scopes_source_empty,
+        rate_limit: config.jwt_bearer_rate_limit,
+    })
+}
+
+/// Outcome of `lookup_and_touch_key`'s validity checks, kept distinct from a
+/// flat `Result<_, ()>` so the guard can report *why* a key was rejected
+/// (unknown/inactive vs. outside its validity window) rather than a single
+/// generic "Invalid API key".
+enum KeyLookup {
+    Valid(AuthenticatedKey, i64),
+    NotFound,
+    NotYetValid,
+    Expired,
+}
+
+/// Looks up an API key by its hash, checks its `valid_from`/`valid_until`
+/// window against the current time, and on success stamps `last_used_at`/
+/// bumps `requests_count`. Also matches `previous_key_hash` — the hash
+/// `routes::rotate_key` displaced — so a caller still presenting the old
+/// secret keeps working until `previous_key_hash_expires_at` lapses. Split
+/// out of `from_request` so it can run inside `spawn_blocking`.
+fn lookup_and_touch_key(db: &DbPool, key_hash: &str) -> Result<KeyLookup, ()> {
+    let conn = db.get().unwrap();
+
+    let result = conn.query_row(
+        "SELECT id, name, is_admin, rate_limit, valid_from, valid_until, scopes,
+                key_hash, previous_key_hash_expires_at
+         FROM api_keys
+         WHERE (key_hash = ?1 OR previous_key_hash = ?1) AND active = 1",
+        rusqlite::params![key_hash],
+        |row| {
+            let raw_scopes = row.get::<_, String>(6)?;
+            let (scopes, scopes_source_empty) = parse_scope_tokens(raw_scopes.split(','));
+            Ok((
+                AuthenticatedKey {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    is_admin: row.get::<_, i32>(2)? == 1,
+                    scopes,
+                    scopes_source_empty,
+                    rate_limit: row.get::<_, i64>(3)? as u64,
+                },
+                row.get::<_, i64>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, Option<String>>(5)?,
+                row.get::<_, String>(7)?,
+                row.get::<_, Option<String>>(8)?,
+            ))
+        },
+    );
+
+    let (auth_key, rate_limit, valid_from, valid_until, current_key_hash, previous_key_hash_expires_at) =
+        match result {
+            Ok(row) => row,
+            Err(_) => return Ok(KeyLookup::NotFound),
+        };
+
+    let now = chrono::Utc::now();
+
+    // Matched via `previous_key_hash` rather than the live `key_hash` — only
+    // honored inside the rotation grace window.
+    if current_key_hash != key_hash {
+        let still_in_grace = previous_key_hash_expires_at
+            .as_deref()
+            .and_then(|exp| chrono::DateTime::parse_from_rfc3339(exp).ok())
+            .is_some_and(|exp| now <= exp);
+        if !still_in_grace {
+            return Ok(KeyLookup::Expired);
+        }
+    }
+
+    if let Some(ref from) = valid_from {
+        if let Ok(from) = chrono::DateTime::parse_from_rfc3339(from) {
+            if now < from {
+                return Ok(KeyLookup::NotYetValid);
+            }
+        }
+    }
+    if let Some(ref until) = valid_until {
+        if let Ok(until) = chrono::DateTime::parse_from_rfc3339(until) {
+            if now > until {
+                return Ok(KeyLookup::Expired);
+            }
+        }
+    }
+
+    let _ = conn.execute(
+        "UPDATE api_keys SET last_used_at = datetime('now'), requests_count = requests_count + 1 WHERE id = ?1",
+        rusqlite::params![auth_key.id],
+    );
+
+    Ok(KeyLookup::Valid(auth_key, rate_limit))
+}
+
+/// Extracts the caller's IP from `X-Forwarded-For` (first hop), then
+/// `X-Real-Ip`, then the socket address — in that order, since requests
+/// normally arrive via a reverse proxy in production. Shared by `ClientIp`
+/// and `routes::ScanMeta` so the two guards can't drift on precedence.
+pub(crate) fn client_ip_from_headers(request: &Request) -> String {
+    request
+        .headers()
+        .get_one("X-Forwarded-For")
+        .and_then(|v| v.split(',').next())
+        .map(|s| s.trim().to_string())
+        .or_else(|| {
+            request
+                .headers()
+                .get_one("X-Real-Ip")
+                .map(|s| s.to_string())
+        })
+        .or_else(|| request.remote().map(|a| a.ip().to_string()))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Token cost of a generation/decode request, scaled by how much server
+/// work the route actually does, so a batch of QR codes or a decoded image
+/// upload drains a key's (or an anonymous caller's) bucket faster than a
+/// single rendered QR — see `RateLimiter::check_cost`. Matched by path
+/// suffix, since the mounted path is `/api/v1/qr/batch`, `/api/v1/qr/decode`,
+/// etc. regardless of the exact prefix in front.
+pub(crate) fn route_cost(path: &str) -> u64 {
+    if path.ends_with("/batch") {
+        10
+    } else if path.ends_with("/decode") {
+        3
+    } else {
+        1
+    }
+}
+
 /// Extracts the client IP for IP-based rate limiting on public routes.
 #[derive(Debug)]
 pub struct ClientIp(pub String);
@@ -100,21 +548,193 @@ impl<'r> FromRequest<'r> for ClientIp {
     type Error = std::convert::Infallible;
 
     async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
-        // Try X-Forwarded-For, X-Real-Ip, then socket addr
-        let ip = request
+        Outcome::Success(ClientIp(client_ip_from_headers(request)))
+    }
+}
+
+/// Extracts the `User-Agent` header, for request logging and the external
+/// authorization hook (see `grpc_auth`). Empty string when absent.
+#[derive(Debug)]
+pub struct UserAgent(pub String);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for UserAgent {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let ua = request
             .headers()
-            .get_one("X-Forwarded-For")
-            .and_then(|v| v.split(',').next())
-            .map(|s| s.trim().to_string())
-            .or_else(|| {
-                request
-                    .headers()
-                    .get_one("X-Real-Ip")
-                    .map(|s| s.to_string())
-            })
-            .or_else(|| request.remote().map(|a| a.ip().to_string()))
-            .unwrap_or_else(|| "unknown".to_string());
-
-        Outcome::Success(ClientIp(ip))
+            .get_one("User-Agent")
+            .unwrap_or("")
+            .to_string();
+        Outcome::Success(UserAgent(ua))
+    }
+}
+
+/// Raw `Authorization: Bearer <token>` header, if present — unlike
+/// `AuthenticatedKey`, never rejects the request, since routes that accept
+/// either an API key/session or a standalone bearer token (e.g. a tracked
+/// QR's JWT manage token, see `jwt_manage`) need to see "no token" as a
+/// normal case, not a guard failure.
+#[derive(Debug)]
+pub struct BearerToken(pub Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for BearerToken {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let token = request
+            .headers()
+            .get_one("Authorization")
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .map(|s| s.to_string());
+        Outcome::Success(BearerToken(token))
+    }
+}
+
+/// Enforces `Config::anonymous_rate_limit` (keyed by client IP, route class
+/// `"anonymous"`) on routes that also accept `Option<AuthenticatedKey>` —
+/// see `routes::generate_qr` and its siblings. A no-op when the request
+/// carries an `Authorization`/`X-API-Key` header at all: `AuthenticatedKey`'s
+/// own guard already rate-limits that case by key id, so charging this
+/// guard too would double-count a single authenticated request.
+pub struct AnonymousRateLimit;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AnonymousRateLimit {
+    type Error = &'static str;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let has_key_header = request.headers().get_one("Authorization").is_some()
+            || request.headers().get_one("X-API-Key").is_some();
+        if has_key_header {
+            return Outcome::Success(AnonymousRateLimit);
+        }
+
+        let limiter = match request.guard::<&State<RateLimiter>>().await {
+            Outcome::Success(l) => l,
+            _ => return Outcome::Error((Status::InternalServerError, "Rate limiter unavailable")),
+        };
+        let config = match request.guard::<&State<Config>>().await {
+            Outcome::Success(c) => c,
+            _ => return Outcome::Error((Status::InternalServerError, "Config unavailable")),
+        };
+
+        let ip = client_ip_from_headers(request);
+        let cost = route_cost(request.uri().path().as_str());
+        let result = limiter.check_route_cost(
+            "anonymous",
+            &ip,
+            &config.route_rate_limits,
+            config.anonymous_rate_limit,
+            cost,
+        );
+        let _ = request.local_cache(|| Some(result.clone()));
+
+        if !result.allowed {
+            return Outcome::Error((
+                Status::TooManyRequests,
+                "Rate limit exceeded for unauthenticated requests. Supply an API key for a higher limit.",
+            ));
+        }
+        Outcome::Success(AnonymousRateLimit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_with(scopes: Vec<Action>, scopes_source_empty: bool) -> AuthenticatedKey {
+        AuthenticatedKey {
+            id: "key1".to_string(),
+            name: "test".to_string(),
+            is_admin: false,
+            scopes,
+            scopes_source_empty,
+            rate_limit: 1000,
+        }
+    }
+
+    #[test]
+    fn action_parse_recognizes_current_and_legacy_forms() {
+        assert_eq!(Action::parse("tracked.stats"), vec![Action::TrackedStats]);
+        assert_eq!(Action::parse("qr:tracked:write"), vec![Action::TrackedCreate]);
+        assert_eq!(
+            Action::parse("qr:tracked:read"),
+            vec![Action::TrackedStats, Action::TrackedList]
+        );
+        assert_eq!(Action::parse("*"), vec![Action::All]);
+    }
+
+    #[test]
+    fn action_parse_unrecognized_is_empty() {
+        assert_eq!(Action::parse("tracked.stat"), Vec::<Action>::new());
+        assert_eq!(Action::parse("bogus"), Vec::<Action>::new());
+    }
+
+    #[test]
+    fn parse_scope_tokens_empty_source_is_flagged_empty() {
+        let (scopes, source_empty) = parse_scope_tokens("".split(','));
+        assert!(scopes.is_empty());
+        assert!(source_empty);
+    }
+
+    #[test]
+    fn parse_scope_tokens_unrecognized_entries_are_not_flagged_empty() {
+        let (scopes, source_empty) = parse_scope_tokens("tracked.stat".split(','));
+        assert!(scopes.is_empty());
+        assert!(!source_empty);
+    }
+
+    #[test]
+    fn parse_scope_tokens_recognized_entries() {
+        let (scopes, source_empty) = parse_scope_tokens("decode,tracked.create".split(','));
+        assert_eq!(scopes, vec![Action::Decode, Action::TrackedCreate]);
+        assert!(!source_empty);
+    }
+
+    #[test]
+    fn has_scope_legacy_empty_source_is_unrestricted() {
+        let key = key_with(vec![], true);
+        assert!(key.has_scope(Action::TrackedStats));
+        assert!(key.has_scope(Action::KeysManage));
+    }
+
+    #[test]
+    fn has_scope_unrecognized_scopes_grants_nothing() {
+        // A typo'd scope string (e.g. "tracked.stat") parses to an empty
+        // `Vec<Action>` just like a legacy key with no scopes column data
+        // would — `scopes_source_empty` is what tells these apart, and a
+        // non-empty-but-unrecognized source must not fall back to
+        // unrestricted.
+        let key = key_with(vec![], false);
+        assert!(!key.has_scope(Action::TrackedStats));
+        assert!(!key.has_scope(Action::Decode));
+        assert!(key.require(Action::TrackedStats).is_err());
+    }
+
+    #[test]
+    fn has_scope_respects_specific_grants() {
+        let key = key_with(vec![Action::Decode], false);
+        assert!(key.has_scope(Action::Decode));
+        assert!(!key.has_scope(Action::TrackedStats));
+        assert!(key.require(Action::Decode).is_ok());
+        assert!(key.require(Action::TrackedStats).is_err());
+    }
+
+    #[test]
+    fn has_scope_all_grants_everything() {
+        let key = key_with(vec![Action::All], false);
+        assert!(key.has_scope(Action::TrackedStats));
+        assert!(key.has_scope(Action::KeysManage));
+    }
+
+    #[test]
+    fn has_scope_admin_grants_everything_regardless_of_scopes() {
+        let mut key = key_with(vec![], false);
+        key.is_admin = true;
+        assert!(key.has_scope(Action::TrackedStats));
     }
 }
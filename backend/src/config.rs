@@ -0,0 +1,592 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// A per-route-class quota from `Config.route_rate_limits`. Route classes
+/// are plain string ids (e.g. `"health"`, `"generate"`, `"tracked"`) rather
+/// than a closed enum, so an operator can tune or add a tier by editing
+/// config, not code — see `RateLimiter::check_route`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RouteQuota {
+    pub window_secs: u64,
+    pub limit: u64,
+}
+
+/// Typed application configuration, loaded once at startup from `config.toml`
+/// (if present) with individual fields overridable by environment variables.
+/// Replaces the scattered `std::env::var` reads that used to live in
+/// `main.rs`, `db.rs`, and `rate_limit.rs`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Config {
+    #[serde(default = "default_address")]
+    pub address: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    #[serde(default = "default_database_path")]
+    pub database_path: String,
+    /// Name of the database backend, `"sqlite"` by default. `DbPool` is
+    /// always a pooled `r2d2_sqlite` connection against `database_path`:
+    /// this field is loaded from config/env but nothing reads it, and there
+    /// is no Postgres (or other) backend to select.
+    #[serde(default = "default_db_backend")]
+    pub db_backend: String,
+    #[serde(default = "default_db_pool_size")]
+    pub db_pool_size: u32,
+    /// How long `db.get()` waits for a free pooled connection before giving
+    /// up, in seconds.
+    #[serde(default = "default_db_connection_timeout_secs")]
+    pub db_connection_timeout_secs: u64,
+    /// SQLite `PRAGMA busy_timeout`, in milliseconds — how long a writer
+    /// waits on `SQLITE_BUSY` before failing, so concurrent writers under
+    /// WAL mode (e.g. `/r/<code>` scans alongside tracked-QR creation)
+    /// queue briefly instead of erroring out.
+    #[serde(default = "default_db_busy_timeout_ms")]
+    pub db_busy_timeout_ms: u32,
+    #[serde(default = "default_rate_limit_window_secs")]
+    pub rate_limit_window_secs: u64,
+    /// Per-route-class quotas, overriding `rate_limit_window_secs` and a
+    /// key's `api_keys.rate_limit` for routes checked via
+    /// `RateLimiter::check_route` (e.g. a cheap `/health` vs. an expensive
+    /// `/generate`). A route class with no entry here falls back to the
+    /// caller's own default quota.
+    #[serde(default)]
+    pub route_rate_limits: HashMap<String, RouteQuota>,
+    /// Requests-per-window quota for `generate_qr`/`batch_generate`/
+    /// `generate_from_template`/`decode_qr` when called with no
+    /// `AuthenticatedKey` at all (see `auth::AnonymousRateLimit`),
+    /// keyed by client IP instead of key id. Deliberately far below the
+    /// default per-key `rate_limit` (100), since an IP can be shared by many
+    /// callers behind NAT/a proxy. An operator wanting a different anonymous
+    /// quota per route can still add an `"anonymous"` entry to
+    /// `route_rate_limits`, which takes precedence over this default.
+    #[serde(default = "default_anonymous_rate_limit")]
+    pub anonymous_rate_limit: u64,
+    /// Selects the `rate_limit::RateLimitStore` backing token buckets (see
+    /// `rate_limit::store_from_config`). The only backend bundled here is
+    /// the in-process one; a multi-instance deployment sharing one logical
+    /// quota would add a shared-store backend and switch this to select it.
+    #[serde(default = "default_rate_limit_backend")]
+    pub rate_limit_backend: String,
+    #[serde(default = "default_static_dir")]
+    pub static_dir: PathBuf,
+    #[serde(default = "default_cors_allowed_origins")]
+    pub cors_allowed_origins: Vec<String>,
+    #[serde(default = "default_cors_allowed_methods")]
+    pub cors_allowed_methods: Vec<String>,
+    #[serde(default = "default_cors_allowed_headers")]
+    pub cors_allowed_headers: Vec<String>,
+    /// Response headers a browser is allowed to read from script via
+    /// `Access-Control-Expose-Headers`, beyond the CORS-safelisted set.
+    /// Defaults to the `X-RateLimit-*`/`Retry-After` headers `RateLimitHeaders`
+    /// attaches, so a browser client can see its remaining quota.
+    #[serde(default = "default_cors_expose_headers")]
+    pub cors_expose_headers: Vec<String>,
+    #[serde(default)]
+    pub cors_allow_credentials: bool,
+    /// Opts a wildcard `cors_allowed_origins` entry into `cors::Origin::Copy`
+    /// instead of `Origin::Any` when `cors_allow_credentials` is also set —
+    /// echoing back whatever `Origin` a request sent rather than a literal
+    /// `*`, which is the only way to pair credentialed requests with an
+    /// open-ended set of origins (a dashboard serving many customer-owned
+    /// domains via cookie sessions, say). Ignored unless credentials are
+    /// also on; defaults off so the combination is always explicit.
+    #[serde(default)]
+    pub cors_reflect_credentials: bool,
+    /// How long (seconds) a browser may cache a preflight `OPTIONS` response
+    /// before re-checking it, via the `Access-Control-Max-Age` header.
+    #[serde(default = "default_cors_max_age_secs")]
+    pub cors_max_age_secs: u64,
+    /// Dev mode is the only thing allowed to fall back to a wide-open,
+    /// credential-less CORS policy (mirrors the old `AllowedOrigins::all()`
+    /// behavior). Defaults to off so a deployment has to opt in explicitly.
+    #[serde(default)]
+    pub dev_mode: bool,
+    #[serde(default = "default_base_url")]
+    pub base_url: String,
+
+    /// Issuer URL of the OIDC/OAuth2 provider backing the dashboard login
+    /// (e.g. `https://accounts.example.com`). Authorization and token
+    /// endpoints are assumed to live at `{issuer}/authorize`, `{issuer}/token`
+    /// and `{issuer}/jwks` unless the provider requires otherwise. Empty
+    /// disables the login subsystem entirely.
+    #[serde(default)]
+    pub oidc_issuer_url: String,
+    #[serde(default)]
+    pub oidc_client_id: String,
+    #[serde(default)]
+    pub oidc_client_secret: String,
+    /// Email addresses granted admin-equivalent access when logged in via
+    /// OIDC (mirrors `is_admin` on API keys).
+    #[serde(default)]
+    pub oidc_admin_emails: Vec<String>,
+
+    /// Issuer (`iss`) every bearer JWT presented to `AuthenticatedKey`'s
+    /// `FromRequest` guard (see `auth::verify_bearer_jwt`) must match. Empty
+    /// disables bearer-JWT auth entirely, leaving only the hashed-API-key
+    /// lookup — a JWT-shaped `Authorization: Bearer` value is verified
+    /// against the JWKS (instead of being looked up as an API key) only once
+    /// this is set.
+    #[serde(default)]
+    pub jwt_bearer_issuer: String,
+    /// Audience (`aud`) every bearer JWT must carry.
+    #[serde(default)]
+    pub jwt_bearer_audience: String,
+    /// JWKS endpoint used to verify bearer JWTs' signatures, cached by `kid`
+    /// and re-fetched on a cache miss (see `auth::JwksCache`).
+    #[serde(default)]
+    pub jwt_bearer_jwks_url: String,
+    /// Per-`sub` rate-limit quota for bearer-JWT callers, since there's no
+    /// `api_keys` row to read one from.
+    #[serde(default = "default_jwt_bearer_rate_limit")]
+    pub jwt_bearer_rate_limit: u64,
+
+    /// Address of an external gRPC authorization service implementing the
+    /// `Authorizer/Authorize` RPC (see `grpc_auth`). Consulted before QR
+    /// creation and short-URL redirects so operators can enforce allowlists,
+    /// blocklists and quota policies centrally. Empty disables the hook.
+    #[serde(default)]
+    pub grpc_auth_url: String,
+    /// When the hook is enabled, whether a transport error (timeout,
+    /// connection refused) allows the request through instead of denying it.
+    /// Defaults to fail-closed, the safer choice for an authorization gate.
+    #[serde(default)]
+    pub grpc_auth_fail_open: bool,
+
+    /// Secret used to HMAC-sign auto-generated tracked-QR short codes (see
+    /// `db::generate_short_code`/`verify_short_code`), so the redirect
+    /// handler can reject forged or enumerated codes before touching the
+    /// database. Empty disables signing; auto-generated codes are then
+    /// plain random strings, as before.
+    #[serde(default)]
+    pub shortcode_signing_key: String,
+
+    /// Secret used to HMAC-sign public image links minted by `POST
+    /// /qr/<id>/sign` (see `db::sign_image_url`/`verify_image_signature`). Empty
+    /// disables the public-image endpoint entirely, since an unsigned link
+    /// would have no way to prove it was actually issued by this server.
+    #[serde(default)]
+    pub image_signing_key: String,
+
+    /// Where QR image blobs are stored: `"sqlite"` (default, in the `qr_blobs`
+    /// table alongside the rest of the database) or `"s3"` (an S3-compatible
+    /// object store; see `storage::S3Storage`). Selects the `StorageBackend`
+    /// built in `main.rs`.
+    #[serde(default = "default_storage_backend")]
+    pub storage_backend: String,
+    #[serde(default)]
+    pub s3_bucket: String,
+    /// Base URL of the S3-compatible endpoint, e.g. `https://s3.amazonaws.com`
+    /// or a MinIO/R2 equivalent. Objects are addressed path-style as
+    /// `{s3_endpoint}/{s3_bucket}/{id}`.
+    #[serde(default)]
+    pub s3_endpoint: String,
+    #[serde(default = "default_s3_region")]
+    pub s3_region: String,
+    #[serde(default)]
+    pub s3_access_key: String,
+    #[serde(default)]
+    pub s3_secret_key: String,
+    /// How long (seconds) a presigned GET URL `S3Storage` hands back stays
+    /// valid, via the presign's `X-Amz-Expires` window. Kept short by
+    /// default since these URLs need no API key to fetch the image.
+    #[serde(default = "default_s3_presign_expiry_secs")]
+    pub s3_presign_expiry_secs: u64,
+
+    /// Which `geoip::GeoIpLookup` resolves a scan's client IP to a country
+    /// for analytics. `"noop"` (default) skips resolution entirely, since a
+    /// real provider (MaxMind, IP2Location, ...) needs a local database file
+    /// or network call this service doesn't bundle. Selects the lookup built
+    /// in `main.rs`.
+    #[serde(default = "default_geoip_backend")]
+    pub geoip_backend: String,
+
+    /// How often (seconds) the background reaper sweeps `tracked_qr` for
+    /// rows past `expires_at`. See `reaper`.
+    #[serde(default = "default_expiry_sweep_interval_secs")]
+    pub expiry_sweep_interval_secs: u64,
+    /// What the reaper does with an expired row: `"tombstone"` (default)
+    /// keeps it (and its `scan_events`) for historical stats but stops
+    /// serving `/r/<code>`; `"delete"` removes the row and its scan events
+    /// outright.
+    #[serde(default = "default_expiry_policy")]
+    pub expiry_policy: String,
+
+    /// Enables signed JWT "manage tokens" for tracked QRs: `POST
+    /// /api/v1/qr/tracked` mints an RS256 JWT (claims `id`/`short_code`/`exp`)
+    /// alongside the response, and `DELETE /api/v1/qr/tracked/{id}` accepts
+    /// it as a `Bearer` token in place of an API key/session, verified
+    /// against the keypair published at `/.well-known/jwks.json` (see
+    /// `jwt_manage`). Defaults to off; the existing `Principal`-based
+    /// ownership check keeps working either way.
+    #[serde(default)]
+    pub jwt_manage_tokens_enabled: bool,
+    /// Manage-token lifetime (seconds) used when a tracked QR has no
+    /// `expires_at` of its own.
+    #[serde(default = "default_jwt_manage_token_default_ttl_secs")]
+    pub jwt_manage_token_default_ttl_secs: u64,
+
+    /// How long (seconds) a rotated-out API key hash keeps working after
+    /// `POST /keys/{id}/rotate` mints its replacement, so a caller mid-flight
+    /// with the old secret isn't cut off the instant it rotates. `0` disables
+    /// the grace window (old hash stops working immediately).
+    #[serde(default = "default_key_rotation_grace_secs")]
+    pub key_rotation_grace_secs: u64,
+}
+
+fn default_address() -> String {
+    "0.0.0.0".to_string()
+}
+fn default_port() -> u16 {
+    8000
+}
+fn default_database_path() -> String {
+    "qr_service.db".to_string()
+}
+fn default_db_backend() -> String {
+    "sqlite".to_string()
+}
+fn default_db_pool_size() -> u32 {
+    8
+}
+fn default_db_connection_timeout_secs() -> u64 {
+    30
+}
+fn default_db_busy_timeout_ms() -> u32 {
+    5000
+}
+fn default_rate_limit_window_secs() -> u64 {
+    60
+}
+fn default_anonymous_rate_limit() -> u64 {
+    20
+}
+fn default_rate_limit_backend() -> String {
+    "memory".to_string()
+}
+fn default_jwt_bearer_rate_limit() -> u64 {
+    100
+}
+fn default_static_dir() -> PathBuf {
+    PathBuf::from("../frontend/dist")
+}
+fn default_cors_allowed_origins() -> Vec<String> {
+    Vec::new()
+}
+fn default_cors_allowed_methods() -> Vec<String> {
+    vec![
+        "GET".to_string(),
+        "POST".to_string(),
+        "DELETE".to_string(),
+        "OPTIONS".to_string(),
+    ]
+}
+fn default_cors_allowed_headers() -> Vec<String> {
+    vec![
+        "Authorization".to_string(),
+        "X-API-Key".to_string(),
+        "Content-Type".to_string(),
+    ]
+}
+fn default_cors_expose_headers() -> Vec<String> {
+    vec![
+        "X-RateLimit-Limit".to_string(),
+        "X-RateLimit-Remaining".to_string(),
+        "X-RateLimit-Reset".to_string(),
+        "Retry-After".to_string(),
+    ]
+}
+fn default_cors_max_age_secs() -> u64 {
+    3600
+}
+fn default_base_url() -> String {
+    "http://localhost:8000".to_string()
+}
+fn default_storage_backend() -> String {
+    "sqlite".to_string()
+}
+fn default_s3_region() -> String {
+    "us-east-1".to_string()
+}
+fn default_s3_presign_expiry_secs() -> u64 {
+    300
+}
+fn default_geoip_backend() -> String {
+    "noop".to_string()
+}
+fn default_expiry_sweep_interval_secs() -> u64 {
+    300
+}
+fn default_expiry_policy() -> String {
+    "tombstone".to_string()
+}
+fn default_jwt_manage_token_default_ttl_secs() -> u64 {
+    86400 * 30
+}
+fn default_key_rotation_grace_secs() -> u64 {
+    3600
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            address: default_address(),
+            port: default_port(),
+            database_path: default_database_path(),
+            db_backend: default_db_backend(),
+            db_pool_size: default_db_pool_size(),
+            db_connection_timeout_secs: default_db_connection_timeout_secs(),
+            db_busy_timeout_ms: default_db_busy_timeout_ms(),
+            rate_limit_window_secs: default_rate_limit_window_secs(),
+            route_rate_limits: HashMap::new(),
+            anonymous_rate_limit: default_anonymous_rate_limit(),
+            rate_limit_backend: default_rate_limit_backend(),
+            static_dir: default_static_dir(),
+            cors_allowed_origins: default_cors_allowed_origins(),
+            cors_allowed_methods: default_cors_allowed_methods(),
+            cors_allowed_headers: default_cors_allowed_headers(),
+            cors_expose_headers: default_cors_expose_headers(),
+            cors_allow_credentials: false,
+            cors_reflect_credentials: false,
+            cors_max_age_secs: default_cors_max_age_secs(),
+            dev_mode: false,
+            base_url: default_base_url(),
+            oidc_issuer_url: String::new(),
+            oidc_client_id: String::new(),
+            oidc_client_secret: String::new(),
+            oidc_admin_emails: Vec::new(),
+            jwt_bearer_issuer: String::new(),
+            jwt_bearer_audience: String::new(),
+            jwt_bearer_jwks_url: String::new(),
+            jwt_bearer_rate_limit: default_jwt_bearer_rate_limit(),
+            grpc_auth_url: String::new(),
+            grpc_auth_fail_open: false,
+            shortcode_signing_key: String::new(),
+            image_signing_key: String::new(),
+            storage_backend: default_storage_backend(),
+            s3_bucket: String::new(),
+            s3_endpoint: String::new(),
+            s3_region: default_s3_region(),
+            s3_access_key: String::new(),
+            s3_secret_key: String::new(),
+            s3_presign_expiry_secs: default_s3_presign_expiry_secs(),
+            geoip_backend: default_geoip_backend(),
+            expiry_sweep_interval_secs: default_expiry_sweep_interval_secs(),
+            expiry_policy: default_expiry_policy(),
+            jwt_manage_tokens_enabled: false,
+            jwt_manage_token_default_ttl_secs: default_jwt_manage_token_default_ttl_secs(),
+            key_rotation_grace_secs: default_key_rotation_grace_secs(),
+        }
+    }
+}
+
+impl Config {
+    /// Loads `config.toml` from the current directory (if present), then
+    /// applies environment-variable overrides on top. Env vars always win,
+    /// so a deployment can tweak a single setting without touching the file.
+    /// Fails fast with a descriptive message if `config.toml` exists but
+    /// doesn't parse.
+    pub fn load() -> Result<Self, String> {
+        let mut config = match std::fs::read_to_string("config.toml") {
+            Ok(contents) => toml::from_str(&contents)
+                .map_err(|e| format!("Invalid config.toml: {}", e))?,
+            Err(_) => Config::default(),
+        };
+
+        if let Ok(v) = std::env::var("ADDRESS") {
+            config.address = v;
+        }
+        if let Ok(v) = std::env::var("PORT") {
+            config.port = v
+                .parse()
+                .map_err(|_| format!("Invalid PORT env var: {}", v))?;
+        }
+        if let Ok(v) = std::env::var("DATABASE_PATH") {
+            config.database_path = v;
+        }
+        if let Ok(v) = std::env::var("DB_BACKEND") {
+            config.db_backend = v;
+        }
+        if let Ok(v) = std::env::var("DB_POOL_SIZE") {
+            config.db_pool_size = v
+                .parse()
+                .map_err(|_| format!("Invalid DB_POOL_SIZE env var: {}", v))?;
+        }
+        if let Ok(v) = std::env::var("DB_CONNECTION_TIMEOUT_SECS") {
+            config.db_connection_timeout_secs = v
+                .parse()
+                .map_err(|_| format!("Invalid DB_CONNECTION_TIMEOUT_SECS env var: {}", v))?;
+        }
+        if let Ok(v) = std::env::var("DB_BUSY_TIMEOUT_MS") {
+            config.db_busy_timeout_ms = v
+                .parse()
+                .map_err(|_| format!("Invalid DB_BUSY_TIMEOUT_MS env var: {}", v))?;
+        }
+        if let Ok(v) = std::env::var("RATE_LIMIT_WINDOW_SECS") {
+            config.rate_limit_window_secs = v
+                .parse()
+                .map_err(|_| format!("Invalid RATE_LIMIT_WINDOW_SECS env var: {}", v))?;
+        }
+        // Format: "route_class=limit:window_secs,...", e.g.
+        // "health=60:1,generate=30:60".
+        if let Ok(v) = std::env::var("ROUTE_RATE_LIMITS") {
+            let mut route_rate_limits = HashMap::new();
+            for entry in v.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+                let (class, quota) = entry
+                    .split_once('=')
+                    .ok_or_else(|| format!("Invalid ROUTE_RATE_LIMITS entry: {}", entry))?;
+                let (limit, window_secs) = quota
+                    .split_once(':')
+                    .ok_or_else(|| format!("Invalid ROUTE_RATE_LIMITS entry: {}", entry))?;
+                route_rate_limits.insert(
+                    class.to_string(),
+                    RouteQuota {
+                        limit: limit
+                            .parse()
+                            .map_err(|_| format!("Invalid ROUTE_RATE_LIMITS entry: {}", entry))?,
+                        window_secs: window_secs
+                            .parse()
+                            .map_err(|_| format!("Invalid ROUTE_RATE_LIMITS entry: {}", entry))?,
+                    },
+                );
+            }
+            config.route_rate_limits = route_rate_limits;
+        }
+        if let Ok(v) = std::env::var("RATE_LIMIT_BACKEND") {
+            config.rate_limit_backend = v;
+        }
+        if let Ok(v) = std::env::var("ANONYMOUS_RATE_LIMIT") {
+            config.anonymous_rate_limit = v
+                .parse()
+                .map_err(|_| format!("Invalid ANONYMOUS_RATE_LIMIT env var: {}", v))?;
+        }
+        if let Ok(v) = std::env::var("STATIC_DIR") {
+            config.static_dir = PathBuf::from(v);
+        }
+        if let Ok(v) = std::env::var("CORS_ALLOWED_ORIGINS") {
+            config.cors_allowed_origins = v.split(',').map(|s| s.trim().to_string()).collect();
+        }
+        if let Ok(v) = std::env::var("CORS_ALLOWED_METHODS") {
+            config.cors_allowed_methods = v.split(',').map(|s| s.trim().to_string()).collect();
+        }
+        if let Ok(v) = std::env::var("CORS_ALLOWED_HEADERS") {
+            config.cors_allowed_headers = v.split(',').map(|s| s.trim().to_string()).collect();
+        }
+        if let Ok(v) = std::env::var("CORS_EXPOSE_HEADERS") {
+            config.cors_expose_headers = v.split(',').map(|s| s.trim().to_string()).collect();
+        }
+        if let Ok(v) = std::env::var("CORS_ALLOW_CREDENTIALS") {
+            config.cors_allow_credentials = v
+                .parse()
+                .map_err(|_| format!("Invalid CORS_ALLOW_CREDENTIALS env var: {}", v))?;
+        }
+        if let Ok(v) = std::env::var("CORS_REFLECT_CREDENTIALS") {
+            config.cors_reflect_credentials = v
+                .parse()
+                .map_err(|_| format!("Invalid CORS_REFLECT_CREDENTIALS env var: {}", v))?;
+        }
+        if let Ok(v) = std::env::var("CORS_MAX_AGE_SECS") {
+            config.cors_max_age_secs = v
+                .parse()
+                .map_err(|_| format!("Invalid CORS_MAX_AGE_SECS env var: {}", v))?;
+        }
+        if let Ok(v) = std::env::var("DEV_MODE") {
+            config.dev_mode = v
+                .parse()
+                .map_err(|_| format!("Invalid DEV_MODE env var: {}", v))?;
+        }
+        if let Ok(v) = std::env::var("BASE_URL") {
+            config.base_url = v;
+        }
+        if let Ok(v) = std::env::var("OIDC_ISSUER_URL") {
+            config.oidc_issuer_url = v;
+        }
+        if let Ok(v) = std::env::var("OIDC_CLIENT_ID") {
+            config.oidc_client_id = v;
+        }
+        if let Ok(v) = std::env::var("OIDC_CLIENT_SECRET") {
+            config.oidc_client_secret = v;
+        }
+        if let Ok(v) = std::env::var("OIDC_ADMIN_EMAILS") {
+            config.oidc_admin_emails = v.split(',').map(|s| s.trim().to_string()).collect();
+        }
+        if let Ok(v) = std::env::var("JWT_BEARER_ISSUER") {
+            config.jwt_bearer_issuer = v;
+        }
+        if let Ok(v) = std::env::var("JWT_BEARER_AUDIENCE") {
+            config.jwt_bearer_audience = v;
+        }
+        if let Ok(v) = std::env::var("JWT_BEARER_JWKS_URL") {
+            config.jwt_bearer_jwks_url = v;
+        }
+        if let Ok(v) = std::env::var("JWT_BEARER_RATE_LIMIT") {
+            config.jwt_bearer_rate_limit = v
+                .parse()
+                .map_err(|_| format!("Invalid JWT_BEARER_RATE_LIMIT env var: {}", v))?;
+        }
+        if let Ok(v) = std::env::var("GRPC_AUTH_URL") {
+            config.grpc_auth_url = v;
+        }
+        if let Ok(v) = std::env::var("GRPC_AUTH_FAIL_OPEN") {
+            config.grpc_auth_fail_open = v
+                .parse()
+                .map_err(|_| format!("Invalid GRPC_AUTH_FAIL_OPEN env var: {}", v))?;
+        }
+        if let Ok(v) = std::env::var("SHORTCODE_SIGNING_KEY") {
+            config.shortcode_signing_key = v;
+        }
+        if let Ok(v) = std::env::var("IMAGE_SIGNING_KEY") {
+            config.image_signing_key = v;
+        }
+        if let Ok(v) = std::env::var("STORAGE_BACKEND") {
+            config.storage_backend = v;
+        }
+        if let Ok(v) = std::env::var("S3_BUCKET") {
+            config.s3_bucket = v;
+        }
+        if let Ok(v) = std::env::var("S3_ENDPOINT") {
+            config.s3_endpoint = v;
+        }
+        if let Ok(v) = std::env::var("S3_REGION") {
+            config.s3_region = v;
+        }
+        if let Ok(v) = std::env::var("S3_ACCESS_KEY") {
+            config.s3_access_key = v;
+        }
+        if let Ok(v) = std::env::var("S3_SECRET_KEY") {
+            config.s3_secret_key = v;
+        }
+        if let Ok(v) = std::env::var("S3_PRESIGN_EXPIRY_SECS") {
+            config.s3_presign_expiry_secs = v
+                .parse()
+                .map_err(|_| format!("Invalid S3_PRESIGN_EXPIRY_SECS env var: {}", v))?;
+        }
+        if let Ok(v) = std::env::var("GEOIP_BACKEND") {
+            config.geoip_backend = v;
+        }
+        if let Ok(v) = std::env::var("EXPIRY_SWEEP_INTERVAL_SECS") {
+            config.expiry_sweep_interval_secs = v
+                .parse()
+                .map_err(|_| format!("Invalid EXPIRY_SWEEP_INTERVAL_SECS env var: {}", v))?;
+        }
+        if let Ok(v) = std::env::var("EXPIRY_POLICY") {
+            config.expiry_policy = v;
+        }
+        if let Ok(v) = std::env::var("JWT_MANAGE_TOKENS_ENABLED") {
+            config.jwt_manage_tokens_enabled = v
+                .parse()
+                .map_err(|_| format!("Invalid JWT_MANAGE_TOKENS_ENABLED env var: {}", v))?;
+        }
+        if let Ok(v) = std::env::var("JWT_MANAGE_TOKEN_DEFAULT_TTL_SECS") {
+            config.jwt_manage_token_default_ttl_secs = v.parse().map_err(|_| {
+                format!("Invalid JWT_MANAGE_TOKEN_DEFAULT_TTL_SECS env var: {}", v)
+            })?;
+        }
+        if let Ok(v) = std::env::var("KEY_ROTATION_GRACE_SECS") {
+            config.key_rotation_grace_secs = v
+                .parse()
+                .map_err(|_| format!("Invalid KEY_ROTATION_GRACE_SECS env var: {}", v))?;
+        }
+
+        Ok(config)
+    }
+}
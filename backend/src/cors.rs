@@ -0,0 +1,63 @@
+//! CORS origin-resolution policy.
+//!
+//! `main.rs` wires the resolved policy into `rocket_cors`, but the four
+//! modes an operator can land in — wide open, one fixed origin, an
+//! allowlist, or reflect-anything-with-credentials — are named here
+//! independent of that crate's builder API (and exercised through it by
+//! `tests/http_test.rs`).
+
+use crate::config::Config;
+use std::collections::HashSet;
+
+/// How `Access-Control-Allow-Origin` is derived for a given request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin {
+    /// Emit a literal `*`. Credential-less only — browsers reject `*`
+    /// alongside `Access-Control-Allow-Credentials: true`.
+    Any,
+    /// Always emit this one fixed origin, regardless of what the request
+    /// sent in its `Origin` header.
+    Single(String),
+    /// Echo the request's `Origin` header back only if it's in this set;
+    /// otherwise omit the ACAO header entirely.
+    List(HashSet<String>),
+    /// Echo back whatever `Origin` the request sent, unconditionally. Only
+    /// meaningful combined with credentials, since it's the one way to
+    /// serve an open-ended set of origins (e.g. many customer-owned
+    /// dashboards) without losing cookie-based sessions to the `*` ban.
+    Copy,
+}
+
+impl Origin {
+    /// Resolves the configured policy from `Config`. `dev_mode` or an
+    /// explicit `"*"` entry in `cors_allowed_origins` select a wide-open
+    /// mode — `Copy` if credentials are also requested and
+    /// `cors_reflect_credentials` opts in, `Any` otherwise. Anything else is
+    /// a literal allowlist, collapsed to `Single` when it names exactly one
+    /// origin.
+    pub fn from_config(config: &Config) -> Self {
+        let wildcard = config.cors_allowed_origins.iter().any(|o| o == "*");
+        if config.dev_mode || wildcard {
+            if config.cors_allow_credentials && config.cors_reflect_credentials {
+                Origin::Copy
+            } else {
+                Origin::Any
+            }
+        } else if let [only] = config.cors_allowed_origins.as_slice() {
+            Origin::Single(only.clone())
+        } else {
+            Origin::List(config.cors_allowed_origins.iter().cloned().collect())
+        }
+    }
+
+    /// Whether `Access-Control-Allow-Credentials: true` may be sent
+    /// alongside this policy without violating the `*`-with-credentials
+    /// ban.
+    pub fn allows_credentials(&self, config: &Config) -> bool {
+        match self {
+            Origin::Any => false,
+            Origin::Copy => true,
+            Origin::Single(_) | Origin::List(_) => config.cors_allow_credentials,
+        }
+    }
+}
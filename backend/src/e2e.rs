@@ -0,0 +1,104 @@
+//! End-to-end encrypted QR payloads (see `routes::generate_encrypted_qr`/
+//! `routes::decrypt_envelope`). Ephemeral x25519 key agreement plus
+//! AES-256-GCM, so a QR code's encoded content is ciphertext a mere scanner
+//! can't do anything with — only whoever holds the recipient's private key
+//! can recover the plaintext.
+//!
+//! Envelope layout, concatenated and then base64-encoded so it can be
+//! carried as ordinary QR text: `ephemeral_pubkey (32) || nonce (12) ||
+//! ciphertext||tag`. The ephemeral key pair is generated fresh per call and
+//! discarded, so the same plaintext/recipient pair never produces the same
+//! envelope twice.
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use hkdf::Hkdf;
+use sha2::Sha256;
+use x25519_dalek::{EphemeralSecret, PublicKey, StaticSecret};
+
+const PUBKEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+const HKDF_INFO: &[u8] = b"qr-service e2e envelope v1";
+
+/// Parses a base64-encoded x25519 public key, rejecting anything that isn't
+/// exactly 32 bytes once decoded.
+pub fn parse_pubkey(raw: &str) -> Result<PublicKey, String> {
+    let bytes = BASE64
+        .decode(raw)
+        .map_err(|e| format!("Invalid base64 public key: {}", e))?;
+    let bytes: [u8; PUBKEY_LEN] = bytes
+        .try_into()
+        .map_err(|_| format!("Public key must be exactly {} bytes", PUBKEY_LEN))?;
+    Ok(PublicKey::from(bytes))
+}
+
+/// Parses a base64-encoded x25519 private (scalar) key, rejecting anything
+/// that isn't exactly 32 bytes once decoded.
+pub fn parse_privkey(raw: &str) -> Result<StaticSecret, String> {
+    let bytes = BASE64
+        .decode(raw)
+        .map_err(|e| format!("Invalid base64 private key: {}", e))?;
+    let bytes: [u8; PUBKEY_LEN] = bytes
+        .try_into()
+        .map_err(|_| format!("Private key must be exactly {} bytes", PUBKEY_LEN))?;
+    Ok(StaticSecret::from(bytes))
+}
+
+/// Derives the AES-256-GCM key for one x25519 shared secret via HKDF-SHA256,
+/// the same pattern `db::DbEncryption` uses for its at-rest key — a shared
+/// secret isn't itself a uniformly random 256-bit key, so it's run through a
+/// KDF rather than used directly.
+fn derive_cipher(shared_secret: &[u8]) -> Aes256Gcm {
+    let hk = Hkdf::<Sha256>::new(None, shared_secret);
+    let mut key = [0u8; 32];
+    hk.expand(HKDF_INFO, &mut key)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    Aes256Gcm::new_from_slice(&key).expect("key is exactly 32 bytes")
+}
+
+/// Encrypts `plaintext` for `recipient_pubkey` and returns the base64
+/// envelope `open` can reverse given the matching private key.
+pub fn seal(plaintext: &[u8], recipient_pubkey: &PublicKey) -> String {
+    let ephemeral_secret = EphemeralSecret::random_from_rng(OsRng);
+    let ephemeral_pubkey = PublicKey::from(&ephemeral_secret);
+    let shared_secret = ephemeral_secret.diffie_hellman(recipient_pubkey);
+
+    let cipher = derive_cipher(shared_secret.as_bytes());
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .expect("AES-256-GCM encryption is infallible for in-memory buffers");
+
+    let mut envelope = Vec::with_capacity(PUBKEY_LEN + NONCE_LEN + ciphertext.len());
+    envelope.extend_from_slice(ephemeral_pubkey.as_bytes());
+    envelope.extend_from_slice(&nonce);
+    envelope.extend_from_slice(&ciphertext);
+    BASE64.encode(envelope)
+}
+
+/// Reverses `seal` given the recipient's private key, recovering the
+/// original plaintext.
+pub fn open(envelope_b64: &str, recipient_privkey: &StaticSecret) -> Result<Vec<u8>, String> {
+    let envelope = BASE64
+        .decode(envelope_b64)
+        .map_err(|e| format!("Invalid base64 envelope: {}", e))?;
+    if envelope.len() < PUBKEY_LEN + NONCE_LEN {
+        return Err("Envelope is shorter than a pubkey+nonce".to_string());
+    }
+
+    let (ephemeral_pubkey_bytes, rest) = envelope.split_at(PUBKEY_LEN);
+    let (nonce_bytes, ciphertext) = rest.split_at(NONCE_LEN);
+    let ephemeral_pubkey = PublicKey::from(
+        <[u8; PUBKEY_LEN]>::try_from(ephemeral_pubkey_bytes)
+            .expect("split_at(PUBKEY_LEN) guarantees this length"),
+    );
+
+    let shared_secret = recipient_privkey.diffie_hellman(&ephemeral_pubkey);
+    let cipher = derive_cipher(shared_secret.as_bytes());
+
+    cipher
+        .decrypt(Nonce::from_slice(nonce_bytes), ciphertext)
+        .map_err(|_| "Failed to decrypt envelope (wrong key?)".to_string())
+}
@@ -0,0 +1,181 @@
+//! RS256-signed "manage tokens" for tracked QR codes. Gated behind
+//! `Config::jwt_manage_tokens_enabled` — when on, `POST /api/v1/qr/tracked`
+//! mints a JWT (claims: `id`, `short_code`, `exp`) instead of relying solely
+//! on the existing `Principal`-based ownership check, so a holder (even a
+//! third party, via the published JWKS) can prove ownership statelessly.
+//! When disabled, `routes::delete_tracked_qr` works exactly as before.
+//!
+//! The keypair is generated fresh at process startup and lives only for the
+//! process's lifetime, same as the other signing secrets this service holds
+//! in memory (`Config::shortcode_signing_key`/`image_signing_key`).
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use rsa::{BigUint, Pkcs1v15Sign, RsaPrivateKey, RsaPublicKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// A manage-token JWT is only ever good for one thing: proving the holder
+/// created (or was handed) a particular tracked QR. The stats, scan-history
+/// and delete routes all check for this same scope, mirroring `AuthenticatedKey`'s
+/// all-or-nothing `keys:admin` scope rather than minting a distinct scope
+/// per route for a token that already proves full ownership.
+pub const MANAGE_SCOPE: &str = "tracked_qr:manage";
+
+/// How far a verifier's clock is allowed to drift from the signer's before a
+/// token is rejected as not-yet-valid or expired.
+const CLOCK_SKEW_SECS: i64 = 60;
+
+#[derive(Serialize, Deserialize)]
+struct ManageTokenClaims {
+    id: String,
+    short_code: String,
+    scope: String,
+    iat: i64,
+    exp: i64,
+}
+
+pub struct JwtManageKeys {
+    private_key: RsaPrivateKey,
+    public_key: RsaPublicKey,
+    /// Fingerprint of `public_key`, published as the JWK's `kid` and stamped
+    /// into every token's header so a verifier holding multiple JWKs (e.g.
+    /// during key rotation) can pick the right one before attempting to
+    /// verify the signature.
+    kid: String,
+}
+
+impl JwtManageKeys {
+    /// Generates a fresh 2048-bit RSA keypair.
+    pub fn generate() -> Self {
+        let mut rng = rand::thread_rng();
+        let private_key =
+            RsaPrivateKey::new(&mut rng, 2048).expect("Failed to generate RSA manage-token keypair");
+        let public_key = RsaPublicKey::from(&private_key);
+        let kid = key_id_for(&public_key);
+        JwtManageKeys {
+            private_key,
+            public_key,
+            kid,
+        }
+    }
+
+    /// Mints a manage-token JWT for a just-created tracked QR, expiring at
+    /// `exp`.
+    pub fn issue(&self, id: &str, short_code: &str, exp: chrono::DateTime<chrono::Utc>) -> String {
+        let header = serde_json::json!({"alg": "RS256", "typ": "JWT", "kid": self.kid});
+        let claims = ManageTokenClaims {
+            id: id.to_string(),
+            short_code: short_code.to_string(),
+            scope: MANAGE_SCOPE.to_string(),
+            iat: chrono::Utc::now().timestamp(),
+            exp: exp.timestamp(),
+        };
+        let header_b64 = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&header).unwrap());
+        let claims_b64 = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&claims).unwrap());
+        let signing_input = format!("{}.{}", header_b64, claims_b64);
+
+        let digest = Sha256::digest(signing_input.as_bytes());
+        let signature = self
+            .private_key
+            .sign(Pkcs1v15Sign::new::<Sha256>(), &digest)
+            .expect("RSA signing failed");
+        let sig_b64 = URL_SAFE_NO_PAD.encode(signature);
+
+        format!("{}.{}", signing_input, sig_b64)
+    }
+
+    /// Verifies `token`'s signature and that its `id`/`short_code`/`scope`
+    /// claims match what the caller expects, and that `iat`/`exp` are
+    /// sane within `CLOCK_SKEW_SECS`. Reconstructs the RSA public key from
+    /// the stored `n`/`e` (the same values published at
+    /// `/.well-known/jwks.json`) rather than reusing the in-memory
+    /// `RsaPublicKey` directly, so this matches exactly what an independent
+    /// verifier fetching the JWKS would do.
+    pub fn verify(
+        &self,
+        token: &str,
+        expected_id: &str,
+        expected_short_code: &str,
+        expected_scope: &str,
+    ) -> Result<(), &'static str> {
+        let mut parts = token.split('.');
+        let (header_b64, claims_b64, sig_b64) =
+            match (parts.next(), parts.next(), parts.next(), parts.next()) {
+                (Some(h), Some(c), Some(s), None) => (h, c, s),
+                _ => return Err("Malformed token"),
+            };
+
+        let header_bytes = URL_SAFE_NO_PAD
+            .decode(header_b64)
+            .map_err(|_| "Malformed token header")?;
+        let header: serde_json::Value =
+            serde_json::from_slice(&header_bytes).map_err(|_| "Malformed token header")?;
+        let kid = header.get("kid").and_then(|v| v.as_str());
+        if kid != Some(self.kid.as_str()) {
+            return Err("Unknown signing key");
+        }
+
+        let claims_bytes = URL_SAFE_NO_PAD
+            .decode(claims_b64)
+            .map_err(|_| "Malformed token claims")?;
+        let sig_bytes = URL_SAFE_NO_PAD
+            .decode(sig_b64)
+            .map_err(|_| "Malformed token signature")?;
+
+        let public_key = RsaPublicKey::new(
+            BigUint::from_bytes_be(&self.public_key.n().to_bytes_be()),
+            BigUint::from_bytes_be(&self.public_key.e().to_bytes_be()),
+        )
+        .map_err(|_| "Invalid public key")?;
+
+        let signing_input = format!("{}.{}", header_b64, claims_b64);
+        let digest = Sha256::digest(signing_input.as_bytes());
+        public_key
+            .verify(Pkcs1v15Sign::new::<Sha256>(), &digest, &sig_bytes)
+            .map_err(|_| "Invalid signature")?;
+
+        let claims: ManageTokenClaims =
+            serde_json::from_slice(&claims_bytes).map_err(|_| "Malformed token claims")?;
+
+        if claims.id != expected_id || claims.short_code != expected_short_code {
+            return Err("Token does not match this tracked QR");
+        }
+        if claims.scope != expected_scope {
+            return Err("Token scope does not permit this action");
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        if claims.iat > now + CLOCK_SKEW_SECS {
+            return Err("Token is not yet valid");
+        }
+        if claims.exp < now - CLOCK_SKEW_SECS {
+            return Err("Token has expired");
+        }
+
+        Ok(())
+    }
+
+    /// The public key as a JWK set, for `/.well-known/jwks.json`.
+    pub fn jwks(&self) -> serde_json::Value {
+        serde_json::json!({
+            "keys": [{
+                "kty": "RSA",
+                "alg": "RS256",
+                "use": "sig",
+                "kid": self.kid,
+                "n": URL_SAFE_NO_PAD.encode(self.public_key.n().to_bytes_be()),
+                "e": URL_SAFE_NO_PAD.encode(self.public_key.e().to_bytes_be()),
+            }]
+        })
+    }
+}
+
+/// Derives a stable `kid` from a public key's modulus and exponent, so the
+/// same key always publishes the same `kid` (e.g. across `jwks()` calls).
+fn key_id_for(public_key: &RsaPublicKey) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(public_key.n().to_bytes_be());
+    hasher.update(public_key.e().to_bytes_be());
+    let digest = hasher.finalize();
+    digest[..8].iter().map(|b| format!("{:02x}", b)).collect()
+}
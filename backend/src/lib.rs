@@ -2,8 +2,18 @@
 extern crate rocket;
 
 pub mod auth;
+pub mod config;
+pub mod cors;
 pub mod db;
+pub mod geoip;
+pub mod grpc_auth;
+pub mod jwt_manage;
 pub mod models;
+pub mod oidc;
+pub mod openapi;
 pub mod qr;
 pub mod rate_limit;
+pub mod reaper;
 pub mod routes;
+pub mod storage;
+pub mod ua;
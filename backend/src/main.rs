@@ -2,11 +2,24 @@
 extern crate rocket;
 
 mod auth;
+mod config;
+mod cors;
 mod db;
+mod e2e;
+mod geoip;
+mod grpc_auth;
+mod jwt_manage;
 mod models;
+mod oidc;
+mod openapi;
 mod qr;
 mod rate_limit;
+mod reaper;
 mod routes;
+mod storage;
+mod ua;
+
+use config::Config;
 
 use rocket::fairing::AdHoc;
 use rocket::fs::{FileServer, Options};
@@ -14,71 +27,228 @@ use rocket_cors::{AllowedOrigins, CorsOptions};
 use std::path::PathBuf;
 use std::time::Duration;
 
+#[cfg(feature = "embed-frontend")]
+mod embedded {
+    use include_dir::{include_dir, Dir};
+    use rocket::http::ContentType;
+    use std::path::PathBuf;
+
+    pub static FRONTEND_DIST: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/../frontend/dist");
+
+    /// Serves a file baked into the binary at compile time, guessing its
+    /// `ContentType` from the extension. Falls back to `index.html` for
+    /// client-side routes so deep links still load the SPA.
+    #[get("/<path..>", rank = 15)]
+    pub fn serve(path: PathBuf) -> Option<(ContentType, &'static [u8])> {
+        if path.as_os_str().is_empty() {
+            return index();
+        }
+        match FRONTEND_DIST.get_file(&path) {
+            Some(file) => {
+                let ct = path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .and_then(ContentType::from_extension)
+                    .unwrap_or(ContentType::Bytes);
+                Some((ct, file.contents()))
+            }
+            None => index(),
+        }
+    }
+
+    fn index() -> Option<(ContentType, &'static [u8])> {
+        FRONTEND_DIST
+            .get_file("index.html")
+            .map(|file| (ContentType::HTML, file.contents()))
+    }
+}
+
 #[launch]
 fn rocket() -> _ {
     // Load .env file if present (silently ignore if missing)
     let _ = dotenvy::dotenv();
 
-    // Rate limit window: default 60 seconds, configurable via RATE_LIMIT_WINDOW_SECS
-    let window_secs: u64 = std::env::var("RATE_LIMIT_WINDOW_SECS")
-        .ok()
-        .and_then(|v| v.parse().ok())
-        .unwrap_or(60);
-    let limiter = rate_limit::RateLimiter::new(Duration::from_secs(window_secs));
+    // Unified config: config.toml (if present) with env-var overrides. Fails
+    // fast with a clear message rather than limping along on bad settings.
+    let config = Config::load().unwrap_or_else(|e| {
+        eprintln!("❌ Failed to load configuration: {}", e);
+        std::process::exit(1);
+    });
 
-    // Frontend static files directory (default: ../frontend/dist relative to CWD)
-    let static_dir: PathBuf = std::env::var("STATIC_DIR")
-        .map(PathBuf::from)
-        .unwrap_or_else(|_| PathBuf::from("../frontend/dist"));
+    // Downstream modules (db::init_db, routes::create_tracked_qr) still read
+    // these two via env var, so bridge the resolved config back into the
+    // process environment for them.
+    std::env::set_var("DATABASE_PATH", &config.database_path);
+    std::env::set_var("BASE_URL", &config.base_url);
+    std::env::set_var("ROCKET_ADDRESS", &config.address);
+    std::env::set_var("ROCKET_PORT", config.port.to_string());
 
+    let limiter = rate_limit::RateLimiter::with_store(
+        Duration::from_secs(config.rate_limit_window_secs),
+        rate_limit::store_from_config(&config),
+    );
+
+    // Frontend static files directory.
+    // Unused when the `embed-frontend` feature bakes the dist folder in instead.
+    #[cfg(not(feature = "embed-frontend"))]
+    let static_dir: PathBuf = config.static_dir.clone();
+
+    // Dev mode, or an explicit "*" entry in `cors_allowed_origins`, are the
+    // only ways to get the wide-open behavior; everything else requires an
+    // explicit origin allowlist so in-browser agents hitting `/qr/*/image`
+    // or `/qr/decode` don't need a proxy just to see CORS headers at all.
+    // See `cors::Origin` for what each of these modes means.
+    let cors_origin = cors::Origin::from_config(&config);
+    let cors_allow_credentials = cors_origin.allows_credentials(&config);
+    let allowed_origins = match &cors_origin {
+        cors::Origin::Any | cors::Origin::Copy => AllowedOrigins::all(),
+        cors::Origin::Single(origin) => AllowedOrigins::some_exact(&[origin.clone()]),
+        cors::Origin::List(origins) => {
+            AllowedOrigins::some_exact(&origins.iter().cloned().collect::<Vec<_>>())
+        }
+    };
+    let allowed_methods = config
+        .cors_allowed_methods
+        .iter()
+        .map(|m| {
+            m.parse()
+                .unwrap_or_else(|_| panic!("Invalid CORS method in config: {}", m))
+        })
+        .collect::<std::collections::HashSet<rocket_cors::Method>>();
     let cors = CorsOptions::default()
-        .allowed_origins(AllowedOrigins::all())
+        .allowed_origins(allowed_origins)
+        .allowed_methods(allowed_methods)
+        .allowed_headers(rocket_cors::AllowedHeaders::some(
+            &config
+                .cors_allowed_headers
+                .iter()
+                .map(|h| h.as_str())
+                .collect::<Vec<_>>(),
+        ))
+        .expose_headers(config.cors_expose_headers.iter().cloned().collect())
+        .allow_credentials(cors_allow_credentials)
+        .max_age(Some(config.cors_max_age_secs as usize))
         .to_cors()
         .expect("CORS configuration failed");
 
     let mut build = rocket::build()
         .attach(cors)
         .attach(rate_limit::RateLimitHeaders)
+        // `rocket_cors`'s fairing only attaches CORS headers to responses
+        // from routes that actually matched; without these, a preflight
+        // `OPTIONS` request against a route that doesn't itself handle
+        // `OPTIONS` 404s before the fairing gets a chance to answer it.
+        .mount("/", rocket_cors::catch_all_options_routes())
         .attach(AdHoc::on_ignite("Database", |rocket| async {
-            let db = db::init_db().expect("Failed to initialize database");
+            let (db_path, pool_size, connection_timeout_secs, busy_timeout_ms, sweep_interval_secs, expiry_policy) = {
+                let config = rocket.state::<Config>().expect("Config must be managed before the Database fairing runs");
+                (
+                    config.database_path.clone(),
+                    config.db_pool_size,
+                    config.db_connection_timeout_secs,
+                    config.db_busy_timeout_ms,
+                    config.expiry_sweep_interval_secs,
+                    config.expiry_policy.clone(),
+                )
+            };
+            let db = db::init_db_with_config(&db_path, pool_size, connection_timeout_secs, busy_timeout_ms)
+                .expect("Failed to initialize database pool");
+            reaper::spawn(db.clone(), sweep_interval_secs, expiry_policy);
             rocket.manage(db)
         }))
         .manage(limiter)
+        .manage(auth::JwksCache::new())
+        .manage(oidc::OidcState::new())
+        .manage(db::encryption_from_env())
+        .manage(geoip::from_config(&config))
+        .manage(std::sync::Arc::new(jwt_manage::JwtManageKeys::generate()))
+        .manage(config)
+        .attach(AdHoc::on_ignite("Storage", |rocket| async {
+            let config = rocket
+                .state::<Config>()
+                .expect("Config must be managed before the Storage fairing runs")
+                .clone();
+            let db = rocket
+                .state::<db::DbPool>()
+                .expect("Database must be initialized before the Storage fairing runs")
+                .clone();
+            let enc = rocket
+                .state::<db::DbEncryption>()
+                .expect("DbEncryption must be managed before the Storage fairing runs")
+                .clone();
+            let backend = storage::from_config(&config, db, enc);
+            rocket.manage(backend)
+        }))
+        .mount("/", routes![oidc::login, oidc::callback])
+        .mount("/", routes![routes::jwks])
         .mount(
             "/api/v1",
             routes![
                 routes::health,
                 routes::openapi,
                 routes::generate_qr,
+                routes::generate_qr_form,
                 routes::decode_qr,
+                routes::decode_qr_multipart,
                 routes::batch_generate,
+                routes::generate_encrypted_qr,
+                routes::decrypt_envelope,
                 routes::generate_from_template,
+                routes::generate_from_template_form,
                 routes::get_history,
                 routes::get_qr_by_id,
                 routes::get_qr_image,
+                routes::get_qr_image_public,
+                routes::sign_qr_image,
                 routes::delete_qr,
                 routes::create_tracked_qr,
+                routes::create_tracked_qr_batch,
                 routes::list_tracked_qr,
                 routes::get_tracked_qr_stats,
+                routes::get_tracked_qr_scans,
+                routes::get_tracked_qr_timeseries,
                 routes::delete_tracked_qr,
                 routes::list_keys,
                 routes::create_key,
                 routes::delete_key,
+                routes::rotate_key,
             ],
         )
-        .mount("/", routes![routes::redirect_short_url]);
-
-    // Serve frontend static files if the directory exists
-    if static_dir.is_dir() {
-        println!("📦 Serving frontend from: {}", static_dir.display());
-        build = build
-            .mount("/", FileServer::new(&static_dir, Options::Index))
-            .mount("/", routes![routes::spa_fallback]);
-    } else {
-        println!(
-            "⚠️  Frontend directory not found: {} (API-only mode)",
-            static_dir.display()
-        );
+        .mount("/", routes![routes::redirect_short_url])
+        .register("/", catchers![routes::rate_limited]);
+
+    // Interactive API docs (Swagger UI) — can be disabled for API-only
+    // deployments that don't want to expose a browsable explorer.
+    let enable_docs = std::env::var("ENABLE_DOCS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(true);
+    if enable_docs {
+        build = build.mount("/api/v1", routes![routes::docs_ui]);
+    }
+
+    // Serve the frontend. With the `embed-frontend` feature, the dist
+    // directory is baked into the binary at compile time so the service
+    // runs standalone with no on-disk static assets required. Otherwise,
+    // fall back to serving `STATIC_DIR` from disk at runtime.
+    #[cfg(feature = "embed-frontend")]
+    {
+        build = build.mount("/", routes![embedded::serve]);
+        println!("📦 Serving embedded frontend");
+    }
+    #[cfg(not(feature = "embed-frontend"))]
+    {
+        if static_dir.is_dir() {
+            println!("📦 Serving frontend from: {}", static_dir.display());
+            build = build
+                .mount("/", FileServer::new(&static_dir, Options::Index))
+                .mount("/", routes![routes::spa_fallback]);
+        } else {
+            println!(
+                "⚠️  Frontend directory not found: {} (API-only mode)",
+                static_dir.display()
+            );
+        }
     }
 
     build
@@ -1,6 +1,7 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct GenerateRequest {
     pub data: String,
     #[serde(default = "default_format")]
@@ -15,11 +16,117 @@ pub struct GenerateRequest {
     pub error_correction: String,
     #[serde(default = "default_style")]
     pub style: String,
+    /// Smooth `dots`/`rounded` module edges instead of a hard pixel cutoff.
+    #[serde(default)]
+    pub antialias: bool,
+    /// Explicit QR version (e.g. `"5"` for normal, `"M2"` for Micro QR).
+    /// Omit to auto-pick the smallest version that fits `data`.
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Swap dark/light modules in `format: "text"` output, for terminals
+    /// with a dark background. No effect on `png`/`svg`.
+    #[serde(default)]
+    pub invert: bool,
+    /// Include the blank quiet-zone border in `format: "text"` output.
+    #[serde(default = "default_true")]
+    pub quiet_zone: bool,
+    /// A logo image to overlay at the center of the code, as a base64 string
+    /// (data URI or raw). Forces `error_correction` to `"H"` regardless of
+    /// the field above, so the overlay doesn't make the code unscannable.
+    /// No effect on `format: "text"`.
+    #[serde(default)]
+    pub logo: Option<String>,
+    /// Percentage of the code's size the logo should occupy, 5-40.
+    #[serde(default = "default_logo_size")]
+    pub logo_size: u8,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Request for `/qr/generate/encrypted` — same rendering knobs as
+/// [`GenerateRequest`], but `data` is sealed for `recipient_pubkey` (see
+/// `e2e::seal`) before it's encoded into the QR image, so the image itself
+/// carries ciphertext rather than plaintext.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct EncryptedGenerateRequest {
+    pub data: String,
+    /// Recipient's 32-byte x25519 public key, base64-encoded.
+    pub recipient_pubkey: String,
+    #[serde(default = "default_format")]
+    pub format: String,
+    #[serde(default = "default_size")]
+    pub size: u32,
+    #[serde(default = "default_fg_color")]
+    pub fg_color: String,
+    #[serde(default = "default_bg_color")]
+    pub bg_color: String,
+    #[serde(default = "default_error_correction")]
+    pub error_correction: String,
+    #[serde(default = "default_style")]
+    pub style: String,
+}
+
+impl EncryptedGenerateRequest {
+    /// Builds the plain `GenerateRequest` that actually renders the QR
+    /// image, substituting `data` with the already-sealed `envelope` — the
+    /// encrypted path only differs from the plaintext one in what gets
+    /// encoded, not in how it's rendered.
+    pub(crate) fn into_generate_request(self, envelope: String) -> GenerateRequest {
+        GenerateRequest {
+            data: envelope,
+            format: self.format,
+            size: self.size,
+            fg_color: self.fg_color,
+            bg_color: self.bg_color,
+            error_correction: self.error_correction,
+            style: self.style,
+            antialias: false,
+            version: None,
+            invert: false,
+            quiet_zone: default_true(),
+            logo: None,
+            logo_size: default_logo_size(),
+        }
+    }
+}
+
+/// Request for `/qr/decrypt` — the envelope produced by `/qr/generate/encrypted`
+/// (however it was obtained, e.g. scanning the QR image and base64-decoding
+/// it wasn't necessary since `data` in `DecodeResponse` is already the raw
+/// envelope string) plus the recipient's private key to open it.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DecryptRequest {
+    /// Base64 envelope: `ephemeral_pubkey (32) || nonce (12) || ciphertext||tag`.
+    pub envelope: String,
+    /// Recipient's 32-byte x25519 private key, base64-encoded.
+    pub recipient_privkey: String,
+}
+
+/// One `/qr/batch` item's operation. Untagged so the existing bare
+/// `GenerateRequest` wire shape (no discriminant field) keeps working —
+/// `Track` is tried first since `CreateTrackedQrRequest::target_url` has no
+/// default, so a plain generate item (no `target_url`) falls through to it.
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum BatchChainItem {
+    Track(CreateTrackedQrRequest),
+    Generate(GenerateRequest),
+}
+
+/// A `/qr/batch` item, optionally named so a later item can reference its
+/// result. See `routes::resolve_batch_ref`.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct BatchChainRequestItem {
+    /// Name this item's output so later items can reference it as
+    /// `"#ref:<id>.<field>"` in a string field (e.g. a `track` item's
+    /// `short_url` fed into a later `generate` item's `data`).
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(flatten)]
+    pub item: BatchChainItem,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct BatchGenerateRequest {
-    pub items: Vec<GenerateRequest>,
+    pub items: Vec<BatchChainRequestItem>,
 }
 
 // Typed template structs — kept for future migration from serde_json::Value
@@ -52,6 +159,26 @@ pub struct VCardTemplateRequest {
     pub title: Option<String>,
     #[serde(default)]
     pub url: Option<String>,
+    #[serde(default = "default_vcard_version")]
+    pub vcard_version: String,
+    #[serde(default = "default_format")]
+    pub format: String,
+    #[serde(default = "default_size")]
+    pub size: u32,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MeCardTemplateRequest {
+    pub name: String,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub phone: Option<String>,
+    #[serde(default)]
+    pub org: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
     #[serde(default = "default_format")]
     pub format: String,
     #[serde(default = "default_size")]
@@ -74,6 +201,59 @@ pub struct UrlTemplateRequest {
     pub size: u32,
 }
 
+#[allow(dead_code)]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GeoTemplateRequest {
+    pub lat: f64,
+    pub lon: f64,
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default = "default_format")]
+    pub format: String,
+    #[serde(default = "default_size")]
+    pub size: u32,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SmsTemplateRequest {
+    pub number: String,
+    #[serde(default)]
+    pub body: String,
+    #[serde(default = "default_format")]
+    pub format: String,
+    #[serde(default = "default_size")]
+    pub size: u32,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MailtoTemplateRequest {
+    pub address: String,
+    #[serde(default)]
+    pub subject: Option<String>,
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default = "default_format")]
+    pub format: String,
+    #[serde(default = "default_size")]
+    pub size: u32,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CalendarTemplateRequest {
+    pub summary: String,
+    pub start: String,
+    pub end: String,
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(default = "default_format")]
+    pub format: String,
+    #[serde(default = "default_size")]
+    pub size: u32,
+}
+
 #[allow(dead_code)]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -82,39 +262,119 @@ pub enum TemplateRequest {
     Wifi(WifiTemplateRequest),
     #[serde(rename = "vcard")]
     VCard(VCardTemplateRequest),
+    #[serde(rename = "mecard")]
+    MeCard(MeCardTemplateRequest),
     #[serde(rename = "url")]
     Url(UrlTemplateRequest),
+    #[serde(rename = "geo")]
+    Geo(GeoTemplateRequest),
+    #[serde(rename = "sms")]
+    Sms(SmsTemplateRequest),
+    #[serde(rename = "mailto")]
+    Mailto(MailtoTemplateRequest),
+    #[serde(rename = "calendar")]
+    Calendar(CalendarTemplateRequest),
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct QrResponse {
-    pub image_base64: String,
-    pub share_url: String,
+    pub id: String,
+    pub data: String,
     pub format: String,
     pub size: u32,
-    pub data: String,
+    pub image_base64: String,
+    /// A directly-fetchable presigned URL, present when `Config::storage_backend`
+    /// is `"s3"` (see `StorageBackend::public_url`); absent for the default
+    /// sqlite backend, which has nothing externally fetchable to offer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_url: Option<String>,
+    pub created_at: String,
 }
 
-#[derive(Debug, Serialize)]
+/// Outcome of one item in a `/qr/batch` request. Kept in request order so a
+/// client can zip `results` back up against the `items` it sent, rather than
+/// silently dropping the items that failed to generate.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum BatchItemResult {
+    Success(QrResponse),
+    /// A `track` item (see `BatchChainItem`) that created a tracked short URL.
+    Tracked(TrackedQrResponse),
+    Error {
+        index: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        error: String,
+        code: String,
+    },
+    /// An item whose `"#ref:<id>.<field>"` could not be resolved — the
+    /// referenced item errored or was skipped, no earlier item has that id,
+    /// its output has no string field of that name, or the ref is malformed —
+    /// so this one never ran.
+    Skipped {
+        index: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        error: String,
+        code: String,
+    },
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct BatchQrResponse {
-    pub items: Vec<QrResponse>,
+    pub results: Vec<BatchItemResult>,
     pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
 }
 
-#[derive(Debug, Serialize)]
+/// One QR symbol detected in a decoded image.
+#[derive(Debug, Serialize, Clone, JsonSchema)]
+pub struct DecodedQr {
+    pub data: String,
+    /// `data` classified by its well-known prefix (`WIFI:`, `BEGIN:VCARD`,
+    /// `mailto:`, ...), so clients get structured fields alongside the raw
+    /// text. See `qr::classify_content`.
+    pub content: crate::qr::QrContent,
+    /// Corners of the detected symbol in the source image, in pixel
+    /// coordinates, as reported by the finder-pattern/perspective pipeline.
+    pub bounding_box: [(i32, i32); 4],
+    pub version: String,
+    pub ec_level: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct DecodeResponse {
+    /// Payload of the first detected symbol (kept for existing callers that
+    /// only expect one QR code per image).
     pub data: String,
     pub format: String,
+    /// Every symbol detected in the image, in case it contains more than one.
+    pub results: Vec<DecodedQr>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct CreateKeyRequest {
     pub name: String,
     #[serde(default = "default_rate_limit")]
     pub rate_limit: i64,
+    /// Scopes this key is restricted to — `Action::as_str` names like
+    /// `generate`, `tracked.create`, `keys.manage`, or `*` for every action
+    /// (the old colon-style names like `qr:generate`/`keys:admin` are still
+    /// accepted for compatibility — see `Action::parse`). Omit or leave empty
+    /// for an unrestricted (legacy-style) key — see `AuthenticatedKey::has_scope`.
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    /// RFC3339 timestamp before which the key is not yet valid. Omit for no
+    /// lower bound.
+    #[serde(default)]
+    pub valid_from: Option<String>,
+    /// RFC3339 timestamp after which the key is expired. Omit for no expiry.
+    #[serde(default)]
+    pub valid_until: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct KeyResponse {
     pub id: String,
     pub name: String,
@@ -124,16 +384,19 @@ pub struct KeyResponse {
     pub requests_count: i64,
     pub rate_limit: i64,
     pub active: bool,
+    pub scopes: Vec<String>,
+    pub valid_from: Option<String>,
+    pub valid_until: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct ApiError {
     pub error: String,
     pub code: String,
     pub status: u16,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct HealthResponse {
     pub status: String,
     pub version: String,
@@ -162,13 +425,23 @@ fn default_style() -> String {
 fn default_wifi_encryption() -> String {
     "WPA2".to_string()
 }
+#[allow(dead_code)]
+fn default_vcard_version() -> String {
+    "3.0".to_string()
+}
 fn default_rate_limit() -> i64 {
     100
 }
+fn default_true() -> bool {
+    true
+}
+fn default_logo_size() -> u8 {
+    20
+}
 
 // ============ Tracked QR / Short URLs ============
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct CreateTrackedQrRequest {
     pub target_url: String,
     #[serde(default = "default_format")]
@@ -183,13 +456,21 @@ pub struct CreateTrackedQrRequest {
     pub error_correction: String,
     #[serde(default = "default_style")]
     pub style: String,
+    #[serde(default)]
+    pub antialias: bool,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub invert: bool,
+    #[serde(default = "default_true")]
+    pub quiet_zone: bool,
     /// Optional custom short code (auto-generated if omitted)
     pub short_code: Option<String>,
     /// Optional expiry as ISO-8601 timestamp
     pub expires_at: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct TrackedQrResponse {
     pub id: String,
     pub qr_id: String,
@@ -200,9 +481,15 @@ pub struct TrackedQrResponse {
     pub expires_at: Option<String>,
     pub created_at: String,
     pub qr: QrResponse,
+    /// Signed JWT proving ownership of this tracked QR, present only when
+    /// `Config::jwt_manage_tokens_enabled` is on (see `jwt_manage`). Pass it
+    /// as `Authorization: Bearer <token>` to `DELETE` this QR without an API
+    /// key/session.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manage_token: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct ScanEventResponse {
     pub id: String,
     pub scanned_at: String,
@@ -210,7 +497,7 @@ pub struct ScanEventResponse {
     pub referrer: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct TrackedQrStatsResponse {
     pub id: String,
     pub short_code: String,
@@ -219,9 +506,27 @@ pub struct TrackedQrStatsResponse {
     pub expires_at: Option<String>,
     pub created_at: String,
     pub recent_scans: Vec<ScanEventResponse>,
+    /// Opaque cursor (a scan `id`) to pass as `?before=` to fetch the next,
+    /// older page of `recent_scans`. `None` once there's nothing older left.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+/// Full, dedicated scan-event history for a tracked QR code (`GET
+/// /qr/tracked/{id}/scans`), as opposed to `TrackedQrStatsResponse`'s
+/// `recent_scans`, which exists for the stats dashboard and is capped at
+/// `RECENT_SCANS_MAX_LIMIT` per page.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ScanHistoryResponse {
+    pub scans: Vec<ScanEventResponse>,
+    /// Opaque cursor encoding the `(scanned_at, rowid)` of the last
+    /// returned event; pass as `?after=` to fetch the next, older page.
+    /// `None` once there's nothing older left.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct TrackedQrListItem {
     pub id: String,
     pub short_code: String,
@@ -231,8 +536,101 @@ pub struct TrackedQrListItem {
     pub created_at: String,
 }
 
-#[derive(Debug, Serialize)]
+/// One entry in `TrackedQrFacets::top_scanned`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct TrackedQrTopItem {
+    pub short_code: String,
+    pub scan_count: i64,
+}
+
+/// Aggregates over the same filtered set `TrackedQrListResponse::items` was
+/// paginated from, so a dashboard can render totals without a second
+/// unfiltered round trip.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct TrackedQrFacets {
+    pub total_scans: i64,
+    pub distinct_short_codes: i64,
+    pub top_scanned: Vec<TrackedQrTopItem>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct TrackedQrListResponse {
     pub items: Vec<TrackedQrListItem>,
     pub total: usize,
+    pub facets: TrackedQrFacets,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct BatchCreateTrackedQrRequest {
+    pub items: Vec<CreateTrackedQrRequest>,
+}
+
+/// Outcome of one item in a `/qr/tracked/batch` request, mirroring
+/// `BatchItemResult` for the plain `/qr/batch` endpoint.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum TrackedQrBatchItemResult {
+    Success(TrackedQrResponse),
+    Error {
+        index: usize,
+        error: String,
+        code: String,
+    },
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct BatchTrackedQrResponse {
+    pub results: Vec<TrackedQrBatchItemResult>,
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+/// One point in a `/qr/tracked/<id>/stats/timeseries` series.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ScanTimeseriesBucket {
+    /// `YYYY-MM-DD` for a `day` bucket, `YYYY-MM-DDTHH:00:00` for `hour`.
+    pub bucket: String,
+    pub count: i64,
+}
+
+/// One row of a top-N breakdown (by country, device, or referrer).
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ScanBreakdownItem {
+    pub key: String,
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct TrackedQrTimeseriesResponse {
+    pub id: String,
+    pub bucket: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub series: Vec<ScanTimeseriesBucket>,
+    /// Scans classified as bot/crawler traffic over the same window,
+    /// counted separately so they aren't folded into `series`/breakdowns.
+    pub bot_count: i64,
+    pub by_country: Vec<ScanBreakdownItem>,
+    pub by_device: Vec<ScanBreakdownItem>,
+    pub by_referrer: Vec<ScanBreakdownItem>,
+}
+
+// ============ Public Image Links ============
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SignImageRequest {
+    /// How long the minted link stays valid for, in seconds.
+    #[serde(default = "default_image_link_ttl_secs")]
+    pub ttl_secs: i64,
+}
+
+fn default_image_link_ttl_secs() -> i64 {
+    3600
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SignedImageUrlResponse {
+    pub url: String,
+    pub expires_at: i64,
 }
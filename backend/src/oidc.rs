@@ -0,0 +1,357 @@
+//! OIDC/OAuth2 authorization-code login for the management dashboard.
+//!
+//! API keys (see `auth.rs`) authenticate machines; this module adds a human
+//! login so a browser session can reach the tracked-QR analytics and key
+//! admin routes without minting an API key. Disabled entirely when
+//! `Config::oidc_issuer_url` is empty.
+
+use crate::config::Config;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rocket::http::{Cookie, CookieJar, Status};
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::response::Redirect;
+use rocket::State;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const PENDING_TTL: Duration = Duration::from_secs(600);
+const SESSION_COOKIE: &str = "session_id";
+
+struct PendingAuth {
+    pkce_verifier: String,
+    nonce: String,
+    created_at: Instant,
+}
+
+#[derive(Clone)]
+struct Session {
+    subject: String,
+    email: Option<String>,
+}
+
+/// In-memory store for state/PKCE pairs awaiting their callback and for
+/// sessions issued after a successful login. Mirrors the bucket map in
+/// `rate_limit::RateLimiter` rather than pulling in a separate session store.
+pub struct OidcState {
+    pending: Mutex<HashMap<String, PendingAuth>>,
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+impl Default for OidcState {
+    fn default() -> Self {
+        OidcState {
+            pending: Mutex::new(HashMap::new()),
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl OidcState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a pending authorization request and returns its `state`
+    /// token, pruning anything older than `PENDING_TTL` along the way.
+    fn begin(&self, pkce_verifier: String, nonce: String) -> String {
+        let state_token = random_token(32);
+        let now = Instant::now();
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.retain(|_, p| now.duration_since(p.created_at) < PENDING_TTL);
+        pending.insert(
+            state_token.clone(),
+            PendingAuth {
+                pkce_verifier,
+                nonce,
+                created_at: now,
+            },
+        );
+        state_token
+    }
+
+    /// Consumes (and removes) a pending authorization request by its `state`
+    /// token. `None` means the state is unknown, expired, or already used —
+    /// all of which are treated as a rejected callback.
+    fn take_pending(&self, state_token: &str) -> Option<PendingAuth> {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = pending.remove(state_token)?;
+        if Instant::now().duration_since(entry.created_at) >= PENDING_TTL {
+            return None;
+        }
+        Some(entry)
+    }
+
+    fn create_session(&self, subject: String, email: Option<String>) -> String {
+        let session_id = random_token(32);
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.insert(session_id.clone(), Session { subject, email });
+        session_id
+    }
+
+    fn session(&self, session_id: &str) -> Option<Session> {
+        let sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.get(session_id).cloned()
+    }
+}
+
+/// Generates a URL-safe random token at least `min_len` characters long by
+/// concatenating UUIDv4s (with their hyphens stripped). Reuses the `uuid`
+/// crate already pulled in for record ids rather than adding a dedicated
+/// CSPRNG dependency.
+fn random_token(min_len: usize) -> String {
+    let mut token = String::with_capacity(min_len + 32);
+    while token.len() < min_len {
+        token.push_str(&uuid::Uuid::new_v4().simple().to_string());
+    }
+    token
+}
+
+fn pkce_challenge(verifier: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(verifier.as_bytes());
+    URL_SAFE_NO_PAD.encode(hasher.finalize())
+}
+
+fn percent_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(b as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+#[derive(Deserialize)]
+struct IdTokenClaims {
+    sub: String,
+    email: Option<String>,
+    nonce: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Jwks {
+    keys: Vec<jsonwebtoken::jwk::Jwk>,
+}
+
+/// Fetches the provider's JWKS, verifies `id_token`'s signature and `nonce`,
+/// and returns its claims. The issuer's JWKS endpoint is assumed to live at
+/// `{issuer}/jwks`; providers that publish a discovery document elsewhere
+/// aren't supported yet.
+fn verify_id_token(id_token: &str, config: &Config, expected_nonce: &str) -> Result<IdTokenClaims, String> {
+    let header = jsonwebtoken::decode_header(id_token).map_err(|e| e.to_string())?;
+    let kid = header.kid.ok_or("ID token is missing a key id")?;
+
+    let jwks: Jwks = reqwest::blocking::get(format!("{}/jwks", config.oidc_issuer_url))
+        .map_err(|e| format!("Failed to fetch JWKS: {}", e))?
+        .json()
+        .map_err(|e| format!("Invalid JWKS response: {}", e))?;
+
+    let jwk = jwks
+        .keys
+        .iter()
+        .find(|k| k.common.key_id.as_deref() == Some(kid.as_str()))
+        .ok_or("No matching signing key in JWKS")?;
+
+    let decoding_key = jsonwebtoken::DecodingKey::from_jwk(jwk).map_err(|e| e.to_string())?;
+    let mut validation = jsonwebtoken::Validation::new(header.alg);
+    validation.set_audience(&[&config.oidc_client_id]);
+
+    let data = jsonwebtoken::decode::<IdTokenClaims>(id_token, &decoding_key, &validation)
+        .map_err(|e| e.to_string())?;
+
+    if data.claims.nonce.as_deref() != Some(expected_nonce) {
+        return Err("Nonce mismatch".to_string());
+    }
+
+    Ok(data.claims)
+}
+
+/// Redirects to the provider's authorization endpoint with a freshly
+/// generated `state` (stored server-side) and PKCE challenge.
+#[get("/auth/login")]
+pub fn login(config: &State<Config>, oidc: &State<OidcState>) -> Result<Redirect, Status> {
+    if config.oidc_issuer_url.is_empty() {
+        return Err(Status::NotFound);
+    }
+
+    let pkce_verifier = random_token(64);
+    let nonce = random_token(32);
+    let state_token = oidc.begin(pkce_verifier.clone(), nonce.clone());
+    let redirect_uri = format!("{}/auth/callback", config.base_url);
+
+    let url = format!(
+        "{issuer}/authorize?response_type=code&client_id={client_id}&redirect_uri={redirect_uri}&scope=openid%20email&state={state}&nonce={nonce}&code_challenge={challenge}&code_challenge_method=S256",
+        issuer = config.oidc_issuer_url,
+        client_id = percent_encode(&config.oidc_client_id),
+        redirect_uri = percent_encode(&redirect_uri),
+        state = state_token,
+        nonce = nonce,
+        challenge = pkce_challenge(&pkce_verifier),
+    );
+
+    Ok(Redirect::to(url))
+}
+
+/// Exchanges the authorization code for tokens, validates the ID token, and
+/// issues a private (encrypted, signed) session cookie.
+#[get("/auth/callback?<code>&<state>")]
+pub fn callback(
+    code: String,
+    state: String,
+    config: &State<Config>,
+    oidc: &State<OidcState>,
+    cookies: &CookieJar<'_>,
+) -> Result<Redirect, Status> {
+    let pending = oidc.take_pending(&state).ok_or(Status::BadRequest)?;
+
+    let redirect_uri = format!("{}/auth/callback", config.base_url);
+    let token_endpoint = format!("{}/token", config.oidc_issuer_url);
+
+    let token_response: TokenResponse = reqwest::blocking::Client::new()
+        .post(&token_endpoint)
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", code.as_str()),
+            ("redirect_uri", redirect_uri.as_str()),
+            ("client_id", config.oidc_client_id.as_str()),
+            ("client_secret", config.oidc_client_secret.as_str()),
+            ("code_verifier", pending.pkce_verifier.as_str()),
+        ])
+        .send()
+        .map_err(|_| Status::BadGateway)?
+        .json()
+        .map_err(|_| Status::BadGateway)?;
+
+    let claims = verify_id_token(&token_response.id_token, config, &pending.nonce)
+        .map_err(|_| Status::Unauthorized)?;
+
+    let session_id = oidc.create_session(claims.sub, claims.email);
+    cookies.add_private(Cookie::new(SESSION_COOKIE, session_id));
+
+    Ok(Redirect::to("/"))
+}
+
+/// A human operator authenticated via an OIDC session cookie, as opposed to
+/// an `AuthenticatedKey` (machine, via API key).
+pub struct AuthenticatedSession {
+    pub subject: String,
+    pub is_admin: bool,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AuthenticatedSession {
+    type Error = &'static str;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let oidc = match request.guard::<&State<OidcState>>().await {
+            Outcome::Success(s) => s,
+            _ => return Outcome::Error((Status::InternalServerError, "Session store unavailable")),
+        };
+        let config = match request.guard::<&State<Config>>().await {
+            Outcome::Success(c) => c,
+            _ => return Outcome::Error((Status::InternalServerError, "Config unavailable")),
+        };
+
+        let session_id = match request.cookies().get_private(SESSION_COOKIE) {
+            Some(cookie) => cookie.value().to_string(),
+            None => return Outcome::Error((Status::Unauthorized, "Not logged in")),
+        };
+
+        match oidc.session(&session_id) {
+            Some(session) => {
+                let is_admin = session
+                    .email
+                    .as_deref()
+                    .map(|email| config.oidc_admin_emails.iter().any(|e| e == email))
+                    .unwrap_or(false);
+                Outcome::Success(AuthenticatedSession {
+                    subject: session.subject,
+                    is_admin,
+                })
+            }
+            None => Outcome::Error((Status::Unauthorized, "Session expired or unknown")),
+        }
+    }
+}
+
+/// Either a machine caller (API key) or a logged-in human operator (OIDC
+/// session) — whichever the request presents. Routes that should be usable
+/// from both the API and the dashboard take this instead of
+/// `AuthenticatedKey` directly.
+pub enum Principal {
+    ApiKey(crate::auth::AuthenticatedKey),
+    Session(AuthenticatedSession),
+}
+
+impl Principal {
+    pub fn is_admin(&self) -> bool {
+        match self {
+            Principal::ApiKey(key) => key.is_admin,
+            Principal::Session(session) => session.is_admin,
+        }
+    }
+
+    /// The owning API key id, when this principal authenticated as one.
+    /// Session-based operators aren't scoped to a single key's QR codes, so
+    /// callers should treat `None` as "not ownership-restricted". Kept
+    /// private — callers that need an ownership decision should go through
+    /// `ownership_scope`, which also accounts for non-admin sessions.
+    fn api_key_id(&self) -> Option<&str> {
+        match self {
+            Principal::ApiKey(key) => Some(&key.id),
+            Principal::Session(_) => None,
+        }
+    }
+
+    /// What to scope a tracked-QR ownership query by: `Some(key_id)` for an
+    /// API key (only its own rows), `Ok(None)` for an *admin* session (every
+    /// tenant's rows, same as `list_keys`), or a `Forbidden` error for a
+    /// non-admin session — which has no API key of its own to scope by and
+    /// isn't an admin either, so it gets neither "own rows only" nor "every
+    /// row".
+    pub fn ownership_scope(&self) -> Result<Option<&str>, (Status, rocket::serde::json::Json<crate::models::ApiError>)> {
+        match self {
+            Principal::ApiKey(_) => Ok(self.api_key_id()),
+            Principal::Session(_) if self.is_admin() => Ok(None),
+            Principal::Session(_) => Err((
+                Status::Forbidden,
+                rocket::serde::json::Json(crate::models::ApiError {
+                    error: "Admin access required".to_string(),
+                    code: "FORBIDDEN".to_string(),
+                    status: 403,
+                }),
+            )),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Principal {
+    type Error = &'static str;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match request.guard::<crate::auth::AuthenticatedKey>().await {
+            Outcome::Success(key) => return Outcome::Success(Principal::ApiKey(key)),
+            Outcome::Error(_) | Outcome::Forward(_) => {}
+        }
+
+        match request.guard::<AuthenticatedSession>().await {
+            Outcome::Success(session) => Outcome::Success(Principal::Session(session)),
+            Outcome::Error((status, _)) => Outcome::Error((status, "Missing API key or session")),
+            Outcome::Forward(f) => Outcome::Forward(f),
+        }
+    }
+}
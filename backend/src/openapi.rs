@@ -0,0 +1,363 @@
+//! Builds the OpenAPI 3.0 document served at `GET /api/v1/openapi.json`.
+//!
+//! `paths` is hand-listed to match the route table in `main.rs` — there's no
+//! macro wiring a route's path/method back to its handler, so this has to be
+//! kept in sync by hand when routes are added or removed. `components.schemas`
+//! is derived automatically from the model types via `schemars`, so request/
+//! response shapes can't drift from what `models.rs`/`qr.rs` actually define.
+
+use schemars::schema_for;
+use serde_json::{json, Value};
+
+use crate::models::*;
+use crate::qr::QrContent;
+
+fn schema_ref(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{}", name) })
+}
+
+fn error_response(description: &str) -> Value {
+    json!({
+        "description": description,
+        "content": {
+            "application/json": { "schema": schema_ref("ApiError") }
+        }
+    })
+}
+
+fn json_body(schema_name: &str) -> Value {
+    json!({
+        "required": true,
+        "content": {
+            "application/json": { "schema": schema_ref(schema_name) }
+        }
+    })
+}
+
+fn json_response(description: &str, schema_name: &str) -> Value {
+    json!({
+        "description": description,
+        "content": {
+            "application/json": { "schema": schema_ref(schema_name) }
+        }
+    })
+}
+
+fn api_key_security() -> Value {
+    json!([{ "ApiKeyAuth": [] }])
+}
+
+/// Builds the full OpenAPI document from the live model/route set.
+pub fn generate_spec() -> Value {
+    let schemas = json!({
+        "GenerateRequest": schema_for!(GenerateRequest),
+        "EncryptedGenerateRequest": schema_for!(EncryptedGenerateRequest),
+        "DecryptRequest": schema_for!(DecryptRequest),
+        "BatchGenerateRequest": schema_for!(BatchGenerateRequest),
+        "QrResponse": schema_for!(QrResponse),
+        "BatchItemResult": schema_for!(BatchItemResult),
+        "BatchQrResponse": schema_for!(BatchQrResponse),
+        "QrContent": schema_for!(QrContent),
+        "DecodedQr": schema_for!(DecodedQr),
+        "DecodeResponse": schema_for!(DecodeResponse),
+        "CreateKeyRequest": schema_for!(CreateKeyRequest),
+        "KeyResponse": schema_for!(KeyResponse),
+        "ApiError": schema_for!(ApiError),
+        "HealthResponse": schema_for!(HealthResponse),
+        "CreateTrackedQrRequest": schema_for!(CreateTrackedQrRequest),
+        "TrackedQrResponse": schema_for!(TrackedQrResponse),
+        "ScanEventResponse": schema_for!(ScanEventResponse),
+        "TrackedQrStatsResponse": schema_for!(TrackedQrStatsResponse),
+        "TrackedQrListItem": schema_for!(TrackedQrListItem),
+        "TrackedQrTopItem": schema_for!(TrackedQrTopItem),
+        "TrackedQrFacets": schema_for!(TrackedQrFacets),
+        "TrackedQrListResponse": schema_for!(TrackedQrListResponse),
+        "ScanHistoryResponse": schema_for!(ScanHistoryResponse),
+        "SignImageRequest": schema_for!(SignImageRequest),
+        "SignedImageUrlResponse": schema_for!(SignedImageUrlResponse),
+        "BatchCreateTrackedQrRequest": schema_for!(BatchCreateTrackedQrRequest),
+        "TrackedQrBatchItemResult": schema_for!(TrackedQrBatchItemResult),
+        "BatchTrackedQrResponse": schema_for!(BatchTrackedQrResponse),
+        "ScanTimeseriesBucket": schema_for!(ScanTimeseriesBucket),
+        "ScanBreakdownItem": schema_for!(ScanBreakdownItem),
+        "TrackedQrTimeseriesResponse": schema_for!(TrackedQrTimeseriesResponse),
+    });
+
+    let paths = json!({
+        "/api/v1/health": {
+            "get": {
+                "summary": "Liveness check",
+                "responses": { "200": json_response("Service is healthy", "HealthResponse") }
+            }
+        },
+        "/api/v1/qr/generate": {
+            "post": {
+                "summary": "Generate a QR code",
+                "security": api_key_security(),
+                "requestBody": json_body("GenerateRequest"),
+                "responses": {
+                    "200": json_response("Generated QR code", "QrResponse"),
+                    "400": error_response("Invalid request"),
+                    "401": error_response("Missing or invalid API key"),
+                }
+            }
+        },
+        "/api/v1/qr/generate/encrypted": {
+            "post": {
+                "summary": "Generate a QR code whose encoded content is end-to-end encrypted for a recipient's x25519 public key",
+                "security": api_key_security(),
+                "requestBody": json_body("EncryptedGenerateRequest"),
+                "responses": {
+                    "200": json_response("Generated QR code carrying a ciphertext envelope", "QrResponse"),
+                    "400": error_response("Invalid request or public key"),
+                    "401": error_response("Missing or invalid API key"),
+                }
+            }
+        },
+        "/api/v1/qr/decrypt": {
+            "post": {
+                "summary": "Decrypt an envelope produced by /qr/generate/encrypted using the recipient's private key",
+                "security": api_key_security(),
+                "requestBody": json_body("DecryptRequest"),
+                "responses": {
+                    "200": json_response("Recovered plaintext", "DecodeResponse"),
+                    "400": error_response("Invalid request or decryption failed"),
+                    "401": error_response("Missing or invalid API key"),
+                }
+            }
+        },
+        "/api/v1/qr/batch": {
+            "post": {
+                "summary": "Generate multiple QR codes in one request",
+                "security": api_key_security(),
+                "requestBody": json_body("BatchGenerateRequest"),
+                "responses": {
+                    "200": json_response("Per-item generation results", "BatchQrResponse"),
+                    "401": error_response("Missing or invalid API key"),
+                }
+            }
+        },
+        "/api/v1/qr/decode": {
+            "post": {
+                "summary": "Decode QR codes from an uploaded image",
+                "security": api_key_security(),
+                "responses": {
+                    "200": json_response("Decoded QR payloads", "DecodeResponse"),
+                    "400": error_response("Image could not be decoded"),
+                    "401": error_response("Missing or invalid API key"),
+                }
+            }
+        },
+        "/api/v1/qr/template": {
+            "post": {
+                "summary": "Generate a QR code from a structured template (wifi, vcard, ...)",
+                "security": api_key_security(),
+                "responses": {
+                    "200": json_response("Generated QR code", "QrResponse"),
+                    "400": error_response("Invalid template request"),
+                    "401": error_response("Missing or invalid API key"),
+                }
+            }
+        },
+        "/api/v1/qr/history": {
+            "get": {
+                "summary": "List previously generated QR codes for the calling key",
+                "security": api_key_security(),
+                "responses": {
+                    "200": json_response("History page", "QrResponse"),
+                    "401": error_response("Missing or invalid API key"),
+                }
+            }
+        },
+        "/api/v1/qr/{id}": {
+            "get": {
+                "summary": "Fetch a previously generated QR code's metadata",
+                "security": api_key_security(),
+                "responses": {
+                    "200": json_response("QR code metadata", "QrResponse"),
+                    "404": error_response("No QR code with that id"),
+                }
+            },
+            "delete": {
+                "summary": "Delete a previously generated QR code",
+                "security": api_key_security(),
+                "responses": {
+                    "204": { "description": "Deleted" },
+                    "404": error_response("No QR code with that id"),
+                }
+            }
+        },
+        "/api/v1/qr/{id}/image": {
+            "get": {
+                "summary": "Fetch a QR code's rendered image",
+                "security": api_key_security(),
+                "responses": {
+                    "200": { "description": "Image bytes or a redirect to the storage backend" },
+                    "404": error_response("No QR code with that id"),
+                }
+            }
+        },
+        "/api/v1/qr/{id}/image/public": {
+            "get": {
+                "summary": "Fetch a QR code's image via a signed, time-limited public link",
+                "parameters": [
+                    { "name": "exp", "in": "query", "required": true, "schema": { "type": "integer" } },
+                    { "name": "sig", "in": "query", "required": true, "schema": { "type": "string" } },
+                ],
+                "responses": {
+                    "200": { "description": "Image bytes or a redirect to the storage backend" },
+                    "403": error_response("Signature invalid, expired, or the feature is disabled"),
+                    "404": error_response("No QR code with that id"),
+                }
+            }
+        },
+        "/api/v1/qr/{id}/sign": {
+            "post": {
+                "summary": "Mint a signed public link for a QR code's image",
+                "security": api_key_security(),
+                "requestBody": json_body("SignImageRequest"),
+                "responses": {
+                    "200": json_response("Signed URL", "SignedImageUrlResponse"),
+                    "401": error_response("Missing or invalid API key"),
+                    "404": error_response("No QR code with that id"),
+                }
+            }
+        },
+        "/api/v1/qr/tracked": {
+            "post": {
+                "summary": "Create a tracked (scan-counted) short-URL QR code",
+                "security": api_key_security(),
+                "requestBody": json_body("CreateTrackedQrRequest"),
+                "responses": {
+                    "200": json_response("Created tracked QR code", "TrackedQrResponse"),
+                    "401": error_response("Missing or invalid API key"),
+                }
+            },
+            "get": {
+                "summary": "List tracked QR codes for the calling key",
+                "security": api_key_security(),
+                "responses": {
+                    "200": json_response("Tracked QR codes", "TrackedQrListResponse"),
+                    "401": error_response("Missing or invalid API key"),
+                }
+            }
+        },
+        "/api/v1/qr/tracked/batch": {
+            "post": {
+                "summary": "Create multiple tracked QR codes in one request",
+                "security": api_key_security(),
+                "requestBody": json_body("BatchCreateTrackedQrRequest"),
+                "responses": {
+                    "200": json_response("Per-item creation results", "BatchTrackedQrResponse"),
+                    "401": error_response("Missing or invalid API key"),
+                    "403": error_response("Missing the qr:tracked:write scope"),
+                    "413": error_response("Batch exceeds the item limit"),
+                }
+            }
+        },
+        "/api/v1/qr/tracked/{id}": {
+            "delete": {
+                "summary": "Delete a tracked QR code",
+                "security": api_key_security(),
+                "responses": {
+                    "204": { "description": "Deleted" },
+                    "404": error_response("No tracked QR code with that id"),
+                }
+            }
+        },
+        "/api/v1/qr/tracked/{id}/stats": {
+            "get": {
+                "summary": "Fetch scan stats for a tracked QR code",
+                "security": api_key_security(),
+                "responses": {
+                    "200": json_response("Scan stats", "TrackedQrStatsResponse"),
+                    "404": error_response("No tracked QR code with that id"),
+                }
+            }
+        },
+        "/api/v1/qr/tracked/{id}/scans": {
+            "get": {
+                "summary": "Full, cursor-paginated scan event history for a tracked QR code",
+                "security": api_key_security(),
+                "parameters": [
+                    { "name": "after", "in": "query", "required": false, "schema": { "type": "string" } },
+                    { "name": "limit", "in": "query", "required": false, "schema": { "type": "integer" } },
+                ],
+                "responses": {
+                    "200": json_response("Scan event history", "ScanHistoryResponse"),
+                    "400": error_response("Invalid 'after' cursor"),
+                    "404": error_response("No tracked QR code with that id"),
+                }
+            }
+        },
+        "/api/v1/qr/tracked/{id}/stats/timeseries": {
+            "get": {
+                "summary": "Scan counts bucketed by hour/day, plus top-10 breakdowns by country, device, and referrer",
+                "security": api_key_security(),
+                "parameters": [
+                    { "name": "bucket", "in": "query", "required": false, "schema": { "type": "string", "enum": ["hour", "day"] } },
+                    { "name": "from", "in": "query", "required": false, "schema": { "type": "string", "format": "date-time" } },
+                    { "name": "to", "in": "query", "required": false, "schema": { "type": "string", "format": "date-time" } },
+                ],
+                "responses": {
+                    "200": json_response("Bucketed scan counts and breakdowns", "TrackedQrTimeseriesResponse"),
+                    "400": error_response("Invalid bucket"),
+                    "404": error_response("No tracked QR code with that id"),
+                }
+            }
+        },
+        "/api/v1/keys": {
+            "get": {
+                "summary": "List API keys",
+                "security": api_key_security(),
+                "responses": { "200": json_response("API keys", "KeyResponse") }
+            },
+            "post": {
+                "summary": "Create an API key",
+                "requestBody": json_body("CreateKeyRequest"),
+                "responses": { "200": json_response("Created API key", "KeyResponse") }
+            }
+        },
+        "/api/v1/keys/{id}": {
+            "delete": {
+                "summary": "Revoke an API key",
+                "security": api_key_security(),
+                "responses": {
+                    "204": { "description": "Deleted" },
+                    "404": error_response("No API key with that id"),
+                }
+            }
+        },
+        "/api/v1/keys/{id}/rotate": {
+            "post": {
+                "summary": "Rotate an API key's secret, preserving its scopes and validity window",
+                "security": api_key_security(),
+                "responses": {
+                    "200": json_response("Rotated API key", "KeyResponse"),
+                    "403": error_response("Missing the keys:admin scope"),
+                    "404": error_response("No API key with that id"),
+                }
+            }
+        },
+    });
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "QR Service API",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "servers": [{ "url": "/api/v1" }],
+        "paths": paths,
+        "components": {
+            "schemas": schemas,
+            "securitySchemes": {
+                "ApiKeyAuth": {
+                    "type": "apiKey",
+                    "in": "header",
+                    "name": "Authorization",
+                }
+            }
+        }
+    })
+}
@@ -4,6 +4,7 @@ use image::{ImageBuffer, Rgba, RgbaImage};
 use qrcode::types::QrError;
 use qrcode::EcLevel;
 use qrcode::QrCode;
+use std::collections::HashMap;
 use std::io::Cursor;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -11,6 +12,13 @@ pub enum QrStyle {
     Square,
     Rounded,
     Dots,
+    /// Like `Rounded`, but touching dark modules are merged into a single
+    /// outline before rounding instead of each module getting its own
+    /// corner treatment — no seams or overdraw where modules share an edge.
+    /// Vector formats (`svg`, `pdf`) render the merged contour directly;
+    /// `png` falls back to `Rounded`'s per-module neighbor-aware rounding,
+    /// since a raster fill has no seams to merge away in the first place.
+    Merged,
 }
 
 impl QrStyle {
@@ -18,17 +26,224 @@ impl QrStyle {
         match s.to_lowercase().as_str() {
             "rounded" => QrStyle::Rounded,
             "dots" => QrStyle::Dots,
+            "merged" => QrStyle::Merged,
             _ => QrStyle::Square,
         }
     }
 }
 
+/// Explicit QR symbol version, mirroring `qrcode::types::Version` but kept
+/// as our own type so callers outside this module don't need the `qrcode`
+/// crate in scope just to build a `QrOptions`.
+#[derive(Debug, Clone, Copy)]
+pub enum QrVersion {
+    /// Standard QR, version 1-40 (21x21 to 177x177 modules).
+    Normal(i16),
+    /// Micro QR, version 1-4 (11x11 to 17x17 modules) — much smaller for
+    /// short payloads, but not all scanners support it.
+    Micro(i16),
+}
+
+impl QrVersion {
+    fn to_qrcode_version(self) -> qrcode::Version {
+        match self {
+            QrVersion::Normal(n) => qrcode::Version::Normal(n),
+            QrVersion::Micro(n) => qrcode::Version::Micro(n),
+        }
+    }
+}
+
+/// A color stop at position `t` (the first component, in `[0.0, 1.0]`)
+/// along a gradient.
+pub type ColorStop = (f64, [u8; 4]);
+
+/// Foreground fill for a QR symbol's dark modules. `Solid` is the default
+/// and behaves exactly like the plain `[u8; 4]` this replaced; the gradient
+/// variants sample a color per module from its normalized position in the
+/// symbol, `(0.0, 0.0)` at the top-left corner to `(1.0, 1.0)` at the
+/// bottom-right.
+#[derive(Debug, Clone)]
+pub enum Fill {
+    Solid([u8; 4]),
+    LinearGradient {
+        start: (f64, f64),
+        end: (f64, f64),
+        stops: Vec<ColorStop>,
+    },
+    RadialGradient {
+        center: (f64, f64),
+        radius: f64,
+        stops: Vec<ColorStop>,
+    },
+}
+
+impl Fill {
+    /// Color sampled at normalized module position `(nx, ny)`, each in
+    /// `[0.0, 1.0]`. Always `Solid`'s one color regardless of position.
+    fn color_at(&self, nx: f64, ny: f64) -> [u8; 4] {
+        match self {
+            Fill::Solid(c) => *c,
+            Fill::LinearGradient { start, end, stops } => {
+                let dx = end.0 - start.0;
+                let dy = end.1 - start.1;
+                let len_sq = dx * dx + dy * dy;
+                let t = if len_sq == 0.0 {
+                    0.0
+                } else {
+                    (((nx - start.0) * dx + (ny - start.1) * dy) / len_sq).clamp(0.0, 1.0)
+                };
+                sample_stops(stops, t)
+            }
+            Fill::RadialGradient {
+                center,
+                radius,
+                stops,
+            } => {
+                let dist = ((nx - center.0).powi(2) + (ny - center.1).powi(2)).sqrt();
+                let t = if *radius <= 0.0 {
+                    0.0
+                } else {
+                    (dist / radius).clamp(0.0, 1.0)
+                };
+                sample_stops(stops, t)
+            }
+        }
+    }
+
+    /// A single representative color for contexts that can only draw a flat
+    /// fill (currently `generate_pdf`'s vector output, which doesn't yet
+    /// support PDF shading patterns) — the gradient's midpoint color.
+    fn flat_color(&self) -> [u8; 4] {
+        match self {
+            Fill::Solid(c) => *c,
+            Fill::LinearGradient { stops, .. } | Fill::RadialGradient { stops, .. } => {
+                sample_stops(stops, 0.5)
+            }
+        }
+    }
+}
+
+/// Linearly interpolates a color between the two stops bracketing `t`,
+/// clamping to the end stops outside `[0.0, 1.0]`.
+fn sample_stops(stops: &[ColorStop], t: f64) -> [u8; 4] {
+    if stops.is_empty() {
+        return [0, 0, 0, 255];
+    }
+    if t <= stops[0].0 {
+        return stops[0].1;
+    }
+    for pair in stops.windows(2) {
+        let (t0, c0) = pair[0];
+        let (t1, c1) = pair[1];
+        if t <= t1 {
+            let span = (t1 - t0).max(f64::EPSILON);
+            let local_t = ((t - t0) / span).clamp(0.0, 1.0);
+            return lerp_color(c0, c1, local_t);
+        }
+    }
+    stops[stops.len() - 1].1
+}
+
+fn lerp_color(a: [u8; 4], b: [u8; 4], t: f64) -> [u8; 4] {
+    let mut out = [0u8; 4];
+    for (i, o) in out.iter_mut().enumerate() {
+        *o = (a[i] as f64 + (b[i] as f64 - a[i] as f64) * t).round() as u8;
+    }
+    out
+}
+
 pub struct QrOptions {
     pub size: u32,
-    pub fg_color: [u8; 4],
+    pub fg_color: Fill,
     pub bg_color: [u8; 4],
     pub error_correction: EcLevel,
     pub style: QrStyle,
+    /// Smooth `Dots`/`Rounded` module edges with fractional pixel coverage
+    /// instead of a hard inside/outside test. No effect on `Square`, whose
+    /// edges are already pixel-aligned.
+    pub antialias: bool,
+    /// Pin the symbol to a specific version instead of letting the `qrcode`
+    /// crate auto-pick the smallest one that fits `data` at
+    /// `error_correction`. `None` keeps the previous auto-sizing behavior.
+    pub version: Option<QrVersion>,
+    /// Diameter of a `Dots`-style module as a fraction of its cell, in
+    /// `(0.0, 1.0]`. Below 1.0 leaves a gap between neighboring dots so
+    /// they read as discrete circles instead of a solid blob. ~0.85 is a
+    /// good default.
+    pub dot_fill_ratio: f64,
+    /// Render finder-pattern modules (the three positioning squares) as
+    /// solid squares even when `style` is `Dots`/`Rounded` — scanners lean
+    /// on their crisp, unbroken edges to lock onto the symbol.
+    pub square_finder_modules: bool,
+    /// Degrees to rotate the whole symbol by in `generate_pdf`, clockwise
+    /// around its own center. `0.0` leaves it axis-aligned. No effect on
+    /// PNG/SVG/batch-PDF output.
+    pub rotation_degrees: f32,
+    /// Uniform scale factor applied to the symbol in `generate_pdf` before
+    /// rotation, e.g. `0.5` to shrink it to half size within the page.
+    /// `1.0` is a no-op. No effect on PNG/SVG/batch-PDF output.
+    pub scale: f32,
+}
+
+/// Whether module `(x, y)` falls inside one of the symbol's finder
+/// patterns — the 8x8 block (7x7 finder + 1-module separator) anchored at
+/// the top-left corner, and, for standard (non-Micro) symbols large enough
+/// to have them, the matching blocks at the top-right and bottom-left
+/// corners. Micro QR only has the one top-left finder.
+fn is_finder_module(x: usize, y: usize, module_count: usize) -> bool {
+    let in_top_left = x < 8 && y < 8;
+    if module_count < 21 {
+        return in_top_left; // Micro QR: single finder pattern
+    }
+    let in_top_right = x >= module_count.saturating_sub(8) && y < 8;
+    let in_bottom_left = x < 8 && y >= module_count.saturating_sub(8);
+    in_top_left || in_top_right || in_bottom_left
+}
+
+/// Builds the `QrCode` for `data` under `options`, honoring an explicit
+/// `version` when set and otherwise auto-picking the smallest version that
+/// fits. Shared by `generate_png`/`generate_svg`/`generate_pdf` so all three
+/// renderers fail the same way when the data doesn't fit the requested
+/// version/EC combination.
+fn build_qr_code(data: &str, options: &QrOptions) -> Result<QrCode, String> {
+    match options.version {
+        Some(version) => QrCode::with_version(
+            data,
+            version.to_qrcode_version(),
+            options.error_correction,
+        )
+        .map_err(|e: QrError| {
+            format!(
+                "Data does not fit the requested QR version/error-correction combination: {}",
+                e
+            )
+        }),
+        None => QrCode::with_error_correction_level(data, options.error_correction)
+            .map_err(|e: QrError| format!("QR encoding error: {}", e)),
+    }
+}
+
+/// Same as `build_qr_code`, but for raw byte payloads (verification
+/// handshakes, signed tokens) that should be encoded in byte mode rather
+/// than interpreted as text. The `qrcode` crate picks byte mode on its own
+/// whenever the input isn't valid numeric/alphanumeric-mode content, which
+/// arbitrary bytes essentially never are.
+fn build_qr_code_bytes(data: &[u8], options: &QrOptions) -> Result<QrCode, String> {
+    match options.version {
+        Some(version) => QrCode::with_version(
+            data,
+            version.to_qrcode_version(),
+            options.error_correction,
+        )
+        .map_err(|e: QrError| {
+            format!(
+                "Data does not fit the requested QR version/error-correction combination: {}",
+                e
+            )
+        }),
+        None => QrCode::with_error_correction_level(data, options.error_correction)
+            .map_err(|e: QrError| format!("QR encoding error: {}", e)),
+    }
 }
 
 pub fn parse_hex_color(hex: &str) -> Result<[u8; 4], String> {
@@ -49,6 +264,29 @@ pub fn parse_hex_color(hex: &str) -> Result<[u8; 4], String> {
     Ok([r, g, b, a])
 }
 
+/// Parses an explicit version string: a plain number (`"5"`) for a standard
+/// QR version 1-40, or `M` followed by a number (`"M2"`) for a Micro QR
+/// version 1-4.
+pub fn parse_version(s: &str) -> Result<QrVersion, String> {
+    if let Some(rest) = s.strip_prefix('M').or_else(|| s.strip_prefix('m')) {
+        let n: i16 = rest
+            .parse()
+            .map_err(|_| format!("Invalid micro QR version: {}", s))?;
+        if !(1..=4).contains(&n) {
+            return Err(format!("Micro QR version must be 1-4, got {}", n));
+        }
+        Ok(QrVersion::Micro(n))
+    } else {
+        let n: i16 = s
+            .parse()
+            .map_err(|_| format!("Invalid QR version: {}", s))?;
+        if !(1..=40).contains(&n) {
+            return Err(format!("QR version must be 1-40, got {}", n));
+        }
+        Ok(QrVersion::Normal(n))
+    }
+}
+
 pub fn parse_ec_level(level: &str) -> EcLevel {
     match level.to_uppercase().as_str() {
         "L" => EcLevel::L,
@@ -60,9 +298,16 @@ pub fn parse_ec_level(level: &str) -> EcLevel {
 }
 
 pub fn generate_png(data: &str, options: &QrOptions) -> Result<Vec<u8>, String> {
-    let code = QrCode::with_error_correction_level(data, options.error_correction)
-        .map_err(|e: QrError| format!("QR encoding error: {}", e))?;
+    render_png(build_qr_code(data, options)?, options)
+}
 
+/// Same as `generate_png`, but for a raw byte payload encoded in QR byte
+/// mode instead of a UTF-8 string (see `build_qr_code_bytes`).
+pub fn generate_png_bytes(data: &[u8], options: &QrOptions) -> Result<Vec<u8>, String> {
+    render_png(build_qr_code_bytes(data, options)?, options)
+}
+
+fn render_png(code: QrCode, options: &QrOptions) -> Result<Vec<u8>, String> {
     let modules = code.to_colors();
     let module_count = code.width() as u32;
 
@@ -72,7 +317,6 @@ pub fn generate_png(data: &str, options: &QrOptions) -> Result<Vec<u8>, String>
     let module_size = (options.size / total_modules).max(1);
     let actual_size = total_modules * module_size;
 
-    let fg = Rgba(options.fg_color);
     let bg = Rgba(options.bg_color);
 
     let mut img: RgbaImage = ImageBuffer::from_pixel(actual_size, actual_size, bg);
@@ -82,22 +326,75 @@ pub fn generate_png(data: &str, options: &QrOptions) -> Result<Vec<u8>, String>
             if module == qrcode::Color::Dark {
                 let px = (x as u32 + quiet_zone) * module_size;
                 let py = (y as u32 + quiet_zone) * module_size;
-
-                match options.style {
+                // Sample the fill at this module's normalized position so
+                // gradients vary smoothly across the symbol; `Solid` just
+                // returns the same color every time.
+                let fg = Rgba(options.fg_color.color_at(
+                    x as f64 / module_count.max(1) as f64,
+                    y as f64 / module_count.max(1) as f64,
+                ));
+
+                // `Merged` traces a whole-symbol outline elsewhere (svg/pdf);
+                // for a raster fill there are no seams to merge away, so it
+                // just gets the same per-module neighbor-aware rounding as
+                // `Rounded`.
+                let effective_style = if options.square_finder_modules
+                    && is_finder_module(x, y, module_count as usize)
+                {
+                    QrStyle::Square
+                } else if options.style == QrStyle::Merged {
+                    QrStyle::Rounded
+                } else {
+                    options.style
+                };
+
+                match effective_style {
                     QrStyle::Dots => {
-                        draw_circle_module(&mut img, px, py, module_size, fg, actual_size);
+                        if options.antialias {
+                            draw_circle_module_aa(
+                                &mut img,
+                                px,
+                                py,
+                                module_size,
+                                fg,
+                                actual_size,
+                                options.dot_fill_ratio,
+                            );
+                        } else {
+                            draw_circle_module(
+                                &mut img,
+                                px,
+                                py,
+                                module_size,
+                                fg,
+                                actual_size,
+                                options.dot_fill_ratio,
+                            );
+                        }
                     }
                     QrStyle::Rounded => {
                         let neighbors = get_neighbors(&modules, module_count as usize, x, y);
-                        draw_rounded_module(
-                            &mut img,
-                            px,
-                            py,
-                            module_size,
-                            fg,
-                            actual_size,
-                            &neighbors,
-                        );
+                        if options.antialias {
+                            draw_rounded_module_aa(
+                                &mut img,
+                                px,
+                                py,
+                                module_size,
+                                fg,
+                                actual_size,
+                                &neighbors,
+                            );
+                        } else {
+                            draw_rounded_module(
+                                &mut img,
+                                px,
+                                py,
+                                module_size,
+                                fg,
+                                actual_size,
+                                &neighbors,
+                            );
+                        }
                     }
                     QrStyle::Square => {
                         for dy in 0..module_size {
@@ -108,6 +405,7 @@ pub fn generate_png(data: &str, options: &QrOptions) -> Result<Vec<u8>, String>
                             }
                         }
                     }
+                    QrStyle::Merged => unreachable!("Merged is remapped to Rounded above"),
                 }
             }
         }
@@ -132,6 +430,198 @@ fn get_neighbors(modules: &[qrcode::Color], width: usize, x: usize, y: usize) ->
     ]
 }
 
+/// One edge of a traced `Merged`-style boundary ring, in module-grid
+/// coordinates (unscaled, y increasing downward to match the module grid):
+/// either a straight run to `(x, y)`, or a 90° corner fillet replaced by a
+/// cubic Bezier arc to `(x, y)` through the given control points.
+enum ContourSeg {
+    Line(f64, f64),
+    Arc {
+        c1: (f64, f64),
+        c2: (f64, f64),
+        end: (f64, f64),
+    },
+}
+
+/// A closed, filleted boundary ring: a starting point plus the sequence of
+/// segments that lead back to it.
+struct Contour {
+    start: (f64, f64),
+    segs: Vec<ContourSeg>,
+}
+
+/// Traces the outer boundary of every connected group of dark modules for
+/// which `eligible` returns `true` into closed rectilinear rings (including
+/// the boundaries of any enclosed light "holes"), collapses collinear runs,
+/// then replaces each convex corner with a cubic-Bezier fillet of radius
+/// `corner_r` — clamped per-corner to half of its shorter adjacent edge so
+/// fillets on the same edge never overlap. Concave corners are left sharp,
+/// matching gofpdf/most "liquid QR" renderers, which only round exterior
+/// turns.
+///
+/// This is the same edge-cancellation trick used to extract the boundary of
+/// a union of unit squares: walk each eligible module's 4 edges clockwise
+/// (in this grid's y-down sense) and keep only the ones whose neighbor
+/// across that edge isn't also eligible-dark — shared interior edges always
+/// appear twice, in opposite directions, and cancel out.
+fn trace_merged_contours(
+    modules: &[qrcode::Color],
+    module_count: usize,
+    corner_r: f64,
+    eligible: impl Fn(usize, usize) -> bool,
+) -> Vec<Contour> {
+    let mc = module_count;
+    let is_region = |x: i64, y: i64| -> bool {
+        if x < 0 || y < 0 || x as usize >= mc || y as usize >= mc {
+            return false;
+        }
+        let (ux, uy) = (x as usize, y as usize);
+        eligible(ux, uy) && modules[uy * mc + ux] == qrcode::Color::Dark
+    };
+
+    let mut edges: HashMap<(i64, i64), (i64, i64)> = HashMap::new();
+    for y in 0..mc as i64 {
+        for x in 0..mc as i64 {
+            if !is_region(x, y) {
+                continue;
+            }
+            if !is_region(x, y - 1) {
+                edges.insert((x, y), (x + 1, y)); // top
+            }
+            if !is_region(x + 1, y) {
+                edges.insert((x + 1, y), (x + 1, y + 1)); // right
+            }
+            if !is_region(x, y + 1) {
+                edges.insert((x + 1, y + 1), (x, y + 1)); // bottom
+            }
+            if !is_region(x - 1, y) {
+                edges.insert((x, y + 1), (x, y)); // left
+            }
+        }
+    }
+
+    let mut remaining = edges;
+    let mut rings: Vec<Vec<(i64, i64)>> = Vec::new();
+    while let Some(&start) = remaining.keys().next() {
+        let mut ring = vec![start];
+        let mut cur = start;
+        while let Some(next) = remaining.remove(&cur) {
+            if next == start {
+                break;
+            }
+            ring.push(next);
+            cur = next;
+        }
+        if ring.len() >= 3 {
+            rings.push(ring);
+        }
+    }
+
+    rings.iter().map(|r| fillet_ring(r, corner_r)).collect()
+}
+
+/// Drops collinear vertices from a traced ring, keeping only actual corners.
+fn simplify_ring(ring: &[(i64, i64)]) -> Vec<(f64, f64)> {
+    let n = ring.len();
+    let mut out = Vec::with_capacity(n);
+    for i in 0..n {
+        let prev = ring[(i + n - 1) % n];
+        let cur = ring[i];
+        let next = ring[(i + 1) % n];
+        let d1 = (cur.0 - prev.0, cur.1 - prev.1);
+        let d2 = (next.0 - cur.0, next.1 - cur.1);
+        if d1.0 * d2.1 - d1.1 * d2.0 != 0 {
+            out.push((cur.0 as f64, cur.1 as f64));
+        }
+    }
+    out
+}
+
+/// Rounds the convex corners of one simplified ring into a closed, filleted
+/// `Contour` (see `trace_merged_contours`).
+fn fillet_ring(ring: &[(i64, i64)], corner_r: f64) -> Contour {
+    let ring = simplify_ring(ring);
+    let n = ring.len();
+
+    // Orientation sign (shoelace), so a corner's turn direction can be
+    // compared against the ring's own winding rather than an absolute sense
+    // — outer boundaries and the boundaries of enclosed holes wind opposite
+    // ways, and each rounds its own convex side.
+    let mut signed_area = 0.0;
+    for i in 0..n {
+        let a = ring[i];
+        let b = ring[(i + 1) % n];
+        signed_area += a.0 * b.1 - b.0 * a.1;
+    }
+    let orientation_positive = signed_area > 0.0;
+
+    let dist = |a: (f64, f64), b: (f64, f64)| ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt();
+
+    let mut points: Vec<(f64, f64)> = Vec::new();
+    let mut segs: Vec<ContourSeg> = Vec::new();
+
+    for i in 0..n {
+        let prev = ring[(i + n - 1) % n];
+        let cur = ring[i];
+        let next = ring[(i + 1) % n];
+        let len_in = dist(prev, cur);
+        let len_out = dist(cur, next);
+        let din = ((cur.0 - prev.0) / len_in, (cur.1 - prev.1) / len_in);
+        let dout = ((next.0 - cur.0) / len_out, (next.1 - cur.1) / len_out);
+        let cross = din.0 * dout.1 - din.1 * dout.0;
+        let convex = (cross > 0.0) == orientation_positive;
+        let r = corner_r.min(len_in * 0.5).min(len_out * 0.5);
+
+        if convex && r > 0.0 {
+            let start = (cur.0 - din.0 * r, cur.1 - din.1 * r);
+            let center = (cur.0 - din.0 * r + dout.0 * r, cur.1 - din.1 * r + dout.1 * r);
+            let start_angle = (start.1 - center.1).atan2(start.0 - center.0);
+            let arc = quarter_circle_bezier_f64(center.0, center.1, r, start_angle);
+            if points.is_empty() {
+                points.push(start);
+            } else {
+                segs.push(ContourSeg::Line(start.0, start.1));
+            }
+            segs.push(ContourSeg::Arc {
+                c1: arc.0,
+                c2: arc.1,
+                end: arc.2,
+            });
+        } else if points.is_empty() {
+            points.push(cur);
+        } else {
+            segs.push(ContourSeg::Line(cur.0, cur.1));
+        }
+    }
+
+    Contour {
+        start: points[0],
+        segs,
+    }
+}
+
+/// `f64`, renderer-agnostic sibling of `quarter_circle_bezier` (which works
+/// in PDF `f32` points) — same circle-to-Bezier construction, returning
+/// `(ctrl1, ctrl2, end)` since the caller already tracks the arc's start
+/// point itself.
+fn quarter_circle_bezier_f64(
+    cx: f64,
+    cy: f64,
+    r: f64,
+    start_angle: f64,
+) -> ((f64, f64), (f64, f64), (f64, f64)) {
+    let k = r * (4.0 / 3.0) * (std::f64::consts::SQRT_2 - 1.0);
+    let end_angle = start_angle + std::f64::consts::FRAC_PI_2;
+    let start = (cx + r * start_angle.cos(), cy + r * start_angle.sin());
+    let end = (cx + r * end_angle.cos(), cy + r * end_angle.sin());
+    let tangent = |a: f64| (-a.sin(), a.cos());
+    let (tx0, ty0) = tangent(start_angle);
+    let (tx1, ty1) = tangent(end_angle);
+    let ctrl1 = (start.0 + k * tx0, start.1 + k * ty0);
+    let ctrl2 = (end.0 - k * tx1, end.1 - k * ty1);
+    (ctrl1, ctrl2, end)
+}
+
 /// Draw a filled circle inscribed in the module cell.
 fn draw_circle_module(
     img: &mut RgbaImage,
@@ -140,10 +630,11 @@ fn draw_circle_module(
     module_size: u32,
     color: Rgba<u8>,
     img_size: u32,
+    fill_ratio: f64,
 ) {
     let center_x = px as f64 + module_size as f64 / 2.0;
     let center_y = py as f64 + module_size as f64 / 2.0;
-    let radius = module_size as f64 / 2.0;
+    let radius = module_size as f64 * 0.5 * fill_ratio;
     let r_sq = radius * radius;
 
     for dy in 0..module_size {
@@ -243,20 +734,191 @@ fn draw_rounded_module(
     }
 }
 
+/// Fractional pixel coverage of a circle of `radius` centered at `(cx, cy)`,
+/// sampled at the center of pixel `(ix, iy)`. 1.0 fully inside, 0.0 fully
+/// outside, with a smooth ramp across the one-pixel-wide boundary band.
+fn circle_coverage(ix: u32, iy: u32, cx: f64, cy: f64, radius: f64) -> f64 {
+    let dist_x = ix as f64 + 0.5 - cx;
+    let dist_y = iy as f64 + 0.5 - cy;
+    let dist = (dist_x * dist_x + dist_y * dist_y).sqrt();
+    (0.5 - (dist - radius)).clamp(0.0, 1.0)
+}
+
+/// Anti-aliased variant of `draw_circle_module`: alpha-blends `color` scaled
+/// by analytic coverage instead of a hard inside/outside test.
+fn draw_circle_module_aa(
+    img: &mut RgbaImage,
+    px: u32,
+    py: u32,
+    module_size: u32,
+    color: Rgba<u8>,
+    img_size: u32,
+    fill_ratio: f64,
+) {
+    let center_x = px as f64 + module_size as f64 / 2.0;
+    let center_y = py as f64 + module_size as f64 / 2.0;
+    let radius = module_size as f64 * 0.5 * fill_ratio;
+
+    for dy in 0..module_size {
+        for dx in 0..module_size {
+            let ix = px + dx;
+            let iy = py + dy;
+            if ix >= img_size || iy >= img_size {
+                continue;
+            }
+            let coverage = circle_coverage(ix, iy, center_x, center_y, radius);
+            if coverage > 0.0 {
+                let bg = *img.get_pixel(ix, iy);
+                let mut fg = color;
+                fg.0[3] = (fg.0[3] as f64 * coverage).round() as u8;
+                img.put_pixel(ix, iy, alpha_blend(&bg, &fg));
+            }
+        }
+    }
+}
+
+/// Anti-aliased variant of `draw_rounded_module`: same corner-rounding rule,
+/// but corner pixels get blended by analytic arc coverage instead of a hard
+/// in/out test.
+fn draw_rounded_module_aa(
+    img: &mut RgbaImage,
+    px: u32,
+    py: u32,
+    module_size: u32,
+    color: Rgba<u8>,
+    img_size: u32,
+    neighbors: &[bool; 4], // [top, right, bottom, left]
+) {
+    let radius = (module_size as f64 * 0.35).max(1.0);
+
+    let round_tl = !neighbors[0] && !neighbors[3];
+    let round_tr = !neighbors[0] && !neighbors[1];
+    let round_bl = !neighbors[2] && !neighbors[3];
+    let round_br = !neighbors[2] && !neighbors[1];
+
+    for dy in 0..module_size {
+        for dx in 0..module_size {
+            let ix = px + dx;
+            let iy = py + dy;
+            if ix >= img_size || iy >= img_size {
+                continue;
+            }
+
+            let in_tl = dx as f64 <= radius && dy as f64 <= radius;
+            let in_tr = (module_size - 1 - dx) as f64 <= radius && dy as f64 <= radius;
+            let in_bl = dx as f64 <= radius && (module_size - 1 - dy) as f64 <= radius;
+            let in_br =
+                (module_size - 1 - dx) as f64 <= radius && (module_size - 1 - dy) as f64 <= radius;
+
+            let mut coverage = 1.0f64;
+
+            if round_tl && in_tl {
+                let cx = px as f64 + radius;
+                let cy = py as f64 + radius;
+                coverage = coverage.min(circle_coverage(ix, iy, cx, cy, radius));
+            }
+            if round_tr && in_tr {
+                let cx = (px + module_size) as f64 - radius;
+                let cy = py as f64 + radius;
+                coverage = coverage.min(circle_coverage(ix, iy, cx, cy, radius));
+            }
+            if round_bl && in_bl {
+                let cx = px as f64 + radius;
+                let cy = (py + module_size) as f64 - radius;
+                coverage = coverage.min(circle_coverage(ix, iy, cx, cy, radius));
+            }
+            if round_br && in_br {
+                let cx = (px + module_size) as f64 - radius;
+                let cy = (py + module_size) as f64 - radius;
+                coverage = coverage.min(circle_coverage(ix, iy, cx, cy, radius));
+            }
+
+            if coverage > 0.0 {
+                let bg = *img.get_pixel(ix, iy);
+                let mut fg = color;
+                fg.0[3] = (fg.0[3] as f64 * coverage).round() as u8;
+                img.put_pixel(ix, iy, alpha_blend(&bg, &fg));
+            }
+        }
+    }
+}
+
+/// Builds the SVG `<defs>` block (empty for `Solid`) and the `fill="..."`
+/// value every module shape should use — a plain hex color for `Solid`, or
+/// a `url(#qrFill)` reference into a `<linearGradient>`/`<radialGradient>`
+/// for the gradient variants. Gradient coordinates are left in the
+/// default `objectBoundingBox` units, which matches the `[0.0, 1.0]`
+/// normalized module space `Fill::color_at` samples in `generate_png`.
+fn svg_gradient_defs(fill: &Fill) -> (String, String) {
+    let stop_tags = |stops: &[ColorStop]| -> String {
+        stops
+            .iter()
+            .map(|(t, c)| {
+                format!(
+                    r##"<stop offset="{:.4}" stop-color="#{:02x}{:02x}{:02x}" stop-opacity="{:.4}"/>"##,
+                    t.clamp(0.0, 1.0),
+                    c[0],
+                    c[1],
+                    c[2],
+                    c[3] as f64 / 255.0
+                )
+            })
+            .collect()
+    };
+
+    match fill {
+        Fill::Solid(c) => (
+            String::new(),
+            format!("#{:02x}{:02x}{:02x}", c[0], c[1], c[2]),
+        ),
+        Fill::LinearGradient { start, end, stops } => {
+            let defs = format!(
+                r#"<defs><linearGradient id="qrFill" x1="{:.4}" y1="{:.4}" x2="{:.4}" y2="{:.4}">{}</linearGradient></defs>
+"#,
+                start.0,
+                start.1,
+                end.0,
+                end.1,
+                stop_tags(stops)
+            );
+            (defs, "url(#qrFill)".to_string())
+        }
+        Fill::RadialGradient {
+            center,
+            radius,
+            stops,
+        } => {
+            let defs = format!(
+                r#"<defs><radialGradient id="qrFill" cx="{:.4}" cy="{:.4}" r="{:.4}">{}</radialGradient></defs>
+"#,
+                center.0,
+                center.1,
+                radius,
+                stop_tags(stops)
+            );
+            (defs, "url(#qrFill)".to_string())
+        }
+    }
+}
+
 pub fn generate_svg(data: &str, options: &QrOptions) -> Result<String, String> {
-    let code = QrCode::with_error_correction_level(data, options.error_correction)
-        .map_err(|e: QrError| format!("QR encoding error: {}", e))?;
+    render_svg(build_qr_code(data, options)?, options)
+}
+
+/// Same as `generate_svg`, but for a raw byte payload encoded in QR byte
+/// mode instead of a UTF-8 string (see `build_qr_code_bytes`).
+pub fn generate_svg_bytes(data: &[u8], options: &QrOptions) -> Result<String, String> {
+    render_svg(build_qr_code_bytes(data, options)?, options)
+}
 
+fn render_svg(code: QrCode, options: &QrOptions) -> Result<String, String> {
     let modules = code.to_colors();
     let module_count = code.width() as u32;
     let quiet_zone = 4u32;
     let total_modules = module_count + quiet_zone * 2;
     let module_size = options.size as f64 / total_modules as f64;
 
-    let fg_hex = format!(
-        "#{:02x}{:02x}{:02x}",
-        options.fg_color[0], options.fg_color[1], options.fg_color[2]
-    );
+    let (gradient_defs, fg_hex) = svg_gradient_defs(&options.fg_color);
     let bg_hex = format!(
         "#{:02x}{:02x}{:02x}",
         options.bg_color[0], options.bg_color[1], options.bg_color[2]
@@ -265,23 +927,66 @@ pub fn generate_svg(data: &str, options: &QrOptions) -> Result<String, String> {
     let mut svg = format!(
         r#"<?xml version="1.0" encoding="UTF-8"?>
 <svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {size} {size}" width="{size}" height="{size}">
-<rect width="{size}" height="{size}" fill="{bg}"/>
+{defs}<rect width="{size}" height="{size}" fill="{bg}"/>
 "#,
         size = options.size,
+        defs = gradient_defs,
         bg = bg_hex,
     );
 
+    if options.style == QrStyle::Merged {
+        let corner_r = module_size * 0.35;
+        let eligible = |x: usize, y: usize| {
+            !(options.square_finder_modules && is_finder_module(x, y, module_count as usize))
+        };
+        let contours = trace_merged_contours(&modules, module_count as usize, corner_r, eligible);
+        let mut path_d = String::new();
+        for contour in &contours {
+            path_d.push_str(&svg_path_from_contour(contour, quiet_zone as f64, module_size));
+            path_d.push(' ');
+        }
+        if !path_d.is_empty() {
+            svg.push_str(&format!(r#"<path d="{}" fill="{}"/>"#, path_d.trim(), fg_hex));
+            svg.push('\n');
+        }
+        if options.square_finder_modules {
+            for (y, row) in modules.chunks(module_count as usize).enumerate() {
+                for (x, &module) in row.iter().enumerate() {
+                    if module == qrcode::Color::Dark && is_finder_module(x, y, module_count as usize) {
+                        let px = (x as u32 + quiet_zone) as f64 * module_size;
+                        let py = (y as u32 + quiet_zone) as f64 * module_size;
+                        svg.push_str(&format!(
+                            r#"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" fill="{}"/>"#,
+                            px, py, module_size, module_size, fg_hex
+                        ));
+                        svg.push('\n');
+                    }
+                }
+            }
+        }
+        svg.push_str("</svg>");
+        return Ok(svg);
+    }
+
     for (y, row) in modules.chunks(module_count as usize).enumerate() {
         for (x, &module) in row.iter().enumerate() {
             if module == qrcode::Color::Dark {
                 let px = (x as u32 + quiet_zone) as f64 * module_size;
                 let py = (y as u32 + quiet_zone) as f64 * module_size;
 
-                match options.style {
+                let effective_style = if options.square_finder_modules
+                    && is_finder_module(x, y, module_count as usize)
+                {
+                    QrStyle::Square
+                } else {
+                    options.style
+                };
+
+                match effective_style {
                     QrStyle::Dots => {
                         let cx = px + module_size / 2.0;
                         let cy = py + module_size / 2.0;
-                        let r = module_size / 2.0;
+                        let r = module_size * 0.5 * options.dot_fill_ratio;
                         svg.push_str(&format!(
                             r#"<circle cx="{:.2}" cy="{:.2}" r="{:.2}" fill="{}"/>"#,
                             cx, cy, r, fg_hex
@@ -306,6 +1011,7 @@ pub fn generate_svg(data: &str, options: &QrOptions) -> Result<String, String> {
                             px, py, module_size, module_size, fg_hex
                         ));
                     }
+                    QrStyle::Merged => unreachable!("Merged handled separately above"),
                 }
                 svg.push('\n');
             }
@@ -316,6 +1022,36 @@ pub fn generate_svg(data: &str, options: &QrOptions) -> Result<String, String> {
     Ok(svg)
 }
 
+/// Renders one `trace_merged_contours` ring as an SVG subpath, scaling its
+/// module-grid coordinates into pixel space the same way the per-module
+/// renderers do (`(grid + quiet_zone) * module_size`).
+fn svg_path_from_contour(contour: &Contour, quiet_zone: f64, module_size: f64) -> String {
+    let tx = |gx: f64| (gx + quiet_zone) * module_size;
+    let ty = |gy: f64| (gy + quiet_zone) * module_size;
+
+    let mut d = format!("M{:.2},{:.2}", tx(contour.start.0), ty(contour.start.1));
+    for seg in &contour.segs {
+        match seg {
+            ContourSeg::Line(x, y) => {
+                d.push_str(&format!(" L{:.2},{:.2}", tx(*x), ty(*y)));
+            }
+            ContourSeg::Arc { c1, c2, end } => {
+                d.push_str(&format!(
+                    " C{:.2},{:.2} {:.2},{:.2} {:.2},{:.2}",
+                    tx(c1.0),
+                    ty(c1.1),
+                    tx(c2.0),
+                    ty(c2.1),
+                    tx(end.0),
+                    ty(end.1)
+                ));
+            }
+        }
+    }
+    d.push_str(" Z");
+    d
+}
+
 /// Generate an SVG rect with selectively rounded corners via an SVG path.
 fn svg_rounded_rect(
     x: f64,
@@ -399,6 +1135,54 @@ fn svg_rounded_rect(
     )
 }
 
+/// Render the QR as printable Unicode using half-block characters (`▀`,
+/// `▄`, `█`, space), packing two module rows into one text row so the
+/// result reads at roughly a 1:1 character aspect ratio in a monospace
+/// terminal. Reuses the same `code.to_colors()` module grid as
+/// `generate_png`/`generate_svg`; `invert` swaps dark/light for
+/// light-on-dark terminals and `quiet_zone` toggles the usual blank
+/// module border around the symbol.
+pub fn generate_text(
+    data: &str,
+    options: &QrOptions,
+    invert: bool,
+    quiet_zone: bool,
+) -> Result<String, String> {
+    let code = build_qr_code(data, options)?;
+
+    let modules = code.to_colors();
+    let module_count = code.width() as usize;
+    let quiet = if quiet_zone { 4usize } else { 0usize };
+    let total = module_count + quiet * 2;
+
+    let is_dark = |x: usize, y: usize| -> bool {
+        if x < quiet || y < quiet || x >= quiet + module_count || y >= quiet + module_count {
+            false
+        } else {
+            modules[(y - quiet) * module_count + (x - quiet)] == qrcode::Color::Dark
+        }
+    };
+
+    let mut out = String::new();
+    let mut y = 0;
+    while y < total {
+        for x in 0..total {
+            let top = is_dark(x, y) ^ invert;
+            let bottom = y + 1 < total && is_dark(x, y + 1) ^ invert;
+            out.push(match (top, bottom) {
+                (true, true) => '█',
+                (true, false) => '▀',
+                (false, true) => '▄',
+                (false, false) => ' ',
+            });
+        }
+        out.push('\n');
+        y += 2;
+    }
+
+    Ok(out)
+}
+
 /// Generate WiFi QR code data string
 pub fn wifi_data(ssid: &str, password: &str, encryption: &str, hidden: bool) -> String {
     format!(
@@ -576,101 +1360,309 @@ pub fn svg_logo_overlay(logo_data: &[u8], qr_size: u32, logo_pct: u8) -> Result<
     ))
 }
 
-/// Generate a PDF containing the QR code as vector graphics.
-/// The `size` in options is used as the page size in points (1 pt = 1/72 inch).
-/// Returns raw PDF bytes.
-pub fn generate_pdf(data: &str, options: &QrOptions) -> Result<Vec<u8>, String> {
+/// A 2x3 affine matrix `[a c tx; b d ty]` mapping `(x, y)` to `(a*x + c*y +
+/// tx, b*x + d*y + ty)`, mirroring pathfinder's `Transform2F`. Used to
+/// rotate/scale/translate PDF module geometry before it becomes
+/// `Op::DrawRectangle`/`Polygon` points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Transform2D {
+    a: f32,
+    b: f32,
+    c: f32,
+    d: f32,
+    tx: f32,
+    ty: f32,
+}
+
+impl Transform2D {
+    fn identity() -> Self {
+        Transform2D { a: 1.0, b: 0.0, c: 0.0, d: 1.0, tx: 0.0, ty: 0.0 }
+    }
+
+    /// Builds the rotation matrix from the unit vector `(cos theta, sin
+    /// theta)` so callers applying it to many points pay for `sin`/`cos`
+    /// once, not per point.
+    fn from_rotation(theta: f32) -> Self {
+        let (sin, cos) = theta.sin_cos();
+        Transform2D { a: cos, b: sin, c: -sin, d: cos, tx: 0.0, ty: 0.0 }
+    }
+
+    fn from_scale(sx: f32, sy: f32) -> Self {
+        Transform2D { a: sx, b: 0.0, c: 0.0, d: sy, tx: 0.0, ty: 0.0 }
+    }
+
+    fn from_translation(tx: f32, ty: f32) -> Self {
+        Transform2D { a: 1.0, b: 0.0, c: 0.0, d: 1.0, tx, ty }
+    }
+
+    /// Composes `self` followed by `other`, i.e. `self.then(other).apply(p)
+    /// == other.apply(self.apply(p))`.
+    fn then(&self, other: &Transform2D) -> Transform2D {
+        Transform2D {
+            a: self.a * other.a + self.b * other.c,
+            b: self.a * other.b + self.b * other.d,
+            c: self.c * other.a + self.d * other.c,
+            d: self.c * other.b + self.d * other.d,
+            tx: self.tx * other.a + self.ty * other.c + other.tx,
+            ty: self.tx * other.b + self.ty * other.d + other.ty,
+        }
+    }
+
+    fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        (self.a * x + self.c * y + self.tx, self.b * x + self.d * y + self.ty)
+    }
+
+    fn is_identity(&self) -> bool {
+        *self == Transform2D::identity()
+    }
+}
+
+/// Applies `transform` to every anchor/control point of `polygon` in place.
+fn transform_polygon(polygon: &mut printpdf::Polygon, transform: &Transform2D) {
     use printpdf::*;
+    if transform.is_identity() {
+        return;
+    }
+    for ring in &mut polygon.rings {
+        for point in &mut ring.points {
+            let (x, y) = transform.apply(point.p.x.0, point.p.y.0);
+            point.p = Point { x: Pt(x), y: Pt(y) };
+        }
+    }
+}
 
-    let code = QrCode::with_error_correction_level(data, options.error_correction)
-        .map_err(|e: QrError| format!("QR encoding error: {}", e))?;
+/// Builds a filled axis-aligned rectangle `(x, y, w, h)` as a `Polygon`
+/// with `transform` applied to its four corners — used in place of
+/// `Op::DrawRectangle` whenever `transform` isn't the identity, since a
+/// rotated rectangle is no longer axis-aligned.
+fn transformed_rect_polygon(x: f32, y: f32, w: f32, h: f32, transform: &Transform2D) -> printpdf::Polygon {
+    use printpdf::*;
+    let corners = [(x, y), (x + w, y), (x + w, y + h), (x, y + h)];
+    let points = corners
+        .iter()
+        .map(|&(cx, cy)| {
+            let (tx, ty) = transform.apply(cx, cy);
+            LinePoint { p: Point { x: Pt(tx), y: Pt(ty) }, bezier: false }
+        })
+        .collect();
+    Polygon {
+        rings: vec![PolygonRing { points }],
+        mode: PaintMode::Fill,
+        winding_order: WindingOrder::NonZero,
+    }
+}
+
+/// Emits the vector ops for one QR symbol's background and dark modules
+/// into a `size`×`size` square anchored at `(origin_x, origin_y)` (PDF
+/// points, origin bottom-left), with `transform` applied to every emitted
+/// point. Shared by `generate_pdf` (one code filling the whole page, with
+/// `transform` carrying any `rotation_degrees`/`scale`) and
+/// `generate_pdf_batch` (one code per grid cell, always identity).
+fn draw_qr_ops(
+    ops: &mut Vec<printpdf::Op>,
+    code: &QrCode,
+    options: &QrOptions,
+    origin_x: f32,
+    origin_y: f32,
+    size: f32,
+    transform: &Transform2D,
+) {
+    use printpdf::*;
 
     let modules = code.to_colors();
     let module_count = code.width() as u32;
     let quiet_zone = 4u32;
     let total_modules = module_count + quiet_zone * 2;
-    let page_size_pt = options.size as f32;
-    let module_size_pt = page_size_pt / total_modules as f32;
-
-    // Convert points to mm for page dimensions (printpdf uses Mm for page size)
-    let page_size_mm: Mm = Pt(page_size_pt).into();
+    let module_size_pt = size / total_modules as f32;
 
-    let mut ops: Vec<Op> = Vec::new();
-
-    // Draw background
     let bg_r = options.bg_color[0] as f32 / 255.0;
     let bg_g = options.bg_color[1] as f32 / 255.0;
     let bg_b = options.bg_color[2] as f32 / 255.0;
 
     ops.push(Op::SetFillColor { col: Color::Rgb(Rgb::new(bg_r, bg_g, bg_b, None)) });
     ops.push(Op::SetOutlineThickness { pt: Pt(0.0) });
-    let mut bg_rect = Rect::from_xywh(Pt(0.0), Pt(0.0), Pt(page_size_pt), Pt(page_size_pt));
-    bg_rect.mode = Some(PaintMode::Fill);
-    bg_rect.winding_order = Some(WindingOrder::NonZero);
-    ops.push(Op::DrawRectangle { rectangle: bg_rect });
+    if transform.is_identity() {
+        let mut bg_rect = Rect::from_xywh(Pt(origin_x), Pt(origin_y), Pt(size), Pt(size));
+        bg_rect.mode = Some(PaintMode::Fill);
+        bg_rect.winding_order = Some(WindingOrder::NonZero);
+        ops.push(Op::DrawRectangle { rectangle: bg_rect });
+    } else {
+        let polygon = transformed_rect_polygon(origin_x, origin_y, size, size, transform);
+        ops.push(Op::DrawPolygon { polygon });
+    }
 
-    // Set foreground color
-    let fg_r = options.fg_color[0] as f32 / 255.0;
-    let fg_g = options.fg_color[1] as f32 / 255.0;
-    let fg_b = options.fg_color[2] as f32 / 255.0;
+    // PDF output doesn't support gradient shading patterns yet, so
+    // gradients fall back to a single representative color here.
+    let fg_flat = options.fg_color.flat_color();
+    let fg_r = fg_flat[0] as f32 / 255.0;
+    let fg_g = fg_flat[1] as f32 / 255.0;
+    let fg_b = fg_flat[2] as f32 / 255.0;
 
     ops.push(Op::SetFillColor { col: Color::Rgb(Rgb::new(fg_r, fg_g, fg_b, None)) });
 
-    // Draw QR modules
+    if options.style == QrStyle::Merged {
+        let corner_r = module_size_pt * 0.35;
+        let eligible = |x: usize, y: usize| {
+            !(options.square_finder_modules && is_finder_module(x, y, module_count as usize))
+        };
+        let contours =
+            trace_merged_contours(&modules, module_count as usize, corner_r as f64, eligible);
+        // All rings go into one polygon (not one `DrawPolygon` op each) so the
+        // `NonZero` winding rule correctly leaves enclosed light "holes"
+        // unpainted instead of each hole ring being filled as its own shape.
+        let rings: Vec<PolygonRing> = contours
+            .iter()
+            .map(|c| pdf_polygon_ring_from_contour(c, origin_x, origin_y, size, quiet_zone as f64, module_size_pt as f64))
+            .collect();
+        if !rings.is_empty() {
+            let mut polygon = Polygon {
+                rings,
+                mode: PaintMode::Fill,
+                winding_order: WindingOrder::NonZero,
+            };
+            transform_polygon(&mut polygon, transform);
+            ops.push(Op::DrawPolygon { polygon });
+        }
+        if options.square_finder_modules {
+            for (y, row) in modules.chunks(module_count as usize).enumerate() {
+                for (x, &module) in row.iter().enumerate() {
+                    if module == qrcode::Color::Dark && is_finder_module(x, y, module_count as usize) {
+                        let px = origin_x + (x as u32 + quiet_zone) as f32 * module_size_pt;
+                        let py = origin_y + size - (y as u32 + quiet_zone + 1) as f32 * module_size_pt;
+                        if transform.is_identity() {
+                            let mut rect = Rect::from_xywh(Pt(px), Pt(py), Pt(module_size_pt), Pt(module_size_pt));
+                            rect.mode = Some(PaintMode::Fill);
+                            rect.winding_order = Some(WindingOrder::NonZero);
+                            ops.push(Op::DrawRectangle { rectangle: rect });
+                        } else {
+                            let polygon = transformed_rect_polygon(px, py, module_size_pt, module_size_pt, transform);
+                            ops.push(Op::DrawPolygon { polygon });
+                        }
+                    }
+                }
+            }
+        }
+        return;
+    }
+
     // PDF coordinate system: origin at bottom-left, Y goes up
     for (y, row) in modules.chunks(module_count as usize).enumerate() {
         for (x, &module) in row.iter().enumerate() {
             if module == qrcode::Color::Dark {
-                let px = (x as u32 + quiet_zone) as f32 * module_size_pt;
+                let px = origin_x + (x as u32 + quiet_zone) as f32 * module_size_pt;
                 // Flip Y: PDF origin is bottom-left, QR origin is top-left
-                let py = page_size_pt - (y as u32 + quiet_zone + 1) as f32 * module_size_pt;
+                let py = origin_y + size - (y as u32 + quiet_zone + 1) as f32 * module_size_pt;
+
+                let effective_style = if options.square_finder_modules
+                    && is_finder_module(x, y, module_count as usize)
+                {
+                    QrStyle::Square
+                } else {
+                    options.style
+                };
 
-                match options.style {
+                match effective_style {
                     QrStyle::Dots => {
-                        // Approximate circle with polygon segments
+                        // Exact circle via four cubic-Bezier quarter-arcs,
+                        // reusing the same construction as rounded-rect corners.
                         let cx = px + module_size_pt / 2.0;
                         let cy = py + module_size_pt / 2.0;
-                        let r = module_size_pt / 2.0;
-                        let segments = 24u32;
-                        let circle_points: Vec<LinePoint> = (0..segments)
-                            .map(|i| {
-                                let angle = 2.0 * std::f32::consts::PI * i as f32 / segments as f32;
-                                LinePoint {
-                                    p: Point {
-                                        x: Pt(cx + r * angle.cos()),
-                                        y: Pt(cy + r * angle.sin()),
-                                    },
-                                    bezier: false,
-                                }
-                            })
-                            .collect();
-                        let circle = Polygon {
+                        let r = module_size_pt * 0.5 * options.dot_fill_ratio as f32;
+                        let quadrants = [0.0, std::f32::consts::FRAC_PI_2, std::f32::consts::PI, 3.0 * std::f32::consts::FRAC_PI_2];
+                        let mut circle_points: Vec<LinePoint> = Vec::with_capacity(16);
+                        for &angle in &quadrants {
+                            circle_points.extend(quarter_circle_bezier(cx, cy, r, angle));
+                        }
+                        let mut circle = Polygon {
                             rings: vec![PolygonRing { points: circle_points }],
                             mode: PaintMode::Fill,
                             winding_order: WindingOrder::NonZero,
                         };
+                        transform_polygon(&mut circle, transform);
                         ops.push(Op::DrawPolygon { polygon: circle });
                     }
                     QrStyle::Rounded => {
                         let neighbors = get_neighbors(&modules, module_count as usize, x, y);
                         let corner_r = module_size_pt * 0.35;
-                        let polygon = build_pdf_rounded_rect(px, py, module_size_pt, module_size_pt, corner_r, &neighbors);
+                        let mut polygon = build_pdf_rounded_rect(px, py, module_size_pt, module_size_pt, corner_r, &neighbors);
+                        transform_polygon(&mut polygon, transform);
                         ops.push(Op::DrawPolygon { polygon });
                     }
                     QrStyle::Square => {
-                        let mut rect = Rect::from_xywh(Pt(px), Pt(py), Pt(module_size_pt), Pt(module_size_pt));
-                        rect.mode = Some(PaintMode::Fill);
-                        rect.winding_order = Some(WindingOrder::NonZero);
-                        ops.push(Op::DrawRectangle { rectangle: rect });
+                        if transform.is_identity() {
+                            let mut rect = Rect::from_xywh(Pt(px), Pt(py), Pt(module_size_pt), Pt(module_size_pt));
+                            rect.mode = Some(PaintMode::Fill);
+                            rect.winding_order = Some(WindingOrder::NonZero);
+                            ops.push(Op::DrawRectangle { rectangle: rect });
+                        } else {
+                            let polygon = transformed_rect_polygon(px, py, module_size_pt, module_size_pt, transform);
+                            ops.push(Op::DrawPolygon { polygon });
+                        }
                     }
+                    QrStyle::Merged => unreachable!("Merged returns earlier in this function"),
                 }
             }
         }
     }
+}
+
+/// Generate a PDF containing the QR code as vector graphics, optionally with
+/// a logo (`(raw image bytes, size percent 5-40)`) overlaid at the center —
+/// the vector-output counterpart of `overlay_logo_png`/`svg_logo_overlay`.
+/// The `size` in options is used as the side length of the unrotated symbol
+/// in points (1 pt = 1/72 inch). When `rotation_degrees`/`scale` aren't the
+/// defaults, the symbol is rotated/scaled around its own center and the
+/// page is resized to the bounding box of the transformed corners, so the
+/// whole symbol stays on the page with no extra cropping. The logo, if any,
+/// is placed at the untransformed center and is not itself rotated/scaled.
+/// Returns raw PDF bytes.
+pub fn generate_pdf(
+    data: &str,
+    options: &QrOptions,
+    logo: Option<(&[u8], u8)>,
+) -> Result<Vec<u8>, String> {
+    use printpdf::*;
+
+    let code = build_qr_code(data, options)?;
+    let square_size = options.size as f32;
+    let center = square_size / 2.0;
+
+    let local_transform = Transform2D::from_translation(-center, -center)
+        .then(&Transform2D::from_scale(options.scale, options.scale))
+        .then(&Transform2D::from_rotation(options.rotation_degrees.to_radians()))
+        .then(&Transform2D::from_translation(center, center));
 
-    // Build document
-    let page = PdfPage::new(page_size_mm, page_size_mm, ops);
+    let corners = [
+        (0.0, 0.0),
+        (square_size, 0.0),
+        (square_size, square_size),
+        (0.0, square_size),
+    ];
+    let transformed_corners: Vec<(f32, f32)> =
+        corners.iter().map(|&(x, y)| local_transform.apply(x, y)).collect();
+    let min_x = transformed_corners.iter().map(|p| p.0).fold(f32::INFINITY, f32::min);
+    let min_y = transformed_corners.iter().map(|p| p.1).fold(f32::INFINITY, f32::min);
+    let max_x = transformed_corners.iter().map(|p| p.0).fold(f32::NEG_INFINITY, f32::max);
+    let max_y = transformed_corners.iter().map(|p| p.1).fold(f32::NEG_INFINITY, f32::max);
+
+    let page_width_pt = max_x - min_x;
+    let page_height_pt = max_y - min_y;
+    let transform = local_transform.then(&Transform2D::from_translation(-min_x, -min_y));
+
+    let mut ops: Vec<Op> = Vec::new();
+    draw_qr_ops(&mut ops, &code, options, 0.0, 0.0, square_size, &transform);
+
+    // Convert points to mm for page dimensions (printpdf uses Mm for page size)
+    let page_width_mm: Mm = Pt(page_width_pt).into();
+    let page_height_mm: Mm = Pt(page_height_pt).into();
     let mut doc = PdfDocument::new("QR Code");
+
+    if let Some((logo_data, logo_pct)) = logo {
+        let (center_x, center_y) = transform.apply(center, center);
+        draw_pdf_logo(&mut ops, &mut doc, logo_data, logo_pct, square_size, center_x, center_y)?;
+    }
+
+    let page = PdfPage::new(page_width_mm, page_height_mm, ops);
     doc.pages.push(page);
 
     let mut warnings = Vec::new();
@@ -679,6 +1671,225 @@ pub fn generate_pdf(data: &str, options: &QrOptions) -> Result<Vec<u8>, String>
     Ok(pdf_bytes)
 }
 
+/// Draws a white backing square and the logo image centered at
+/// `(center_x, center_y)` (PDF points), sized to `logo_pct` (clamped 5-40)
+/// percent of `square_size` — mirrors the padding/clamping `overlay_logo_png`
+/// and `svg_logo_overlay` use for their own formats.
+fn draw_pdf_logo(
+    ops: &mut Vec<printpdf::Op>,
+    doc: &mut printpdf::PdfDocument,
+    logo_data: &[u8],
+    logo_pct: u8,
+    square_size: f32,
+    center_x: f32,
+    center_y: f32,
+) -> Result<(), String> {
+    use printpdf::*;
+
+    let mut warnings = Vec::new();
+    let image = RawImage::decode_from_bytes(logo_data, &mut warnings)
+        .map_err(|e| format!("Failed to load logo image: {:?}", e))?;
+
+    let pct = (logo_pct as f32).clamp(5.0, 40.0);
+    let target_pt = square_size * pct / 100.0;
+    let padding_pt = target_pt * 0.15;
+    let bg_size_pt = target_pt + padding_pt * 2.0;
+
+    ops.push(Op::SetFillColor { col: Color::Rgb(Rgb::new(1.0, 1.0, 1.0, None)) });
+    let mut bg_rect = Rect::from_xywh(
+        Pt(center_x - bg_size_pt / 2.0),
+        Pt(center_y - bg_size_pt / 2.0),
+        Pt(bg_size_pt),
+        Pt(bg_size_pt),
+    );
+    bg_rect.mode = Some(PaintMode::Fill);
+    bg_rect.winding_order = Some(WindingOrder::NonZero);
+    ops.push(Op::DrawRectangle { rectangle: bg_rect });
+
+    let (img_w, img_h) = (image.width.max(1) as f32, image.height.max(1) as f32);
+    let scale = (target_pt / img_w).min(target_pt / img_h);
+    let draw_w = img_w * scale;
+    let draw_h = img_h * scale;
+
+    let image_id = doc.add_image(&image);
+    ops.push(Op::UseXobject {
+        id: image_id,
+        transform: XObjectTransform {
+            translate_x: Some(Pt(center_x - draw_w / 2.0)),
+            translate_y: Some(Pt(center_y - draw_h / 2.0)),
+            rotate: None,
+            scale_x: Some(scale),
+            scale_y: Some(scale),
+            dpi: Some(72.0),
+        },
+    });
+
+    Ok(())
+}
+
+/// One QR code to place on a `generate_pdf_batch` label sheet, with an
+/// optional caption printed underneath it (e.g. a ticket holder's name or a
+/// wallet label).
+pub struct PdfItem {
+    pub data: String,
+    pub caption: Option<String>,
+}
+
+/// Page size for `generate_pdf_batch`, in points (1 pt = 1/72 inch).
+#[derive(Debug, Clone, Copy)]
+pub enum PdfPageSize {
+    A4,
+    Letter,
+    Custom { width_pt: f32, height_pt: f32 },
+}
+
+impl PdfPageSize {
+    fn dimensions_pt(self) -> (f32, f32) {
+        match self {
+            PdfPageSize::A4 => (595.28, 841.89),
+            PdfPageSize::Letter => (612.0, 792.0),
+            PdfPageSize::Custom { width_pt, height_pt } => (width_pt, height_pt),
+        }
+    }
+}
+
+/// Grid layout for `generate_pdf_batch`: how many cells fit on a page, how
+/// much blank margin surrounds each code within its cell, and the caption
+/// font size (0 disables captions even when `PdfItem::caption` is set).
+pub struct PdfLayout {
+    pub page_size: PdfPageSize,
+    pub rows: u32,
+    pub columns: u32,
+    pub cell_margin_pt: f32,
+    pub caption_font_size_pt: f32,
+}
+
+/// Lay `items` out across a grid of `layout.rows` x `layout.columns` cells
+/// per page, spilling onto additional pages once a page's cells fill up.
+/// Each cell reuses `draw_qr_ops` scaled to fit inside its margins, with an
+/// optional caption drawn in the margin below. Built for label sheets,
+/// event tickets, and paper-wallet-style printouts carrying several codes
+/// per page.
+pub fn generate_pdf_batch(
+    items: &[PdfItem],
+    options: &QrOptions,
+    layout: PdfLayout,
+) -> Result<Vec<u8>, String> {
+    use printpdf::*;
+
+    if items.is_empty() {
+        return Err("No items to render".to_string());
+    }
+    if layout.rows == 0 || layout.columns == 0 {
+        return Err("Layout must have at least one row and column".to_string());
+    }
+
+    let (page_w, page_h) = layout.page_size.dimensions_pt();
+    let cell_w = page_w / layout.columns as f32;
+    let cell_h = page_h / layout.rows as f32;
+    let caption_h = if layout.caption_font_size_pt > 0.0 {
+        layout.caption_font_size_pt * 1.4
+    } else {
+        0.0
+    };
+    let qr_size = (cell_w - layout.cell_margin_pt * 2.0)
+        .min(cell_h - layout.cell_margin_pt * 2.0 - caption_h);
+    if qr_size <= 0.0 {
+        return Err("Cell too small to fit a QR code at this grid size/margin".to_string());
+    }
+
+    let mut doc = PdfDocument::new("QR Code Sheet");
+    let font = doc.add_builtin_font(BuiltinFont::Helvetica);
+
+    let per_page = (layout.rows * layout.columns) as usize;
+    let page_size_mm: Mm = Pt(page_w).into();
+    let page_height_mm: Mm = Pt(page_h).into();
+
+    for page_items in items.chunks(per_page) {
+        let mut ops: Vec<Op> = Vec::new();
+
+        for (i, item) in page_items.iter().enumerate() {
+            let row = i as u32 / layout.columns;
+            let col = i as u32 % layout.columns;
+            let cell_x = col as f32 * cell_w;
+            let cell_y = page_h - (row + 1) as f32 * cell_h;
+
+            let qr_x = cell_x + (cell_w - qr_size) / 2.0;
+            let qr_y = cell_y + layout.cell_margin_pt + caption_h;
+
+            let code = build_qr_code(&item.data, options)?;
+            draw_qr_ops(&mut ops, &code, options, qr_x, qr_y, qr_size, &Transform2D::identity());
+
+            if let Some(caption) = item.caption.as_ref().filter(|_| caption_h > 0.0) {
+                ops.push(Op::StartTextSection);
+                ops.push(Op::SetFillColor { col: Color::Rgb(Rgb::new(0.0, 0.0, 0.0, None)) });
+                ops.push(Op::SetFontSize { size: Pt(layout.caption_font_size_pt), font: font.clone() });
+                ops.push(Op::SetTextCursor {
+                    pos: Point {
+                        x: Pt(cell_x + layout.cell_margin_pt),
+                        y: Pt(cell_y + layout.cell_margin_pt * 0.5),
+                    },
+                });
+                ops.push(Op::WriteText {
+                    items: vec![TextItem::Text(caption.clone())],
+                    font: font.clone(),
+                });
+                ops.push(Op::EndTextSection);
+            }
+        }
+
+        let page = PdfPage::new(page_size_mm, page_height_mm, ops);
+        doc.pages.push(page);
+    }
+
+    let mut warnings = Vec::new();
+    let pdf_bytes = doc.save(&PdfSaveOptions::default(), &mut warnings);
+
+    Ok(pdf_bytes)
+}
+
+/// Renders one `trace_merged_contours` ring as a PDF `PolygonRing`, scaling
+/// its module-grid coordinates into page points and flipping Y the same way
+/// the per-module renderer does (PDF origin is bottom-left, the module grid's
+/// is top-left). Callers combine all of a symbol's rings into a single
+/// `Polygon` so `NonZero` winding leaves enclosed light "holes" unpainted.
+fn pdf_polygon_ring_from_contour(
+    contour: &Contour,
+    origin_x: f32,
+    origin_y: f32,
+    size: f32,
+    quiet_zone: f64,
+    module_size_pt: f64,
+) -> printpdf::PolygonRing {
+    use printpdf::*;
+
+    let tx = |gx: f64| origin_x as f64 + (gx + quiet_zone) * module_size_pt;
+    let ty = |gy: f64| origin_y as f64 + size as f64 - (gy + quiet_zone) * module_size_pt;
+
+    let anchor = |x: f64, y: f64| LinePoint {
+        p: Point { x: Pt(x as f32), y: Pt(y as f32) },
+        bezier: false,
+    };
+    let ctrl = |x: f64, y: f64| LinePoint {
+        p: Point { x: Pt(x as f32), y: Pt(y as f32) },
+        bezier: true,
+    };
+
+    let mut points = vec![anchor(tx(contour.start.0), ty(contour.start.1))];
+    for seg in &contour.segs {
+        match seg {
+            ContourSeg::Line(x, y) => points.push(anchor(tx(*x), ty(*y))),
+            ContourSeg::Arc { c1, c2, end } => {
+                points.push(ctrl(tx(c1.0), ty(c1.1)));
+                points.push(ctrl(tx(c2.0), ty(c2.1)));
+                points.push(anchor(tx(end.0), ty(end.1)));
+            }
+        }
+    }
+
+    PolygonRing { points }
+}
+
 /// Build a polygon for a rounded rectangle with selective corner rounding based on neighbors.
 fn build_pdf_rounded_rect(
     x: f32,
@@ -690,6 +1901,12 @@ fn build_pdf_rounded_rect(
 ) -> printpdf::Polygon {
     use printpdf::*;
 
+    // Clamp to half the shorter side so two adjacent rounded corners on the
+    // same edge never claim more radius than the edge has room for — past
+    // that point the arcs overshoot and self-intersect, painting black
+    // artifacts under the NonZero winding rule.
+    let r = r.max(0.0).min(w * 0.5).min(h * 0.5);
+
     // In QR grid space: neighbors = [top_qr, right_qr, bottom_qr, left_qr]
     // Y is already flipped when calculating py (PDF bottom-left origin).
     // PDF "top" of the rect = high Y = QR top neighbor
@@ -703,48 +1920,46 @@ fn build_pdf_rounded_rect(
         return Rect::from_xywh(Pt(x), Pt(y), Pt(w), Pt(h)).to_polygon();
     }
 
-    // Build path with selective corner arcs (approximate arcs with line segments)
-    let arc_segments = 8u32;
+    // Build path with selective corner arcs, each an exact quarter-circle
+    // cubic Bezier rather than a line-segment approximation.
     let mut points: Vec<LinePoint> = Vec::new();
 
     let lp = |px: f32, py: f32| LinePoint { p: Point { x: Pt(px), y: Pt(py) }, bezier: false };
 
     // Bottom-left corner (bl radius)
     if bl > 0.0 {
-        for i in 0..=arc_segments {
-            let angle = std::f32::consts::PI + std::f32::consts::FRAC_PI_2 * i as f32 / arc_segments as f32;
-            points.push(lp(x + bl + bl * angle.cos(), y + bl + bl * angle.sin()));
-        }
+        points.extend(quarter_circle_bezier(x + bl, y + bl, bl, std::f32::consts::PI));
     } else {
         points.push(lp(x, y));
     }
 
     // Bottom-right corner (br radius)
     if br > 0.0 {
-        for i in 0..=arc_segments {
-            let angle = 3.0 * std::f32::consts::FRAC_PI_2 + std::f32::consts::FRAC_PI_2 * i as f32 / arc_segments as f32;
-            points.push(lp(x + w - br + br * angle.cos(), y + br + br * angle.sin()));
-        }
+        points.extend(quarter_circle_bezier(
+            x + w - br,
+            y + br,
+            br,
+            3.0 * std::f32::consts::FRAC_PI_2,
+        ));
     } else {
         points.push(lp(x + w, y));
     }
 
     // Top-right corner (tr radius)
     if tr > 0.0 {
-        for i in 0..=arc_segments {
-            let angle = std::f32::consts::FRAC_PI_2 * i as f32 / arc_segments as f32;
-            points.push(lp(x + w - tr + tr * angle.cos(), y + h - tr + tr * angle.sin()));
-        }
+        points.extend(quarter_circle_bezier(x + w - tr, y + h - tr, tr, 0.0));
     } else {
         points.push(lp(x + w, y + h));
     }
 
     // Top-left corner (tl radius)
     if tl > 0.0 {
-        for i in 0..=arc_segments {
-            let angle = std::f32::consts::FRAC_PI_2 + std::f32::consts::FRAC_PI_2 * i as f32 / arc_segments as f32;
-            points.push(lp(x + tl + tl * angle.cos(), y + h - tl + tl * angle.sin()));
-        }
+        points.extend(quarter_circle_bezier(
+            x + tl,
+            y + h - tl,
+            tl,
+            std::f32::consts::FRAC_PI_2,
+        ));
     } else {
         points.push(lp(x, y + h));
     }
@@ -756,7 +1971,113 @@ fn build_pdf_rounded_rect(
     }
 }
 
-/// Generate vCard data string
+/// Emits the 4 `LinePoint`s for one quarter-circle corner of radius `r`
+/// centered at `(cx, cy)`, sweeping counter-clockwise from `start_angle`
+/// through 90°, as a single cubic Bezier: start anchor, two bezier-flagged
+/// control points offset along the tangent at each end by the canonical
+/// circle-to-Bezier magic constant `k = r * (4/3) * (sqrt(2) - 1) ≈
+/// 0.5523 * r`, then the end anchor — matching how printpdf (and gofpdf's
+/// RoundedRect) expect a curve segment: anchor, ctrl1, ctrl2, anchor.
+fn quarter_circle_bezier(cx: f32, cy: f32, r: f32, start_angle: f32) -> [printpdf::LinePoint; 4] {
+    use printpdf::*;
+
+    let k = r * (4.0 / 3.0) * (std::f32::consts::SQRT_2 - 1.0);
+    let end_angle = start_angle + std::f32::consts::FRAC_PI_2;
+
+    let start = (cx + r * start_angle.cos(), cy + r * start_angle.sin());
+    let end = (cx + r * end_angle.cos(), cy + r * end_angle.sin());
+
+    // Tangent direction at angle `a` for travel counter-clockwise.
+    let tangent = |a: f32| (-a.sin(), a.cos());
+    let (tx0, ty0) = tangent(start_angle);
+    let (tx1, ty1) = tangent(end_angle);
+
+    let ctrl1 = (start.0 + k * tx0, start.1 + k * ty0);
+    let ctrl2 = (end.0 - k * tx1, end.1 - k * ty1);
+
+    let anchor = |p: (f32, f32)| LinePoint {
+        p: Point {
+            x: Pt(p.0),
+            y: Pt(p.1),
+        },
+        bezier: false,
+    };
+    let ctrl = |p: (f32, f32)| LinePoint {
+        p: Point {
+            x: Pt(p.0),
+            y: Pt(p.1),
+        },
+        bezier: true,
+    };
+
+    [anchor(start), ctrl(ctrl1), ctrl(ctrl2), anchor(end)]
+}
+
+/// Escapes a vCard (RFC 6350 §3.4) text value: backslash, comma, and
+/// semicolon are backslash-escaped, and embedded newlines become the
+/// literal `\n` escape sequence. Without this, a comma in an org name or a
+/// semicolon in an address silently shifts every field after it.
+fn vcard_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ',' => out.push_str("\\,"),
+            ';' => out.push_str("\\;"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {} // part of a \r\n pair; the \n branch above already emitted \n
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Splits a display name into vCard's structured `N:` components
+/// (`Family;Given;Additional;Prefix;Suffix`) — last word as the family name,
+/// everything before it as given name(s), the convention most "Add to
+/// Contacts" scanners expect when there's no better source to split on.
+fn vcard_n_field(name: &str) -> String {
+    let parts: Vec<&str> = name.split_whitespace().collect();
+    let (family, given) = match parts.split_last() {
+        Some((last, rest)) => (vcard_escape(last), vcard_escape(&rest.join(" "))),
+        None => (String::new(), String::new()),
+    };
+    format!("{};{};;;", family, given)
+}
+
+/// Folds one unfolded vCard content line to RFC 6350's 75-octet limit,
+/// inserting a newline plus a single leading space before each continuation
+/// — the "line folding" every vCard consumer is required to un-fold before
+/// reading a property.
+fn vcard_fold_line(line: &str) -> String {
+    const LIMIT: usize = 75;
+    if line.len() <= LIMIT {
+        return line.to_string();
+    }
+    let mut out = String::new();
+    let mut start = 0;
+    let mut first = true;
+    while start < line.len() {
+        let budget = if first { LIMIT } else { LIMIT - 1 }; // continuations lose 1 octet to the leading space
+        let mut end = (start + budget).min(line.len());
+        while end > start && !line.is_char_boundary(end) {
+            end -= 1;
+        }
+        if !first {
+            out.push('\n');
+            out.push(' ');
+        }
+        out.push_str(&line[start..end]);
+        start = end;
+        first = false;
+    }
+    out
+}
+
+/// Generate vCard data string. `version` selects `"3.0"` (the widely
+/// supported default) or `"4.0"`, which adds `TYPE=`/`VALUE=uri` parameters
+/// on `TEL`/`EMAIL`. Any other value falls back to 3.0. Field values are
+/// RFC 6350-escaped and long lines are folded at 75 octets.
 pub fn vcard_data(
     name: &str,
     email: Option<&str>,
@@ -764,24 +2085,401 @@ pub fn vcard_data(
     org: Option<&str>,
     title: Option<&str>,
     url: Option<&str>,
+    version: &str,
 ) -> String {
-    let mut vcard = String::from("BEGIN:VCARD\nVERSION:3.0\n");
-    vcard.push_str(&format!("FN:{}\n", name));
+    let is_v4 = version.trim() == "4.0";
+
+    let mut lines = vec![
+        "BEGIN:VCARD".to_string(),
+        format!("VERSION:{}", if is_v4 { "4.0" } else { "3.0" }),
+        format!("FN:{}", vcard_escape(name)),
+        format!("N:{}", vcard_n_field(name)),
+    ];
     if let Some(email) = email {
-        vcard.push_str(&format!("EMAIL:{}\n", email));
+        let prop = if is_v4 { "EMAIL;TYPE=home" } else { "EMAIL" };
+        lines.push(format!("{}:{}", prop, vcard_escape(email)));
     }
     if let Some(phone) = phone {
-        vcard.push_str(&format!("TEL:{}\n", phone));
+        if is_v4 {
+            lines.push(format!("TEL;VALUE=uri;TYPE=cell:tel:{}", vcard_escape(phone)));
+        } else {
+            lines.push(format!("TEL;TYPE=CELL:{}", vcard_escape(phone)));
+        }
     }
     if let Some(org) = org {
-        vcard.push_str(&format!("ORG:{}\n", org));
+        lines.push(format!("ORG:{}", vcard_escape(org)));
     }
     if let Some(title) = title {
-        vcard.push_str(&format!("TITLE:{}\n", title));
+        lines.push(format!("TITLE:{}", vcard_escape(title)));
+    }
+    if let Some(url) = url {
+        lines.push(format!("URL:{}", vcard_escape(url)));
+    }
+    lines.push("END:VCARD".to_string());
+
+    lines
+        .iter()
+        .map(|l| vcard_fold_line(l))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Escapes a MECARD field value: backslash, comma, semicolon, and colon are
+/// backslash-escaped (MECARD has no line-folding or newline-escape rules of
+/// its own — fields are expected to be single short tokens).
+fn mecard_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' | ',' | ';' | ':' => {
+                out.push('\\');
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Generate MECARD data — the compact `MECARD:N:...;TEL:...;;` format many
+/// phone camera apps recognize directly (rather than routing through a full
+/// "Add Contact" vCard import), encoding to a noticeably denser QR than
+/// vCard for the same fields.
+pub fn mecard_data(
+    name: &str,
+    email: Option<&str>,
+    phone: Option<&str>,
+    org: Option<&str>,
+    url: Option<&str>,
+) -> String {
+    let mut mecard = format!("MECARD:N:{};", mecard_escape(name));
+    if let Some(phone) = phone {
+        mecard.push_str(&format!("TEL:{};", mecard_escape(phone)));
+    }
+    if let Some(email) = email {
+        mecard.push_str(&format!("EMAIL:{};", mecard_escape(email)));
+    }
+    if let Some(org) = org {
+        mecard.push_str(&format!("ORG:{};", mecard_escape(org)));
     }
     if let Some(url) = url {
-        vcard.push_str(&format!("URL:{}\n", url));
+        mecard.push_str(&format!("URL:{};", mecard_escape(url)));
+    }
+    mecard.push(';');
+    mecard
+}
+
+/// Percent-encodes a string for use in a URI component (`mailto:`/`geo:`
+/// query strings), escaping everything except unreserved characters
+/// (RFC 3986 `ALPHA / DIGIT / "-" / "." / "_" / "~"`).
+fn percent_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+/// Generate a `geo:` URI for a location, the scheme most map apps and
+/// camera QR readers recognize directly. `label` is optional free text
+/// (e.g. a place name) carried in the conventional `?q=lat,lon(label)`
+/// query form; omitted when absent.
+pub fn geo_data(lat: f64, lon: f64, label: Option<&str>) -> String {
+    match label {
+        Some(label) => format!(
+            "geo:{lat},{lon}?q={lat},{lon}({label})",
+            lat = lat,
+            lon = lon,
+            label = percent_encode(label)
+        ),
+        None => format!("geo:{},{}", lat, lon),
+    }
+}
+
+/// Escapes a value for the `SMSTO:` scheme, where `:` is the field
+/// delimiter (mirroring how `wifi_data` escapes WIFI's `;` delimiter).
+fn smsto_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' | ':' => {
+                out.push('\\');
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Generate an `SMSTO:` QR payload that opens the messaging app with a
+/// recipient and prefilled body.
+pub fn sms_data(number: &str, body: &str) -> String {
+    format!("SMSTO:{}:{}", smsto_escape(number), smsto_escape(body))
+}
+
+/// Generate a `mailto:` QR payload with percent-encoded subject/body query
+/// parameters, so scanning opens an email client with the message prefilled.
+pub fn mailto_data(addr: &str, subject: Option<&str>, body: Option<&str>) -> String {
+    let mut params = Vec::new();
+    if let Some(subject) = subject {
+        params.push(format!("subject={}", percent_encode(subject)));
+    }
+    if let Some(body) = body {
+        params.push(format!("body={}", percent_encode(body)));
+    }
+    if params.is_empty() {
+        format!("mailto:{}", addr)
+    } else {
+        format!("mailto:{}?{}", addr, params.join("&"))
+    }
+}
+
+/// Escapes a text value per RFC 5545 (iCalendar): backslash, comma,
+/// semicolon, and newline are backslash-escaped.
+fn icalendar_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' | ',' | ';' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' => out.push_str("\\n"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Generate a minimal `VCALENDAR`/`VEVENT` QR payload for a single event.
+/// `start`/`end` are passed through as-is and expected to already be in
+/// iCalendar `DTSTART`/`DTEND` form (e.g. `20260115T090000Z`).
+pub fn calendar_event(summary: &str, start: &str, end: &str, location: Option<&str>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "BEGIN:VEVENT".to_string(),
+        format!("SUMMARY:{}", icalendar_escape(summary)),
+        format!("DTSTART:{}", start),
+        format!("DTEND:{}", end),
+    ];
+    if let Some(location) = location {
+        lines.push(format!("LOCATION:{}", icalendar_escape(location)));
+    }
+    lines.push("END:VEVENT".to_string());
+    lines.push("END:VCALENDAR".to_string());
+    lines.join("\n")
+}
+
+/// One detected QR symbol, before the caller attaches a `QrContent`
+/// classification of its `text`.
+pub struct DecodedSymbol {
+    pub text: String,
+    pub bounding_box: [(i32, i32); 4],
+    pub version: String,
+    pub ec_level: String,
+}
+
+/// Locates and decodes every QR symbol in an image (PNG/JPEG/...), so a
+/// scanned sheet with several codes round-trips all of them. Built on
+/// `rqrr`'s finder-pattern/Reed-Solomon pipeline, the inverse of the
+/// `generate_*` functions above.
+pub fn decode_image(bytes: &[u8]) -> Result<Vec<DecodedSymbol>, String> {
+    let img = image::load_from_memory(bytes).map_err(|e| format!("Failed to load image: {}", e))?;
+    let gray = img.to_luma8();
+    let mut prepared = rqrr::PreparedImage::prepare(gray);
+    Ok(prepared
+        .detect_grids()
+        .into_iter()
+        .filter_map(|grid| {
+            let bounding_box = grid.bounds.map(|p| (p.x, p.y));
+            grid.decode().ok().map(|(meta, text)| DecodedSymbol {
+                text,
+                bounding_box,
+                version: format!("{:?}", meta.version),
+                ec_level: meta.ecc_level.to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Renders `data` with `generate_png_bytes` and re-decodes it with `rqrr`,
+/// confirming the bytes survive the round trip. Useful for checking that a
+/// stylized `Dots`/`Rounded`/`Merged` renderer hasn't degraded a symbol past
+/// what a real scanner can still read at a given size/error-correction
+/// combination. Returns `false` (rather than an error) for any failure along
+/// the way — doesn't fit the requested version, image failed to render,
+/// no symbol detected, decode failed, or decoded bytes don't match — since
+/// all of those are just different ways the round trip didn't hold.
+pub fn verify_roundtrip(data: &[u8], options: &QrOptions) -> bool {
+    let Ok(png) = generate_png_bytes(data, options) else {
+        return false;
+    };
+    let Ok(img) = image::load_from_memory(&png) else {
+        return false;
+    };
+    let gray = img.to_luma8();
+    let mut prepared = rqrr::PreparedImage::prepare(gray);
+    let Some(grid) = prepared.detect_grids().into_iter().next() else {
+        return false;
+    };
+    grid.decode_bytes()
+        .map(|(_, decoded)| decoded == data)
+        .unwrap_or(false)
+}
+
+/// Structured classification of a decoded QR payload, mirroring how
+/// messenger/camera QR readers route well-known prefixes to a native
+/// action (join this Wi-Fi, add this contact, ...) instead of just
+/// showing raw text.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, schemars::JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QrContent {
+    Wifi {
+        ssid: String,
+        password: String,
+        encryption: String,
+        hidden: bool,
+    },
+    VCard {
+        name: Option<String>,
+        email: Option<String>,
+        phone: Option<String>,
+        org: Option<String>,
+    },
+    Email { address: String },
+    Phone { number: String },
+    Geo { latitude: f64, longitude: f64 },
+    Url { url: String },
+    Raw { text: String },
+}
+
+/// Splits a `KEY:value;KEY:value;;` string (the shape `wifi_data`/
+/// `mecard_data` emit) into `(key, unescaped value)` pairs, treating
+/// `\;`/`\,` as escaped literals rather than field separators.
+fn parse_escaped_fields(data: &str) -> Vec<(String, String)> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut escaped = false;
+    for c in data.chars() {
+        if escaped {
+            current.push(c);
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' => escaped = true,
+            ';' => {
+                if let Some(colon) = current.find(':') {
+                    fields.push((current[..colon].to_string(), current[colon + 1..].to_string()));
+                }
+                current.clear();
+            }
+            _ => current.push(c),
+        }
+    }
+    fields
+}
+
+fn parse_wifi_content(data: &str) -> Option<QrContent> {
+    let rest = data.strip_prefix("WIFI:")?;
+    let mut ssid = String::new();
+    let mut password = String::new();
+    let mut encryption = String::new();
+    let mut hidden = false;
+    for (key, value) in parse_escaped_fields(rest) {
+        match key.as_str() {
+            "T" => encryption = value,
+            "S" => ssid = value,
+            "P" => password = value,
+            "H" => hidden = value == "true",
+            _ => {}
+        }
+    }
+    Some(QrContent::Wifi { ssid, password, encryption, hidden })
+}
+
+/// Reverses `vcard_escape`: `\\` → `\`, `\,` → `,`, `\;` → `;`, `\n` → a
+/// real newline.
+fn vcard_unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => {}
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn parse_vcard_content(data: &str) -> QrContent {
+    let mut name = None;
+    let mut email = None;
+    let mut phone = None;
+    let mut org = None;
+    for line in data.lines() {
+        let Some(colon) = line.find(':') else { continue };
+        let (prop, value) = (&line[..colon], &line[colon + 1..]);
+        // Ignore TEL;TYPE=CELL-style parameters when matching the property name.
+        let prop_name = prop.split(';').next().unwrap_or(prop);
+        let value = vcard_unescape(value.trim());
+        match prop_name {
+            "FN" => name = Some(value),
+            "EMAIL" => email = Some(value),
+            "TEL" => phone = Some(value),
+            "ORG" => org = Some(value),
+            _ => {}
+        }
+    }
+    QrContent::VCard { name, email, phone, org }
+}
+
+/// Classifies a decoded QR payload by its well-known prefix, the way
+/// messenger/camera QR parsers do, falling back to `Raw` for anything else.
+pub fn classify_content(data: &str) -> QrContent {
+    if let Some(content) = parse_wifi_content(data) {
+        return content;
+    }
+    if data.starts_with("BEGIN:VCARD") {
+        return parse_vcard_content(data);
+    }
+    if let Some(address) = data.strip_prefix("mailto:") {
+        return QrContent::Email { address: address.to_string() };
+    }
+    if let Some(rest) = data.strip_prefix("MATMSG:") {
+        if let Some((_, address)) = parse_escaped_fields(rest).into_iter().find(|(k, _)| k == "TO") {
+            return QrContent::Email { address };
+        }
+    }
+    if let Some(number) = data.strip_prefix("tel:") {
+        return QrContent::Phone { number: number.to_string() };
+    }
+    if let Some(rest) = data.strip_prefix("SMSTO:") {
+        let number = rest.split(':').next().unwrap_or(rest).to_string();
+        return QrContent::Phone { number };
+    }
+    if let Some(rest) = data.strip_prefix("geo:") {
+        let mut coords = rest.split(',');
+        if let (Some(lat), Some(lon)) = (coords.next(), coords.next()) {
+            if let (Ok(latitude), Ok(longitude)) = (lat.parse::<f64>(), lon.parse::<f64>()) {
+                return QrContent::Geo { latitude, longitude };
+            }
+        }
+    }
+    if data.starts_with("http://") || data.starts_with("https://") {
+        return QrContent::Url { url: data.to_string() };
     }
-    vcard.push_str("END:VCARD");
-    vcard
+    QrContent::Raw { text: data.to_string() }
 }
@@ -1,19 +1,406 @@
 use std::collections::HashMap;
-use std::sync::Mutex;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
+use rocket::fairing::{Fairing, Info, Kind};
 use rocket::http::Header;
 use rocket::response::Responder;
-use rocket::Request;
+use rocket::{Request, Response};
 
-/// Fixed-window rate limiter.
-///
-/// Each key (e.g. IP address) gets a counter that resets every `window` duration.
-/// Callers pass in the per-key limit when checking.
-pub struct RateLimiter {
+use crate::config::Config;
+
+/// Number of shards each bucket map is split into. Requests for keys that
+/// hash to different shards never contend on the same mutex, unlike a
+/// single `Mutex<HashMap<..>>` which serializes every request regardless of
+/// key.
+const DEFAULT_SHARD_COUNT: usize = 16;
+
+/// A `HashMap<K, V>` split across several independently-locked shards,
+/// chosen by `k.hash() % shard_count`. Trades a little memory (and the cost
+/// of a hash per lookup, which a plain `HashMap` already pays) for letting
+/// unrelated keys proceed without contending on the same lock.
+struct ShardedMap<K, V> {
+    shards: Vec<Mutex<HashMap<K, V>>>,
+}
+
+impl<K: Hash + Eq + Clone, V> ShardedMap<K, V> {
+    fn new(shard_count: usize) -> Self {
+        let shard_count = shard_count.max(1);
+        ShardedMap {
+            shards: (0..shard_count).map(|_| Mutex::new(HashMap::new())).collect(),
+        }
+    }
+
+    fn shard_index(&self, key: &K) -> usize {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        key.hash(&mut hasher);
+        (hasher.finish() as usize) % self.shards.len()
+    }
+
+    /// Locks the shard `key` belongs to and runs `f` against it. `f` gets
+    /// the whole shard map (not just `key`'s entry) so callers needing a
+    /// second lookup in the same shard (see `effective_limit`, which reads
+    /// and then removes an expired override) don't need to re-lock.
+    fn with_shard<R>(&self, key: &K, f: impl FnOnce(&mut HashMap<K, V>) -> R) -> R {
+        let idx = self.shard_index(key);
+        let mut shard = self.shards[idx].lock().unwrap_or_else(|e| e.into_inner());
+        f(&mut shard)
+    }
+
+    /// Runs `retain` against every shard in turn.
+    fn retain(&self, mut f: impl FnMut(&K, &mut V) -> bool) {
+        for shard in &self.shards {
+            let mut map = shard.lock().unwrap_or_else(|e| e.into_inner());
+            map.retain(|k, v| f(k, v));
+        }
+    }
+}
+
+/// Pluggable storage for token-bucket state, selected by
+/// `Config::rate_limit_backend`. Mirrors `storage::StorageBackend`'s shape: a
+/// trait plus a `store_from_config` that picks an implementation, so a
+/// multi-instance deployment can swap in a shared external store (Redis,
+/// Memcached) without touching `RateLimiter::check_cost` or the routes that
+/// call it. The only backend bundled here is the in-process one, since an
+/// external store needs a client and a running service this crate doesn't
+/// depend on.
+pub trait RateLimitStore: Send + Sync {
+    /// Returns the bucket's current `(tokens, last_refill)`, if it's been
+    /// touched before.
+    fn get(&self, key_id: &str) -> Option<(f64, Instant)>;
+    /// Upserts the bucket's token count and refill timestamp.
+    fn set(&self, key_id: &str, tokens: f64, last_refill: Instant);
+    /// Drops buckets that haven't been touched in at least `max_age`.
+    fn prune(&self, max_age: Duration);
+}
+
+/// Default backend: an in-process, sharded `HashMap` (see `ShardedMap`).
+/// Correct for a single instance; a multi-instance deployment sharing one
+/// logical rate limit needs a `RateLimitStore` backed by something all
+/// instances can see.
+struct InProcessStore {
+    buckets: ShardedMap<String, (f64, Instant)>,
+}
+
+impl InProcessStore {
+    fn new() -> Self {
+        InProcessStore {
+            buckets: ShardedMap::new(DEFAULT_SHARD_COUNT),
+        }
+    }
+}
+
+impl RateLimitStore for InProcessStore {
+    fn get(&self, key_id: &str) -> Option<(f64, Instant)> {
+        self.buckets.with_shard(&key_id.to_string(), |buckets| buckets.get(key_id).copied())
+    }
+
+    fn set(&self, key_id: &str, tokens: f64, last_refill: Instant) {
+        self.buckets
+            .with_shard(&key_id.to_string(), |buckets| buckets.insert(key_id.to_string(), (tokens, last_refill)));
+    }
+
+    fn prune(&self, max_age: Duration) {
+        let now = Instant::now();
+        self.buckets
+            .retain(|_, (_, last_refill)| now.duration_since(*last_refill) < max_age);
+    }
+}
+
+/// Builds the token-bucket store selected by `config.rate_limit_backend`.
+/// Unknown values fall back to the in-process backend, matching the rest of
+/// the codebase's preference for failing safe rather than refusing to start
+/// over a typo.
+pub fn store_from_config(_config: &Config) -> Arc<dyn RateLimitStore> {
+    // `rate_limit_backend` only ever names `"memory"` today — `_config` (and
+    // the field) are the extension point a shared external store would
+    // switch on, kept unused for now rather than invented.
+    Arc::new(InProcessStore::new())
+}
+
+/// Handle for the background thread spawned by `RateLimiter::with_auto_prune`.
+/// Stored as a field so it shuts the thread down when the owning
+/// `RateLimiter` is dropped, rather than leaking a pruning loop forever.
+struct AutoPruneHandle {
+    stop: Arc<AtomicBool>,
+    handle: Option<std::thread::JoinHandle<()>>,
+}
+
+impl Drop for AutoPruneHandle {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// The actual bucket storage and checking logic, kept behind an `Arc` so the
+/// background pruning thread spawned by `with_auto_prune` can hold its own
+/// handle to it independent of the `RateLimiter` it was created from.
+struct RateLimiterState {
     window: Duration,
-    /// key_id → (window_start, count)
-    buckets: Mutex<HashMap<String, (Instant, u64)>>,
+    /// key_id → (tokens, last_refill) for the token-bucket algorithm (see
+    /// `check_cost`). Capacity is whatever `limit` the caller passes at
+    /// check time, not stored per entry — a bucket simply clamps to
+    /// whatever capacity it's next checked against. Pluggable (see
+    /// `RateLimitStore`) so a multi-instance deployment can share this
+    /// state externally instead of keeping it in-process.
+    buckets: Arc<dyn RateLimitStore>,
+    /// key_id → theoretical arrival time (GCRA state, see `check_gcra`).
+    gcra_tats: ShardedMap<String, Instant>,
+    /// (key_id, quota_window) → (window_start, count), one bucket per quota
+    /// tier a key is checked against (see `check_multi`).
+    multi_buckets: ShardedMap<(String, Duration), (Instant, u64)>,
+    /// key_id or key-class → (overridden limit, expires at). Consulted by
+    /// `check_cost` ahead of the caller-supplied `limit` so an upstream
+    /// gateway's dynamically-raised/lowered quota wins until it expires.
+    limit_overrides: ShardedMap<String, (u64, Instant)>,
+}
+
+impl RateLimiterState {
+    fn new(window: Duration, buckets: Arc<dyn RateLimitStore>) -> Self {
+        RateLimiterState {
+            window,
+            buckets,
+            gcra_tats: ShardedMap::new(DEFAULT_SHARD_COUNT),
+            multi_buckets: ShardedMap::new(DEFAULT_SHARD_COUNT),
+            limit_overrides: ShardedMap::new(DEFAULT_SHARD_COUNT),
+        }
+    }
+
+    /// Returns `key_id`'s overridden limit if one is set and not yet
+    /// expired, else `default_limit`. Lazily drops an expired override so
+    /// `limit_overrides` doesn't accumulate stale entries.
+    fn effective_limit(&self, key_id: &str, default_limit: u64) -> u64 {
+        let now = Instant::now();
+        self.limit_overrides.with_shard(&key_id.to_string(), |overrides| {
+            match overrides.get(key_id) {
+                Some((override_limit, until)) if *until > now => *override_limit,
+                Some(_) => {
+                    overrides.remove(key_id);
+                    default_limit
+                }
+                None => default_limit,
+            }
+        })
+    }
+
+    fn set_limit_override(&self, key_id: &str, limit: u64, until: Instant) {
+        self.limit_overrides.with_shard(&key_id.to_string(), |overrides| {
+            overrides.insert(key_id.to_string(), (limit, until));
+        });
+    }
+
+    /// Token-bucket check: capacity is `limit`, refilling at `limit /
+    /// self.window` tokens/sec. A request spending `cost` tokens is allowed
+    /// iff the bucket (after refilling for the elapsed time since it was
+    /// last touched) holds at least `cost`; `reset_secs` is how long until
+    /// the bucket would be full again (allowed) or until it holds enough
+    /// tokens for this request (denied) — unlike a fixed window, this can
+    /// be a fraction of `self.window`.
+    fn check_cost(&self, key_id: &str, limit: u64, cost: u64) -> RateLimitResult {
+        let now = Instant::now();
+        let limit = self.effective_limit(key_id, limit);
+        let capacity = limit as f64;
+        let refill_rate = capacity / self.window.as_secs_f64().max(f64::MIN_POSITIVE);
+        let cost = cost as f64;
+
+        let (tokens, last_refill) = self.buckets.get(key_id).unwrap_or((capacity, now));
+
+        let elapsed = now.saturating_duration_since(last_refill);
+        let tokens = (tokens + elapsed.as_secs_f64() * refill_rate).min(capacity);
+
+        if tokens >= cost {
+            let tokens = tokens - cost;
+            self.buckets.set(key_id, tokens, now);
+            RateLimitResult {
+                allowed: true,
+                limit,
+                remaining: tokens.floor() as u64,
+                reset_secs: ((capacity - tokens) / refill_rate).ceil() as u64,
+                tiers: Vec::new(),
+            }
+        } else {
+            self.buckets.set(key_id, tokens, now);
+            RateLimitResult {
+                allowed: false,
+                limit,
+                remaining: tokens.floor() as u64,
+                reset_secs: ((cost - tokens) / refill_rate).ceil() as u64,
+                tiers: Vec::new(),
+            }
+        }
+    }
+
+    fn check_gcra(&self, key_id: &str, limit: u64) -> RateLimitResult {
+        let now = Instant::now();
+        let limit = limit.max(1);
+        let t = self.window / limit as u32;
+        let tau = t * limit as u32;
+
+        self.gcra_tats.with_shard(&key_id.to_string(), |tats| {
+            let stored_tat = tats.get(key_id).copied().unwrap_or(now);
+            let tat = stored_tat.max(now);
+            let arrival_gap = tat.duration_since(now);
+
+            if arrival_gap + t <= tau {
+                tats.insert(key_id.to_string(), tat + t);
+                let remaining = (tau.saturating_sub(arrival_gap + t).as_secs_f64()
+                    / t.as_secs_f64())
+                .floor() as u64;
+                RateLimitResult {
+                    allowed: true,
+                    limit,
+                    remaining,
+                    reset_secs: arrival_gap.as_secs_f64().ceil() as u64,
+                    tiers: Vec::new(),
+                }
+            } else {
+                RateLimitResult {
+                    allowed: false,
+                    limit,
+                    remaining: 0,
+                    reset_secs: (arrival_gap + t - tau).as_secs_f64().ceil() as u64,
+                    tiers: Vec::new(),
+                }
+            }
+        })
+    }
+
+    fn check_multi(&self, key_id: &str, quotas: &[(Duration, u64)]) -> RateLimitResult {
+        let now = Instant::now();
+
+        struct Probe {
+            window: Duration,
+            limit: u64,
+            count: u64,
+            reset_secs: u64,
+        }
+
+        // Each quota tier has its own bucket key `(key_id, window)`, which
+        // may land in a different shard per tier — probe them one at a time
+        // rather than holding every shard's lock at once.
+        let probes: Vec<Probe> = quotas
+            .iter()
+            .map(|(window, limit)| {
+                let bucket_key = (key_id.to_string(), *window);
+                self.multi_buckets.with_shard(&bucket_key, |buckets| {
+                    let entry = buckets.entry(bucket_key.clone()).or_insert_with(|| (now, 0));
+                    if now.duration_since(entry.0) >= *window {
+                        *entry = (now, 0);
+                    }
+                    let reset_secs = window
+                        .checked_sub(now.duration_since(entry.0))
+                        .unwrap_or(Duration::ZERO)
+                        .as_secs();
+                    Probe {
+                        window: *window,
+                        limit: *limit,
+                        count: entry.1,
+                        reset_secs,
+                    }
+                })
+            })
+            .collect();
+
+        let all_allowed = probes.iter().all(|p| p.count < p.limit);
+        if all_allowed {
+            for p in &probes {
+                let bucket_key = (key_id.to_string(), p.window);
+                self.multi_buckets.with_shard(&bucket_key, |buckets| {
+                    if let Some(entry) = buckets.get_mut(&bucket_key) {
+                        entry.1 += 1;
+                    }
+                });
+            }
+        }
+
+        let tiers: Vec<TierStatus> = probes
+            .iter()
+            .map(|p| TierStatus {
+                window_secs: p.window.as_secs(),
+                limit: p.limit,
+                remaining: p
+                    .limit
+                    .saturating_sub(if all_allowed { p.count + 1 } else { p.count }),
+            })
+            .collect();
+
+        let limiting = tiers
+            .iter()
+            .min_by_key(|t| t.remaining)
+            .cloned()
+            .unwrap_or(TierStatus {
+                window_secs: 0,
+                limit: 0,
+                remaining: 0,
+            });
+        let reset_secs = if all_allowed {
+            probes.iter().map(|p| p.reset_secs).max().unwrap_or(0)
+        } else {
+            probes
+                .iter()
+                .filter(|p| p.count >= p.limit)
+                .map(|p| p.reset_secs)
+                .max()
+                .unwrap_or(0)
+        };
+
+        RateLimitResult {
+            allowed: all_allowed,
+            limit: limiting.limit,
+            remaining: limiting.remaining,
+            reset_secs,
+            tiers,
+        }
+    }
+
+    /// Drops only fully-expired buckets (their window has elapsed), so
+    /// active keys are never evicted mid-window.
+    fn prune_stale(&self) {
+        let now = Instant::now();
+        let window = self.window;
+        self.buckets.prune(window);
+        self.gcra_tats
+            .retain(|_, tat| now.duration_since(*tat) < window);
+        self.multi_buckets
+            .retain(|(_, quota_window), (start, _)| now.duration_since(*start) < *quota_window);
+    }
+}
+
+/// Distinguishes what a `check_cost` call is metering, mirroring the dual
+/// op/bandwidth limits VM I/O rate limiters expose. Purely a naming aid for
+/// callers: to keep an ops quota and a bytes quota independent for the same
+/// logical key, suffix the `key_id` passed to `check_cost` with
+/// `TokenType::key_suffix` (e.g. reserve 1 op up front, then charge a
+/// rendered byte count once known).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenType {
+    Ops,
+    Bytes,
+}
+
+impl TokenType {
+    pub fn key_suffix(self) -> &'static str {
+        match self {
+            TokenType::Ops => "ops",
+            TokenType::Bytes => "bytes",
+        }
+    }
+}
+
+/// A single quota tier's status from a `check_multi` call.
+#[derive(Clone)]
+pub struct TierStatus {
+    /// The quota's window, in seconds (e.g. `1` for a per-second burst tier).
+    pub window_secs: u64,
+    /// Configured limit for this tier.
+    pub limit: u64,
+    /// Requests remaining in this tier's current window.
+    pub remaining: u64,
 }
 
 /// Result of a rate limit check.
@@ -28,6 +415,10 @@ pub struct RateLimitResult {
     pub remaining: u64,
     /// Seconds until the current window resets.
     pub reset_secs: u64,
+    /// Per-tier detail when checked via `check_multi` (empty for a
+    /// single-quota `check`/`check_gcra` call). `RateLimited` emits one
+    /// extra pair of headers per entry.
+    pub tiers: Vec<TierStatus>,
 }
 
 /// Wrapper responder that attaches rate limit headers to any inner response.
@@ -54,16 +445,133 @@ impl<'r, 'o: 'r, T: Responder<'r, 'o>> Responder<'r, 'o> for RateLimited<T> {
             "X-RateLimit-Reset",
             self.rate_limit.reset_secs.to_string(),
         ));
+        if !self.rate_limit.allowed {
+            response.set_header(Header::new(
+                "Retry-After",
+                self.rate_limit.reset_secs.to_string(),
+            ));
+        }
+        for tier in &self.rate_limit.tiers {
+            response.set_header(Header::new(
+                format!("X-RateLimit-Limit-{}s", tier.window_secs),
+                tier.limit.to_string(),
+            ));
+            response.set_header(Header::new(
+                format!("X-RateLimit-Remaining-{}s", tier.window_secs),
+                tier.remaining.to_string(),
+            ));
+        }
         Ok(response)
     }
 }
 
+/// Attaches `X-RateLimit-*`/`Retry-After` headers to every response whose
+/// route stashed a `RateLimitResult` in request-local cache — see
+/// `auth::AuthenticatedKey` and `auth::AnonymousRateLimit`'s `FromRequest`
+/// impls, the two guards that do so. A route that never checked a rate
+/// limit (or whose guards never ran, e.g. a 404 before any guard fires) is
+/// left untouched.
+pub struct RateLimitHeaders;
+
+#[rocket::async_trait]
+impl Fairing for RateLimitHeaders {
+    fn info(&self) -> Info {
+        Info {
+            name: "Rate Limit Headers",
+            kind: Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let cached: &Option<RateLimitResult> = request.local_cache(|| None);
+        if let Some(result) = cached {
+            response.set_header(Header::new("X-RateLimit-Limit", result.limit.to_string()));
+            response.set_header(Header::new(
+                "X-RateLimit-Remaining",
+                result.remaining.to_string(),
+            ));
+            response.set_header(Header::new(
+                "X-RateLimit-Reset",
+                result.reset_secs.to_string(),
+            ));
+            if !result.allowed {
+                response.set_header(Header::new("Retry-After", result.reset_secs.to_string()));
+            }
+            for tier in &result.tiers {
+                response.set_header(Header::new(
+                    format!("X-RateLimit-Limit-{}s", tier.window_secs),
+                    tier.limit.to_string(),
+                ));
+                response.set_header(Header::new(
+                    format!("X-RateLimit-Remaining-{}s", tier.window_secs),
+                    tier.remaining.to_string(),
+                ));
+            }
+        }
+    }
+}
+
+/// Token-bucket rate limiter (see `RateLimiterState::check_cost`), plus the
+/// GCRA (`check_gcra`) and tiered (`check_multi`) alternatives below for
+/// callers that want those characteristics instead.
+///
+/// Each key (e.g. IP address or API key id) gets a bucket holding up to
+/// `limit` tokens that refills smoothly over `window`, rather than a
+/// counter that resets in one step at a window boundary — this avoids the
+/// double-burst a fixed window allows right at the reset edge. Callers pass
+/// in the per-key limit (and, via `check_cost`, a request's token cost) when
+/// checking. Bucket storage is sharded (see `ShardedMap`) so concurrent
+/// checks for different keys don't contend on a single lock.
+pub struct RateLimiter {
+    state: Arc<RateLimiterState>,
+    /// Present only when constructed via `with_auto_prune`; its `Drop`
+    /// stops the background pruning thread.
+    _auto_prune: Option<AutoPruneHandle>,
+}
+
 impl RateLimiter {
-    /// Create a new rate limiter with the given window duration.
+    /// Create a new rate limiter with the given window duration, backed by
+    /// the default in-process bucket store. Stale buckets accumulate until
+    /// `prune_stale` is called manually; use `with_auto_prune` instead if
+    /// nothing else is doing that.
     pub fn new(window: Duration) -> Self {
+        Self::with_store(window, Arc::new(InProcessStore::new()))
+    }
+
+    /// Like `new`, but takes an explicit `RateLimitStore` — e.g. one built by
+    /// `store_from_config` — instead of always using the in-process default.
+    pub fn with_store(window: Duration, store: Arc<dyn RateLimitStore>) -> Self {
         RateLimiter {
-            window,
-            buckets: Mutex::new(HashMap::new()),
+            state: Arc::new(RateLimiterState::new(window, store)),
+            _auto_prune: None,
+        }
+    }
+
+    /// Like `new`, but also spawns a dedicated background thread that calls
+    /// `prune_stale` every `prune_interval`. The thread is owned by the
+    /// returned `RateLimiter` and is joined (shut down) when it's dropped.
+    pub fn with_auto_prune(window: Duration, prune_interval: Duration) -> Self {
+        let state = Arc::new(RateLimiterState::new(window, Arc::new(InProcessStore::new())));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread_state = state.clone();
+        let thread_stop = stop.clone();
+        let handle = std::thread::spawn(move || {
+            while !thread_stop.load(Ordering::Relaxed) {
+                std::thread::sleep(prune_interval);
+                if thread_stop.load(Ordering::Relaxed) {
+                    break;
+                }
+                thread_state.prune_stale();
+            }
+        });
+
+        RateLimiter {
+            state,
+            _auto_prune: Some(AutoPruneHandle {
+                stop,
+                handle: Some(handle),
+            }),
         }
     }
 
@@ -72,49 +580,110 @@ impl RateLimiter {
     /// Returns a `RateLimitResult` indicating whether the request is allowed
     /// and the current rate limit state for response headers.
     pub fn check(&self, key_id: &str, limit: u64) -> RateLimitResult {
-        let now = Instant::now();
-        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
-
-        let entry = buckets
-            .entry(key_id.to_string())
-            .or_insert_with(|| (now, 0));
+        self.check_cost(key_id, limit, 1)
+    }
 
-        // If the window has elapsed, reset.
-        if now.duration_since(entry.0) >= self.window {
-            *entry = (now, 0);
-        }
+    /// Check (and, if allowed, consume) `cost` tokens for `key_id` against a
+    /// bucket with capacity `limit` that refills at `limit / self.window`
+    /// tokens/sec, instead of always spending 1. Lets a route meter
+    /// something other than request count — e.g. a batch request spending
+    /// more tokens than a single-item one (see `auth::route_cost`).
+    /// `check(key_id, limit)` is just `check_cost(key_id, limit, 1)`.
+    pub fn check_cost(&self, key_id: &str, limit: u64, cost: u64) -> RateLimitResult {
+        self.state.check_cost(key_id, limit, cost)
+    }
 
-        let reset_secs = self
-            .window
-            .checked_sub(now.duration_since(entry.0))
-            .unwrap_or(Duration::ZERO)
-            .as_secs();
+    /// Check (and consume) one request for `key_id` against `limit` per
+    /// `self.window`, using the Generic Cell Rate Algorithm instead of a
+    /// token bucket. GCRA enforces a smooth emission rate via a single
+    /// "theoretical arrival time" per key rather than a token count, while
+    /// still allowing a burst of up to `limit` requests at once.
+    ///
+    /// Stores a single "theoretical arrival time" (TAT) per key rather than
+    /// `(window_start, count)`. With emission interval `T = window / limit`
+    /// and burst tolerance `τ = T * limit` (== `window`), a request at `now`
+    /// computes `tat = max(stored_tat, now)` and is allowed iff
+    /// `tat - now + T <= τ`, i.e. iff storing `tat + T` keeps the key within
+    /// its burst of `limit`.
+    pub fn check_gcra(&self, key_id: &str, limit: u64) -> RateLimitResult {
+        self.state.check_gcra(key_id, limit)
+    }
 
-        if entry.1 >= limit {
-            RateLimitResult {
-                allowed: false,
-                limit,
-                remaining: 0,
-                reset_secs,
-            }
-        } else {
-            entry.1 += 1;
-            RateLimitResult {
-                allowed: true,
-                limit,
-                remaining: limit.saturating_sub(entry.1),
-                reset_secs,
-            }
-        }
+    /// Check (and consume, if every tier passes) one request for `key_id`
+    /// against several `(window, limit)` quotas at once — e.g. a short burst
+    /// allowance layered with a longer sustained cap. Each quota gets its own
+    /// fixed-window bucket keyed by `(key_id, window)`, so a key's burst
+    /// tier and sustained tier track independently.
+    ///
+    /// The request is allowed only if *every* quota has room; denying on one
+    /// tier leaves all tiers' counters untouched (no partial consumption).
+    /// The returned `RateLimitResult`'s top-level fields mirror the most
+    /// constraining tier (smallest `remaining`); `tiers` carries every
+    /// tier's detail so callers can surface them all.
+    pub fn check_multi(&self, key_id: &str, quotas: &[(Duration, u64)]) -> RateLimitResult {
+        self.state.check_multi(key_id, quotas)
     }
 
     /// Periodically prune stale entries to prevent unbounded memory growth.
-    /// Call this from a background task or on a timer.
+    /// Call this from a background task or on a timer — or use
+    /// `with_auto_prune` to have a thread do it automatically.
     #[allow(dead_code)]
     pub fn prune_stale(&self) {
-        let now = Instant::now();
-        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
-        buckets.retain(|_, (start, _)| now.duration_since(*start) < self.window);
+        self.state.prune_stale()
+    }
+
+    /// Check (and consume) one request for `(route_class, key_id)` using the
+    /// quota configured for `route_class` in `route_quotas` (see
+    /// `config::RouteQuota`), falling back to `default_limit` per
+    /// `self.window` if `route_class` has no entry. The effective bucket key
+    /// is the pair, not just `key_id` — a client's budget on one route never
+    /// drains another's.
+    pub fn check_route(
+        &self,
+        route_class: &str,
+        key_id: &str,
+        route_quotas: &HashMap<String, crate::config::RouteQuota>,
+        default_limit: u64,
+    ) -> RateLimitResult {
+        self.check_route_cost(route_class, key_id, route_quotas, default_limit, 1)
+    }
+
+    /// Like `check_route`, but spends `cost` tokens instead of 1 when
+    /// `route_class` has no configured quota override. A configured
+    /// override takes the tiered `check_multi` path instead, which doesn't
+    /// support weighted cost (every tier always spends 1 per request) — so
+    /// `cost` only has an effect on the `check_cost` fallback path.
+    pub fn check_route_cost(
+        &self,
+        route_class: &str,
+        key_id: &str,
+        route_quotas: &HashMap<String, crate::config::RouteQuota>,
+        default_limit: u64,
+        cost: u64,
+    ) -> RateLimitResult {
+        let bucket_key = format!("{}:{}", route_class, key_id);
+        match route_quotas.get(route_class) {
+            Some(quota) => {
+                // A per-route window differs from `self.window`, so this
+                // can't reuse `check_cost` (which always checks against
+                // `self.window`) — it needs its own bucket keyed by the
+                // route-specific window, same as `check_multi`'s tiers.
+                self.state
+                    .check_multi(&bucket_key, &[(Duration::from_secs(quota.window_secs), quota.limit)])
+            }
+            None => self.check_cost(&bucket_key, default_limit, cost),
+        }
+    }
+
+    /// Override the quota enforced for `key_id` (or a key-class id, if the
+    /// caller uses those as `check`/`check_cost` keys) until `until`. Takes
+    /// effect on the very next `check`/`check_cost` call and is consulted
+    /// ahead of whatever `limit` the caller passes in — e.g. when this
+    /// service fronts a third party and learns of a raised or lowered quota
+    /// from that upstream's response headers. Expires on its own; there's no
+    /// need to clear it once `until` has passed.
+    pub fn set_limit_override(&self, key_id: &str, limit: u64, until: Instant) {
+        self.state.set_limit_override(key_id, limit, until)
     }
 }
 
@@ -153,4 +722,160 @@ mod tests {
         // key2 should still be fine
         assert!(rl.check("key2", 5).allowed);
     }
+
+    #[test]
+    fn gcra_allows_initial_burst_up_to_limit() {
+        let rl = RateLimiter::new(Duration::from_secs(60));
+        for _ in 0..5 {
+            assert!(rl.check_gcra("key1", 5).allowed);
+        }
+        assert!(!rl.check_gcra("key1", 5).allowed);
+    }
+
+    #[test]
+    fn gcra_separate_keys_independent() {
+        let rl = RateLimiter::new(Duration::from_secs(60));
+        for _ in 0..5 {
+            rl.check_gcra("key1", 5);
+        }
+        assert!(!rl.check_gcra("key1", 5).allowed);
+        assert!(rl.check_gcra("key2", 5).allowed);
+    }
+
+    #[test]
+    fn gcra_denied_request_reports_reset_secs() {
+        let rl = RateLimiter::new(Duration::from_secs(60));
+        for _ in 0..5 {
+            rl.check_gcra("key1", 5);
+        }
+        let denied = rl.check_gcra("key1", 5);
+        assert!(!denied.allowed);
+        assert!(denied.reset_secs > 0);
+    }
+
+    #[test]
+    fn check_cost_deducts_cost_not_one() {
+        let rl = RateLimiter::new(Duration::from_secs(60));
+        let r = rl.check_cost("img:bytes", 1000, 400);
+        assert!(r.allowed);
+        assert_eq!(r.remaining, 600);
+        let r = rl.check_cost("img:bytes", 1000, 400);
+        assert!(r.allowed);
+        assert_eq!(r.remaining, 200);
+        // A third 400-byte charge would blow the budget; denied, and the
+        // bucket is left untouched (still at 800 used, 200 remaining).
+        let r = rl.check_cost("img:bytes", 1000, 400);
+        assert!(!r.allowed);
+        assert_eq!(r.remaining, 200);
+    }
+
+    #[test]
+    fn check_is_check_cost_of_one() {
+        let rl = RateLimiter::new(Duration::from_secs(60));
+        let a = rl.check("key1", 10);
+        let rl2 = RateLimiter::new(Duration::from_secs(60));
+        let b = rl2.check_cost("key1", 10, 1);
+        assert_eq!(a.allowed, b.allowed);
+        assert_eq!(a.remaining, b.remaining);
+    }
+
+    #[test]
+    fn check_multi_enforces_tightest_tier() {
+        let rl = RateLimiter::new(Duration::from_secs(60));
+        let quotas = [
+            (Duration::from_secs(1), 2),
+            (Duration::from_secs(3600), 1000),
+        ];
+        assert!(rl.check_multi("key1", &quotas).allowed);
+        assert!(rl.check_multi("key1", &quotas).allowed);
+        // Burst tier (limit 2 per second) is now exhausted even though the
+        // sustained tier has plenty of room left.
+        let denied = rl.check_multi("key1", &quotas);
+        assert!(!denied.allowed);
+        assert_eq!(denied.tiers.len(), 2);
+    }
+
+    #[test]
+    fn check_multi_denial_does_not_partially_consume() {
+        let rl = RateLimiter::new(Duration::from_secs(60));
+        let quotas = [(Duration::from_secs(1), 1), (Duration::from_secs(3600), 2)];
+        assert!(rl.check_multi("key1", &quotas).allowed);
+        // Burst tier denies this one; sustained tier's count must stay at 1,
+        // not be bumped to 2.
+        assert!(!rl.check_multi("key1", &quotas).allowed);
+        let sustained_tier = rl
+            .check_multi("key1", &[(Duration::from_secs(3600), 2)])
+            .tiers[0]
+            .remaining;
+        assert_eq!(sustained_tier, 0);
+    }
+
+    #[test]
+    fn limit_override_takes_precedence_over_caller_limit() {
+        let rl = RateLimiter::new(Duration::from_secs(60));
+        rl.set_limit_override("key1", 1, Instant::now() + Duration::from_secs(60));
+        // Caller asks for a limit of 10, but the override caps it at 1.
+        assert!(rl.check("key1", 10).allowed);
+        assert!(!rl.check("key1", 10).allowed);
+    }
+
+    #[test]
+    fn expired_limit_override_falls_back_to_caller_limit() {
+        let rl = RateLimiter::new(Duration::from_secs(60));
+        // `until` already in the past: the override should be treated as
+        // expired immediately.
+        rl.set_limit_override("key1", 1, Instant::now() - Duration::from_secs(1));
+        let r = rl.check("key1", 10);
+        assert!(r.allowed);
+        assert_eq!(r.limit, 10);
+    }
+
+    #[test]
+    fn check_route_uses_configured_quota_per_class() {
+        let rl = RateLimiter::new(Duration::from_secs(60));
+        let mut quotas = HashMap::new();
+        quotas.insert(
+            "health".to_string(),
+            crate::config::RouteQuota {
+                window_secs: 1,
+                limit: 2,
+            },
+        );
+
+        assert!(rl.check_route("health", "1.2.3.4", &quotas, 100).allowed);
+        assert!(rl.check_route("health", "1.2.3.4", &quotas, 100).allowed);
+        // Configured limit of 2 is exhausted; the unrelated `default_limit`
+        // of 100 must not rescue it.
+        assert!(!rl.check_route("health", "1.2.3.4", &quotas, 100).allowed);
+    }
+
+    #[test]
+    fn check_route_falls_back_without_config_entry() {
+        let rl = RateLimiter::new(Duration::from_secs(60));
+        let quotas = HashMap::new();
+        let r = rl.check_route("generate", "1.2.3.4", &quotas, 3);
+        assert!(r.allowed);
+        assert_eq!(r.limit, 3);
+    }
+
+    #[test]
+    fn check_route_keeps_route_classes_independent() {
+        let rl = RateLimiter::new(Duration::from_secs(60));
+        let quotas = HashMap::new();
+        for _ in 0..3 {
+            rl.check_route("generate", "1.2.3.4", &quotas, 3);
+        }
+        assert!(!rl.check_route("generate", "1.2.3.4", &quotas, 3).allowed);
+        // Same key, different route class: independent budget.
+        assert!(rl.check_route("decode", "1.2.3.4", &quotas, 3).allowed);
+    }
+
+    #[test]
+    fn with_auto_prune_stops_thread_on_drop() {
+        let rl = RateLimiter::with_auto_prune(Duration::from_secs(60), Duration::from_millis(10));
+        rl.check("key1", 5);
+        // Just exercising construction + drop here; the real assertion is
+        // that this doesn't hang or panic when the JoinHandle is dropped.
+        drop(rl);
+    }
 }
@@ -0,0 +1,76 @@
+//! Background sweep for tracked QR codes past their `expires_at`. The
+//! at-scan-time check in `routes::redirect_short_url_blocking` catches most
+//! expired codes immediately, but a code nobody scans again would otherwise
+//! linger in `tracked_qr` forever — this periodically cleans those up too,
+//! per `Config::expiry_policy`.
+//!
+//! Runs as a plain `rocket::tokio::spawn` task started at ignite (see
+//! `main.rs`), not a fairing — it doesn't gate startup and has nothing to
+//! hand back to Rocket's state.
+
+use crate::db::{self, DbPool};
+use rocket::tokio::time::{interval, Duration};
+
+/// Starts the sweep loop on the async runtime. Ticks every
+/// `sweep_interval_secs` (clamped to at least 1, so a misconfigured `0`
+/// doesn't spin the loop).
+pub fn spawn(pool: DbPool, sweep_interval_secs: u64, policy: String) {
+    rocket::tokio::spawn(async move {
+        let mut ticker = interval(Duration::from_secs(sweep_interval_secs.max(1)));
+        loop {
+            ticker.tick().await;
+            let pool = pool.clone();
+            let policy = policy.clone();
+            // The sweep itself is blocking SQLite work; run it off the async
+            // executor like every other DB-touching handler in this service.
+            let _ = rocket::tokio::task::spawn_blocking(move || sweep_once(&pool, &policy)).await;
+        }
+    });
+}
+
+/// One sweep pass: finds live (non-tombstoned) tracked QRs past
+/// `expires_at` and either deletes them (with their `scan_events`) or
+/// tombstones them, per `policy`. Errors getting a pooled connection or
+/// preparing the query are swallowed — there's always another tick.
+fn sweep_once(db: &DbPool, policy: &str) {
+    let Ok(conn) = db.get() else { return };
+    let now = chrono::Utc::now();
+
+    let Ok(mut stmt) = conn.prepare(
+        "SELECT id, expires_at FROM tracked_qr WHERE expires_at IS NOT NULL AND tombstoned_at IS NULL",
+    ) else {
+        return;
+    };
+
+    let Ok(rows) = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    }) else {
+        return;
+    };
+
+    let expired_ids: Vec<String> = rows
+        .filter_map(|r| r.ok())
+        .filter(|(_, expires_at)| {
+            db::parse_expiry(expires_at)
+                .map(|dt| dt <= now)
+                .unwrap_or(false)
+        })
+        .map(|(id, _)| id)
+        .collect();
+    drop(stmt);
+
+    for id in expired_ids {
+        if policy == "delete" {
+            let _ = conn.execute(
+                "DELETE FROM scan_events WHERE tracked_qr_id = ?1",
+                rusqlite::params![id],
+            );
+            let _ = conn.execute("DELETE FROM tracked_qr WHERE id = ?1", rusqlite::params![id]);
+        } else {
+            let _ = conn.execute(
+                "UPDATE tracked_qr SET tombstoned_at = ?2 WHERE id = ?1",
+                rusqlite::params![id, now.to_rfc3339()],
+            );
+        }
+    }
+}
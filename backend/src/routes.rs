@@ -1,15 +1,29 @@
 use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use base64::Engine;
+use rocket::data::{Data, ToByteUnit};
 use rocket::http::{ContentType, Status};
-use rocket::response::Redirect;
+use rocket::response::{Redirect, Responder};
 use rocket::serde::json::Json;
 use rocket::State;
 use std::path::PathBuf;
 
-use crate::auth::AuthenticatedKey;
+use crate::auth;
+use crate::auth::{AnonymousRateLimit, AuthenticatedKey, ClientIp, UserAgent};
+use crate::config::Config;
+use crate::db;
 use crate::db::{hash_key, DbPool};
+use crate::e2e;
+use crate::geoip;
+use crate::grpc_auth;
+use crate::jwt_manage;
 use crate::models::*;
+use crate::oidc::Principal;
 use crate::qr;
+use crate::rate_limit::{RateLimited, RateLimiter};
+use crate::storage::{self, StorageBackend, StoredImage};
+use crate::ua;
+use std::sync::Arc;
 
 // ============ Health & OpenAPI ============
 
@@ -22,20 +36,153 @@ pub fn health() -> Json<HealthResponse> {
     })
 }
 
+/// Publishes the public half of the manage-token signing key as a JWK set,
+/// so anyone holding a tracked QR's manage token can independently verify it
+/// (and so `jwt_manage::JwtManageKeys::verify` exercises the same `n`/`e`
+/// this endpoint serves). Mounted outside `/api/v1` at the conventional
+/// `.well-known` path; served unconditionally, since a JWK set with no
+/// matching issued tokens (JWT manage tokens disabled) reveals nothing.
+#[get("/.well-known/jwks.json")]
+pub fn jwks(jwt_keys: &State<Arc<jwt_manage::JwtManageKeys>>) -> Json<serde_json::Value> {
+    Json(jwt_keys.jwks())
+}
+
+/// Generated from the live model/route set (see `crate::openapi`) rather
+/// than served from a static file, so it can't drift out of sync with the
+/// request/response shapes the handlers actually use.
 #[get("/openapi.json")]
-pub fn openapi() -> (ContentType, &'static str) {
-    (ContentType::JSON, include_str!("../openapi.json"))
+pub fn openapi() -> Json<serde_json::Value> {
+    Json(crate::openapi::generate_spec())
+}
+
+/// Serves a Swagger UI page that points at `openapi`, so the API can be
+/// browsed and exercised interactively. Mounted only when docs are enabled
+/// (see `ENABLE_DOCS` in `main.rs`).
+#[get("/docs")]
+pub fn docs_ui() -> (ContentType, String) {
+    let html = format!(
+        r##"<!DOCTYPE html>
+<html>
+<head>
+  <title>QR Service API Docs</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css" />
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+  <script>
+    window.onload = () => {{
+      window.ui = SwaggerUIBundle({{
+        url: "{spec_url}",
+        dom_id: "#swagger-ui",
+      }});
+    }};
+  </script>
+</body>
+</html>"##,
+        spec_url = "/api/v1/openapi.json"
+    );
+    (ContentType::HTML, html)
 }
 
 // ============ QR Generation ============
 
 #[post("/qr/generate", format = "json", data = "<req>")]
-pub fn generate_qr(
+pub async fn generate_qr(
     req: Json<GenerateRequest>,
-    key: AuthenticatedKey,
+    key: Option<AuthenticatedKey>,
+    _rate_gate: AnonymousRateLimit,
+    client_ip: ClientIp,
+    user_agent: UserAgent,
     db: &State<DbPool>,
+    config: &State<Config>,
+    storage: &State<Arc<dyn StorageBackend>>,
 ) -> Result<Json<QrResponse>, (Status, Json<ApiError>)> {
     let req = req.into_inner();
+    let pool = db.inner().clone();
+    let config = config.inner().clone();
+    let storage = storage.inner().clone();
+
+    rocket::tokio::task::spawn_blocking(move || {
+        generate_qr_blocking(req, key, client_ip, user_agent, &pool, &config, &storage)
+    })
+    .await
+    .unwrap_or_else(|_| {
+        Err((
+            Status::InternalServerError,
+            Json(ApiError {
+                error: "Background generation task panicked".to_string(),
+                code: "TASK_PANIC".to_string(),
+                status: 500,
+            }),
+        ))
+    })
+}
+
+/// `application/x-www-form-urlencoded` counterpart of [`generate_qr`], so a
+/// plain HTML `<form>` or `curl -d 'data=...&format=svg'` can hit this route
+/// without constructing JSON. Parses the body into the same JSON shape the
+/// JSON route deserializes `GenerateRequest` from, then hands off to the
+/// same blocking generation path (same validation, same clamping).
+#[post("/qr/generate", format = "application/x-www-form-urlencoded", data = "<data>")]
+pub async fn generate_qr_form(
+    data: Data<'_>,
+    key: Option<AuthenticatedKey>,
+    _rate_gate: AnonymousRateLimit,
+    client_ip: ClientIp,
+    user_agent: UserAgent,
+    db: &State<DbPool>,
+    config: &State<Config>,
+    storage: &State<Arc<dyn StorageBackend>>,
+) -> Result<Json<QrResponse>, (Status, Json<ApiError>)> {
+    let body = read_form_body(data).await?;
+    let req: GenerateRequest = serde_json::from_value(urlencoded_to_json(&body)).map_err(|e| {
+        (
+            Status::BadRequest,
+            Json(ApiError {
+                error: format!("Invalid form data: {}", e),
+                code: "INVALID_FORM".to_string(),
+                status: 400,
+            }),
+        )
+    })?;
+
+    let pool = db.inner().clone();
+    let config = config.inner().clone();
+    let storage = storage.inner().clone();
+
+    rocket::tokio::task::spawn_blocking(move || {
+        generate_qr_blocking(req, key, client_ip, user_agent, &pool, &config, &storage)
+    })
+    .await
+    .unwrap_or_else(|_| {
+        Err((
+            Status::InternalServerError,
+            Json(ApiError {
+                error: "Background generation task panicked".to_string(),
+                code: "TASK_PANIC".to_string(),
+                status: 500,
+            }),
+        ))
+    })
+}
+
+/// The actual (blocking) QR-generation work, run on Rocket's blocking-task
+/// pool via `spawn_blocking` in `generate_qr` rather than on the async
+/// executor, since image rendering and the SQLite insert both hold a thread
+/// for the duration of the call.
+fn generate_qr_blocking(
+    req: GenerateRequest,
+    key: Option<AuthenticatedKey>,
+    client_ip: ClientIp,
+    user_agent: UserAgent,
+    db: &DbPool,
+    config: &Config,
+    storage: &Arc<dyn StorageBackend>,
+) -> Result<Json<QrResponse>, (Status, Json<ApiError>)> {
+    if let Some(ref k) = key {
+        k.require(auth::Action::Generate)?;
+    }
 
     // Validate
     if req.data.is_empty() {
@@ -49,6 +196,9 @@ pub fn generate_qr(
         ));
     }
 
+    let key_id = key_id_or_anonymous(&key);
+    grpc_auth::check(config, key_id, &req.data, &client_ip.0, &user_agent.0)?;
+
     if req.size < 64 || req.size > 4096 {
         return Err((
             Status::BadRequest,
@@ -82,12 +232,68 @@ pub fn generate_qr(
         )
     })?;
 
+    let version = req
+        .version
+        .as_deref()
+        .map(qr::parse_version)
+        .transpose()
+        .map_err(|e| {
+            (
+                Status::BadRequest,
+                Json(ApiError {
+                    error: e,
+                    code: "INVALID_VERSION".to_string(),
+                    status: 400,
+                }),
+            )
+        })?;
+
+    // A logo overlay forces the higher error-correction level the raster
+    // path needs to stay scannable with part of the symbol covered,
+    // regardless of what the caller asked for in `error_correction`.
+    let logo_data: Option<Vec<u8>> = req
+        .logo
+        .as_deref()
+        .map(qr::decode_logo_base64)
+        .transpose()
+        .map_err(|e| {
+            (
+                Status::BadRequest,
+                Json(ApiError {
+                    error: e,
+                    code: "INVALID_LOGO".to_string(),
+                    status: 400,
+                }),
+            )
+        })?;
+
+    if logo_data.is_some() && !(5..=40).contains(&req.logo_size) {
+        return Err((
+            Status::BadRequest,
+            Json(ApiError {
+                error: "logo_size must be between 5 and 40".to_string(),
+                code: "INVALID_LOGO_SIZE".to_string(),
+                status: 400,
+            }),
+        ));
+    }
+
     let options = qr::QrOptions {
         size: req.size,
-        fg_color,
+        fg_color: qr::Fill::Solid(fg_color),
         bg_color,
-        error_correction: qr::parse_ec_level(&req.error_correction),
+        error_correction: if logo_data.is_some() {
+            qr::parse_ec_level("H")
+        } else {
+            qr::parse_ec_level(&req.error_correction)
+        },
         style: qr::QrStyle::parse(&req.style),
+        antialias: req.antialias,
+        version,
+        dot_fill_ratio: 0.85,
+        square_finder_modules: true,
+        rotation_degrees: 0.0,
+        scale: 1.0,
     };
 
     let (image_data, content_type) = match req.format.as_str() {
@@ -102,6 +308,19 @@ pub fn generate_qr(
                     }),
                 )
             })?;
+            let data = match &logo_data {
+                Some(logo) => qr::overlay_logo_png(&data, logo, req.logo_size).map_err(|e| {
+                    (
+                        Status::InternalServerError,
+                        Json(ApiError {
+                            error: e,
+                            code: "LOGO_OVERLAY_FAILED".to_string(),
+                            status: 500,
+                        }),
+                    )
+                })?,
+                None => data,
+            };
             (data, "image/png")
         }
         "svg" => {
@@ -115,13 +334,57 @@ pub fn generate_qr(
                     }),
                 )
             })?;
+            let svg = match &logo_data {
+                Some(logo) => {
+                    let overlay = qr::svg_logo_overlay(logo, req.size, req.logo_size).map_err(|e| {
+                        (
+                            Status::InternalServerError,
+                            Json(ApiError {
+                                error: e,
+                                code: "LOGO_OVERLAY_FAILED".to_string(),
+                                status: 500,
+                            }),
+                        )
+                    })?;
+                    svg.replacen("</svg>", &format!("{}</svg>", overlay), 1)
+                }
+                None => svg,
+            };
             (svg.into_bytes(), "image/svg+xml")
         }
+        "pdf" => {
+            let logo = logo_data.as_deref().map(|d| (d, req.logo_size));
+            let pdf = qr::generate_pdf(&req.data, &options, logo).map_err(|e| {
+                (
+                    Status::InternalServerError,
+                    Json(ApiError {
+                        error: e,
+                        code: "GENERATION_FAILED".to_string(),
+                        status: 500,
+                    }),
+                )
+            })?;
+            (pdf, "application/pdf")
+        }
+        "text" => {
+            let text = qr::generate_text(&req.data, &options, req.invert, req.quiet_zone)
+                .map_err(|e| {
+                    (
+                        Status::InternalServerError,
+                        Json(ApiError {
+                            error: e,
+                            code: "GENERATION_FAILED".to_string(),
+                            status: 500,
+                        }),
+                    )
+                })?;
+            (text.into_bytes(), "text/plain")
+        }
         _ => {
             return Err((
                 Status::BadRequest,
                 Json(ApiError {
-                    error: "Unsupported format. Use 'png' or 'svg'".to_string(),
+                    error: "Unsupported format. Use 'png', 'svg', 'pdf', or 'text'".to_string(),
                     code: "INVALID_FORMAT".to_string(),
                     status: 400,
                 }),
@@ -136,14 +399,27 @@ pub fn generate_qr(
         BASE64.encode(&image_data)
     );
 
-    // Store in database
-    let conn = db.lock().unwrap();
+    // Upload the bytes to the configured storage backend, then store only
+    // the returned location (not the bytes themselves) in `qr_codes`.
+    let location = storage.put(&id, content_type, &image_data).map_err(|e| {
+        (
+            Status::InternalServerError,
+            Json(ApiError {
+                error: format!("Failed to store QR image: {}", e),
+                code: "STORAGE_FAILED".to_string(),
+                status: 500,
+            }),
+        )
+    })?;
+    let image_url = storage.public_url(&location);
+
+    let conn = db.get().unwrap();
     let _ = conn.execute(
-        "INSERT INTO qr_codes (id, api_key_id, data, format, size, fg_color, bg_color, error_correction, style, image_data) 
+        "INSERT INTO qr_codes (id, api_key_id, data, format, size, fg_color, bg_color, error_correction, style, image_location)
          VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
         rusqlite::params![
             id,
-            key.id,
+            key_id,
             req.data,
             req.format,
             req.size,
@@ -151,7 +427,7 @@ pub fn generate_qr(
             req.bg_color,
             req.error_correction,
             req.style,
-            image_data,
+            location,
         ],
     );
 
@@ -169,38 +445,126 @@ pub fn generate_qr(
         format: req.format,
         size: req.size,
         image_base64,
+        image_url,
         created_at,
     }))
 }
 
-#[post("/qr/decode", data = "<data>")]
+#[post("/qr/decode", data = "<data>", rank = 2)]
 pub fn decode_qr(
     data: Vec<u8>,
-    _key: AuthenticatedKey,
+    key: Option<AuthenticatedKey>,
+    _rate_gate: AnonymousRateLimit,
+) -> Result<Json<DecodeResponse>, (Status, Json<ApiError>)> {
+    if let Some(ref k) = key {
+        k.require(auth::Action::Decode)?;
+    }
+    decode_qr_bytes(&data)
+}
+
+/// `multipart/form-data` counterpart of [`decode_qr`], for browsers and
+/// `curl -F` callers that would rather upload a PNG/JPEG directly than
+/// base64-encode it into a JSON body. Pulls the first `image` or `file` part
+/// out of the multipart body and feeds its raw bytes into the same decode
+/// path; ranked ahead of `decode_qr` so it only wins when the content type
+/// actually is multipart.
+#[post("/qr/decode", format = "multipart/form-data", data = "<data>", rank = 1)]
+pub async fn decode_qr_multipart(
+    content_type: &ContentType,
+    data: Data<'_>,
+    key: Option<AuthenticatedKey>,
+    _rate_gate: AnonymousRateLimit,
+    limits: &rocket::data::Limits,
 ) -> Result<Json<DecodeResponse>, (Status, Json<ApiError>)> {
-    // Try to decode the image
-    let img = image::load_from_memory(&data).map_err(|e| {
+    if let Some(ref k) = key {
+        k.require(auth::Action::Decode)?;
+    }
+
+    let boundary = content_type.media_type().param("boundary").map(|b| b.to_string()).ok_or_else(|| {
         (
             Status::BadRequest,
             Json(ApiError {
-                error: format!("Failed to load image: {}", e),
-                code: "INVALID_IMAGE".to_string(),
+                error: "Missing multipart boundary".to_string(),
+                code: "INVALID_MULTIPART".to_string(),
+                status: 400,
+            }),
+        )
+    })?;
+
+    // Same size cap as the raw-body path's `Vec<u8>` data guard (Rocket's
+    // `bytes` limit), so the multipart route isn't a back door around it.
+    let body = data
+        .open(limits.get("bytes").unwrap_or(rocket::data::Limits::BYTES))
+        .into_bytes()
+        .await
+        .map_err(|e| {
+            (
+                Status::BadRequest,
+                Json(ApiError {
+                    error: format!("Failed to read request body: {}", e),
+                    code: "INVALID_IMAGE".to_string(),
+                    status: 400,
+                }),
+            )
+        })?;
+
+    if !body.is_complete() {
+        return Err((
+            Status::PayloadTooLarge,
+            Json(ApiError {
+                error: "Upload exceeds the maximum allowed size".to_string(),
+                code: "IMAGE_TOO_LARGE".to_string(),
+                status: 413,
+            }),
+        ));
+    }
+
+    let image_bytes = extract_multipart_field(&body.into_inner(), &boundary, &["image", "file"]).ok_or_else(|| {
+        (
+            Status::BadRequest,
+            Json(ApiError {
+                error: "Missing 'image' or 'file' part in multipart body".to_string(),
+                code: "MISSING_IMAGE_PART".to_string(),
                 status: 400,
             }),
         )
     })?;
 
-    let gray = img.to_luma8();
+    decode_qr_bytes(&image_bytes)
+}
+
+fn decode_qr_bytes(data: &[u8]) -> Result<Json<DecodeResponse>, (Status, Json<ApiError>)> {
+    let symbols = qr::decode_image(data).map_err(|e| {
+        (
+            Status::BadRequest,
+            Json(ApiError {
+                error: e,
+                code: "INVALID_IMAGE".to_string(),
+                status: 400,
+            }),
+        )
+    })?;
 
-    // Use a simple decoder approach
-    // For production, we'd use a proper QR decoder like rqrr
-    let decoded = rqrr_decode(&gray);
+    let results: Vec<DecodedQr> = symbols
+        .into_iter()
+        .map(|s| DecodedQr {
+            content: qr::classify_content(&s.text),
+            data: s.text,
+            bounding_box: s.bounding_box,
+            version: s.version,
+            ec_level: s.ec_level,
+        })
+        .collect();
 
-    match decoded {
-        Some(content) => Ok(Json(DecodeResponse {
-            data: content,
-            format: "qr".to_string(),
-        })),
+    match results.first() {
+        Some(first) => {
+            let data = first.data.clone();
+            Ok(Json(DecodeResponse {
+                data,
+                format: "qr".to_string(),
+                results,
+            }))
+        }
         None => Err((
             Status::UnprocessableEntity,
             Json(ApiError {
@@ -212,151 +576,797 @@ pub fn decode_qr(
     }
 }
 
-fn rqrr_decode(img: &image::GrayImage) -> Option<String> {
-    let mut prepared = rqrr::PreparedImage::prepare(img.clone());
-    let grids = prepared.detect_grids();
-    if let Some(grid) = grids.into_iter().next() {
-        if let Ok((_meta, content)) = grid.decode() {
-            return Some(content);
-        }
-    }
-    None
-}
+// ============ End-to-end Encrypted QR ============
 
-#[post("/qr/batch", format = "json", data = "<req>")]
-pub fn batch_generate(
-    req: Json<BatchGenerateRequest>,
-    key: AuthenticatedKey,
+/// Seals `req.data` for `req.recipient_pubkey` (see `e2e::seal`) and renders
+/// the resulting envelope through the same pipeline [`generate_qr`] uses, so
+/// an encrypted QR gets identical sizing/color/logo/storage handling — the
+/// only difference is what ends up encoded.
+#[post("/qr/generate/encrypted", format = "json", data = "<req>")]
+pub async fn generate_encrypted_qr(
+    req: Json<EncryptedGenerateRequest>,
+    key: Option<AuthenticatedKey>,
+    _rate_gate: AnonymousRateLimit,
+    client_ip: ClientIp,
+    user_agent: UserAgent,
     db: &State<DbPool>,
-) -> Result<Json<BatchQrResponse>, (Status, Json<ApiError>)> {
+    config: &State<Config>,
+    storage: &State<Arc<dyn StorageBackend>>,
+) -> Result<Json<QrResponse>, (Status, Json<ApiError>)> {
     let req = req.into_inner();
 
-    if req.items.is_empty() {
+    if req.data.is_empty() {
         return Err((
             Status::BadRequest,
             Json(ApiError {
-                error: "Items array cannot be empty".to_string(),
-                code: "EMPTY_BATCH".to_string(),
+                error: "Data field cannot be empty".to_string(),
+                code: "EMPTY_DATA".to_string(),
                 status: 400,
             }),
         ));
     }
 
-    if req.items.len() > 50 {
-        return Err((
+    let recipient_pubkey = e2e::parse_pubkey(&req.recipient_pubkey).map_err(|e| {
+        (
             Status::BadRequest,
             Json(ApiError {
-                error: "Maximum 50 items per batch".to_string(),
-                code: "BATCH_TOO_LARGE".to_string(),
+                error: e,
+                code: "INVALID_PUBKEY".to_string(),
                 status: 400,
             }),
-        ));
-    }
+        )
+    })?;
 
-    let mut responses = Vec::new();
+    let envelope = e2e::seal(req.data.as_bytes(), &recipient_pubkey);
+    let generate_req = req.into_generate_request(envelope);
 
-    for item in &req.items {
-        let fg_color = qr::parse_hex_color(&item.fg_color).unwrap_or([0, 0, 0, 255]);
-        let bg_color = qr::parse_hex_color(&item.bg_color).unwrap_or([255, 255, 255, 255]);
+    let pool = db.inner().clone();
+    let config = config.inner().clone();
+    let storage = storage.inner().clone();
 
-        let options = qr::QrOptions {
-            size: item.size.clamp(64, 4096),
-            fg_color,
-            bg_color,
-            error_correction: qr::parse_ec_level(&item.error_correction),
-            style: qr::QrStyle::parse(&item.style),
-        };
+    rocket::tokio::task::spawn_blocking(move || {
+        generate_qr_blocking(generate_req, key, client_ip, user_agent, &pool, &config, &storage)
+    })
+    .await
+    .unwrap_or_else(|_| {
+        Err((
+            Status::InternalServerError,
+            Json(ApiError {
+                error: "Background generation task panicked".to_string(),
+                code: "TASK_PANIC".to_string(),
+                status: 500,
+            }),
+        ))
+    })
+}
 
-        let (image_data, content_type) = match item.format.as_str() {
-            "svg" => match qr::generate_svg(&item.data, &options) {
-                Ok(svg) => (svg.into_bytes(), "image/svg+xml"),
-                Err(_) => continue,
-            },
-            _ => match qr::generate_png(&item.data, &options) {
-                Ok(data) => (data, "image/png"),
-                Err(_) => continue,
-            },
-        };
-
-        let id = uuid::Uuid::new_v4().to_string();
-        let image_base64 = format!(
-            "data:{};base64,{}",
-            content_type,
-            BASE64.encode(&image_data)
-        );
-
-        // Store in db
-        let conn = db.lock().unwrap();
-        let _ = conn.execute(
-            "INSERT INTO qr_codes (id, api_key_id, data, format, size, fg_color, bg_color, error_correction, style, image_data) 
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
-            rusqlite::params![id, key.id, item.data, item.format, item.size, item.fg_color, item.bg_color, item.error_correction, item.style, image_data],
-        );
-
-        responses.push(QrResponse {
-            id,
-            data: item.data.clone(),
-            format: item.format.clone(),
-            size: item.size,
-            image_base64,
-            created_at: chrono::Utc::now().to_rfc3339(),
-        });
+/// Opens an envelope produced by [`generate_encrypted_qr`] (however it was
+/// obtained — typically by scanning the QR image and handing the decoded
+/// text straight back here) given the recipient's private key, and shapes
+/// the recovered plaintext into the same [`DecodeResponse`] [`decode_qr`]
+/// returns, since there's nothing else to tell them apart from a caller's
+/// point of view.
+#[post("/qr/decrypt", format = "json", data = "<req>")]
+pub fn decrypt_envelope(
+    req: Json<DecryptRequest>,
+    key: Option<AuthenticatedKey>,
+    _rate_gate: AnonymousRateLimit,
+) -> Result<Json<DecodeResponse>, (Status, Json<ApiError>)> {
+    if let Some(ref k) = key {
+        k.require(auth::Action::Decode)?;
     }
 
-    let total = responses.len();
-    Ok(Json(BatchQrResponse {
-        items: responses,
-        total,
+    let req = req.into_inner();
+    let recipient_privkey = e2e::parse_privkey(&req.recipient_privkey).map_err(|e| {
+        (
+            Status::BadRequest,
+            Json(ApiError {
+                error: e,
+                code: "INVALID_PRIVKEY".to_string(),
+                status: 400,
+            }),
+        )
+    })?;
+
+    let plaintext_bytes = e2e::open(&req.envelope, &recipient_privkey).map_err(|e| {
+        (
+            Status::BadRequest,
+            Json(ApiError {
+                error: e,
+                code: "DECRYPT_FAILED".to_string(),
+                status: 400,
+            }),
+        )
+    })?;
+
+    let data = String::from_utf8(plaintext_bytes).map_err(|_| {
+        (
+            Status::BadRequest,
+            Json(ApiError {
+                error: "Decrypted payload is not valid UTF-8".to_string(),
+                code: "DECRYPT_FAILED".to_string(),
+                status: 400,
+            }),
+        )
+    })?;
+
+    Ok(Json(DecodeResponse {
+        data: data.clone(),
+        format: "qr".to_string(),
+        results: vec![DecodedQr {
+            content: qr::classify_content(&data),
+            data,
+            bounding_box: [(0, 0); 4],
+            version: String::new(),
+            ec_level: String::new(),
+        }],
     }))
 }
 
-#[post("/qr/template/<template_type>", format = "json", data = "<body>")]
-pub fn generate_from_template(
-    template_type: &str,
-    body: Json<serde_json::Value>,
-    key: AuthenticatedKey,
-    db: &State<DbPool>,
-) -> Result<Json<QrResponse>, (Status, Json<ApiError>)> {
-    let body = body.into_inner();
-
-    let (data, format, size) = match template_type {
-        "wifi" => {
-            let ssid = body.get("ssid").and_then(|v| v.as_str()).ok_or_else(|| {
-                (
-                    Status::BadRequest,
-                    Json(ApiError {
-                        error: "Missing 'ssid' field".to_string(),
-                        code: "MISSING_FIELD".to_string(),
-                        status: 400,
-                    }),
-                )
-            })?;
-            let password = body.get("password").and_then(|v| v.as_str()).unwrap_or("");
-            let encryption = body
-                .get("encryption")
-                .and_then(|v| v.as_str())
-                .unwrap_or("WPA2");
-            let hidden = body
-                .get("hidden")
-                .and_then(|v| v.as_bool())
-                .unwrap_or(false);
-            let format = body
-                .get("format")
-                .and_then(|v| v.as_str())
-                .unwrap_or("png")
-                .to_string();
-            let size = body.get("size").and_then(|v| v.as_u64()).unwrap_or(256) as u32;
+/// Finds the first multipart part whose `Content-Disposition` `name`
+/// matches one of `field_names` and returns its raw body bytes. Deliberately
+/// minimal: doesn't handle nested multipart or non-identity
+/// `Content-Transfer-Encoding`, since browsers and `curl -F` don't send
+/// either for a plain file field.
+fn extract_multipart_field(body: &[u8], boundary: &str, field_names: &[&str]) -> Option<Vec<u8>> {
+    let delimiter = format!("--{}", boundary).into_bytes();
 
-            (
-                qr::wifi_data(ssid, password, encryption, hidden),
-                format,
-                size,
-            )
+    let mut rest = body;
+    while let Some(start) = find_subslice(rest, &delimiter) {
+        rest = &rest[start + delimiter.len()..];
+        if rest.starts_with(b"--") {
+            break; // final boundary
         }
-        "vcard" => {
-            let name = body.get("name").and_then(|v| v.as_str()).ok_or_else(|| {
-                (
-                    Status::BadRequest,
+        rest = rest.strip_prefix(b"\r\n").unwrap_or(rest);
+        let end = find_subslice(rest, &delimiter).unwrap_or(rest.len());
+        if let Some(content) = parse_multipart_part(&rest[..end], field_names) {
+            return Some(content);
+        }
+    }
+    None
+}
+
+/// Splits a single multipart part into its headers and body (divided by the
+/// first blank line) and returns the body if `Content-Disposition` names one
+/// of `field_names`.
+fn parse_multipart_part(part: &[u8], field_names: &[&str]) -> Option<Vec<u8>> {
+    let header_end = find_subslice(part, b"\r\n\r\n")?;
+    let headers = std::str::from_utf8(&part[..header_end]).ok()?;
+    let mut body = &part[header_end + 4..];
+    if body.ends_with(b"\r\n") {
+        body = &body[..body.len() - 2];
+    }
+
+    let disposition = headers
+        .lines()
+        .find(|l| l.to_ascii_lowercase().starts_with("content-disposition"))?;
+    let name = disposition
+        .split(';')
+        .find_map(|seg| seg.trim().strip_prefix("name=\""))
+        .map(|s| s.trim_end_matches('"'))?;
+
+    field_names.contains(&name).then(|| body.to_vec())
+}
+
+fn find_subslice(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// Reads an `application/x-www-form-urlencoded` body, capped well above
+/// anything a real form field list needs.
+async fn read_form_body(data: Data<'_>) -> Result<Vec<u8>, (Status, Json<ApiError>)> {
+    let body = data.open(64.kibibytes()).into_bytes().await.map_err(|e| {
+        (
+            Status::BadRequest,
+            Json(ApiError {
+                error: format!("Failed to read request body: {}", e),
+                code: "INVALID_FORM".to_string(),
+                status: 400,
+            }),
+        )
+    })?;
+
+    if !body.is_complete() {
+        return Err((
+            Status::PayloadTooLarge,
+            Json(ApiError {
+                error: "Form body exceeds the maximum allowed size".to_string(),
+                code: "FORM_TOO_LARGE".to_string(),
+                status: 413,
+            }),
+        ));
+    }
+
+    Ok(body.into_inner())
+}
+
+/// Parses an `application/x-www-form-urlencoded` body into a JSON object, so
+/// it can be fed into the same `serde_json::Value`-driven template match or
+/// deserialized straight into the same request struct the JSON routes use.
+/// Coerces `"true"`/`"false"` to booleans and integer-looking values to
+/// numbers, since every numeric/boolean field on these requests accepts a
+/// bare form value rather than a JSON-typed one.
+fn urlencoded_to_json(body: &[u8]) -> serde_json::Value {
+    let body = String::from_utf8_lossy(body);
+    let mut map = serde_json::Map::new();
+    for pair in body.split('&').filter(|p| !p.is_empty()) {
+        let mut parts = pair.splitn(2, '=');
+        let key = parts.next().unwrap_or_default();
+        let value = parts.next().unwrap_or_default().replace('+', " ");
+        let key = urlencoding::decode(key).unwrap_or_default().into_owned();
+        let value = urlencoding::decode(&value).unwrap_or_default().into_owned();
+        map.insert(key, coerce_form_value(&value));
+    }
+    serde_json::Value::Object(map)
+}
+
+/// The `api_keys.id` to attribute a generated QR / gRPC-auth check to when
+/// `AuthenticatedKey` is absent: the `"anonymous"` sentinel row bootstrapped
+/// in `db::init_db_with_config`, which the `qr_codes.api_key_id` foreign key
+/// requires to exist.
+fn key_id_or_anonymous(key: &Option<AuthenticatedKey>) -> &str {
+    key.as_ref().map(|k| k.id.as_str()).unwrap_or("anonymous")
+}
+
+fn coerce_form_value(value: &str) -> serde_json::Value {
+    match value {
+        "true" => serde_json::Value::Bool(true),
+        "false" => serde_json::Value::Bool(false),
+        _ => match value.parse::<u64>() {
+            Ok(n) => serde_json::Value::Number(n.into()),
+            Err(_) => serde_json::Value::String(value.to_string()),
+        },
+    }
+}
+
+/// Resolves a batch item field that may be a `"#ref:<id>.<field>"`
+/// back-reference into a prior item's output, JMAP-batch-style. `outputs`
+/// only holds entries for items that themselves succeeded, so a reference
+/// to a failed or skipped item naturally resolves to "unknown" here too —
+/// the caller turns that into a `Skipped` result for the dependent item.
+/// Fields without the `#ref:` prefix pass through unchanged.
+fn resolve_batch_ref(
+    field: &str,
+    outputs: &std::collections::HashMap<String, serde_json::Value>,
+) -> Result<String, String> {
+    let Some(reference) = field.strip_prefix("#ref:") else {
+        return Ok(field.to_string());
+    };
+    let (item_id, field_name) = reference.split_once('.').ok_or_else(|| {
+        format!(
+            "Invalid '#ref:{}' — expected '#ref:<id>.<field>'",
+            reference
+        )
+    })?;
+    let output = outputs.get(item_id).ok_or_else(|| {
+        format!(
+            "'#ref:{}' refers to item '{}', which did not complete successfully",
+            reference, item_id
+        )
+    })?;
+    output
+        .get(field_name)
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .ok_or_else(|| format!("'#ref:{}' has no string field '{}'", reference, field_name))
+}
+
+#[post("/qr/batch", format = "json", data = "<req>")]
+pub async fn batch_generate(
+    req: Json<BatchGenerateRequest>,
+    key: Option<AuthenticatedKey>,
+    _rate_gate: AnonymousRateLimit,
+    client_ip: ClientIp,
+    user_agent: UserAgent,
+    db: &State<DbPool>,
+    config: &State<Config>,
+    storage: &State<Arc<dyn StorageBackend>>,
+    jwt_keys: &State<Arc<jwt_manage::JwtManageKeys>>,
+    limiter: &State<RateLimiter>,
+) -> Result<RateLimited<Json<BatchQrResponse>>, RateLimited<(Status, Json<ApiError>)>> {
+    // `_rate_gate`/`AuthenticatedKey`'s own guard already reserved
+    // `auth::route_cost("/batch")` (a flat cost) against the caller's
+    // bucket before the body was even parsed — a Rocket request guard
+    // resolves before a data guard like `Json<T>` does, so it can't see
+    // `items.len()` yet. Charge the remainder here, now that it's known, so
+    // a single huge batch can't hide behind the flat per-request cost. Only
+    // charged for a batch `batch_generate_blocking` would actually attempt
+    // to run (1..=50 items) — an empty or oversized batch is rejected there
+    // with its own `ApiError` regardless, so there's nothing to charge for.
+    let extra_cost = if (1..=50).contains(&req.items.len()) {
+        (req.items.len() as u64).saturating_sub(auth::route_cost("/batch"))
+    } else {
+        0
+    };
+    let cost_result = match &key {
+        Some(k) => limiter.check_cost(&k.id, k.rate_limit, extra_cost),
+        None => limiter.check_route_cost(
+            "anonymous",
+            &client_ip.0,
+            &config.route_rate_limits,
+            config.anonymous_rate_limit,
+            extra_cost,
+        ),
+    };
+
+    if !cost_result.allowed {
+        return Err(RateLimited {
+            inner: (
+                Status::TooManyRequests,
+                Json(ApiError {
+                    error: "Rate limit exceeded for batch size".to_string(),
+                    code: "RATE_LIMITED".to_string(),
+                    status: 429,
+                }),
+            ),
+            rate_limit: cost_result,
+        });
+    }
+
+    if let Some(ref k) = key {
+        if let Err(err) = k.require(auth::Action::BatchGenerate) {
+            return Err(RateLimited {
+                inner: err,
+                rate_limit: cost_result,
+            });
+        }
+    }
+
+    let req = req.into_inner();
+    let pool = db.inner().clone();
+    let config = config.inner().clone();
+    let storage = storage.inner().clone();
+    let jwt_keys = jwt_keys.inner().clone();
+
+    let result = rocket::tokio::task::spawn_blocking(move || {
+        batch_generate_blocking(req, key, client_ip, user_agent, &pool, &config, &storage, &jwt_keys)
+    })
+    .await
+    .unwrap_or_else(|_| {
+        Err((
+            Status::InternalServerError,
+            Json(ApiError {
+                error: "Background generation task panicked".to_string(),
+                code: "TASK_PANIC".to_string(),
+                status: 500,
+            }),
+        ))
+    });
+
+    match result {
+        Ok(body) => Ok(RateLimited {
+            inner: body,
+            rate_limit: cost_result,
+        }),
+        Err(err) => Err(RateLimited {
+            inner: err,
+            rate_limit: cost_result,
+        }),
+    }
+}
+
+/// Blocking body of `batch_generate`, run via `spawn_blocking` so up to 50
+/// image renders + inserts per request don't tie up an async worker thread.
+///
+/// Items are processed strictly in order so a `track` item's output is in
+/// `outputs` (and thus resolvable via `#ref:`) by the time a later item
+/// references it. A dependency that never ran successfully makes every item
+/// referencing it `Skipped` rather than aborting the rest of the batch.
+fn batch_generate_blocking(
+    req: BatchGenerateRequest,
+    key: Option<AuthenticatedKey>,
+    client_ip: ClientIp,
+    user_agent: UserAgent,
+    db: &DbPool,
+    config: &Config,
+    storage: &Arc<dyn StorageBackend>,
+    jwt_keys: &jwt_manage::JwtManageKeys,
+) -> Result<Json<BatchQrResponse>, (Status, Json<ApiError>)> {
+    if req.items.is_empty() {
+        return Err((
+            Status::BadRequest,
+            Json(ApiError {
+                error: "Items array cannot be empty".to_string(),
+                code: "EMPTY_BATCH".to_string(),
+                status: 400,
+            }),
+        ));
+    }
+
+    if req.items.len() > 50 {
+        return Err((
+            Status::BadRequest,
+            Json(ApiError {
+                error: "Maximum 50 items per batch".to_string(),
+                code: "BATCH_TOO_LARGE".to_string(),
+                status: 400,
+            }),
+        ));
+    }
+
+    let mut results: Vec<BatchItemResult> = Vec::with_capacity(req.items.len());
+    // (qr id, data, format, size, fg_color, bg_color, error_correction,
+    // style, storage location) for every `generate` item that rendered
+    // successfully, persisted below in one transaction rather than a
+    // pooled-connection round trip per item.
+    #[allow(clippy::type_complexity)]
+    let mut pending_rows: Vec<(String, String, String, u32, String, String, String, String, String)> =
+        Vec::new();
+    let key_id = key_id_or_anonymous(&key);
+    let mut outputs: std::collections::HashMap<String, serde_json::Value> =
+        std::collections::HashMap::new();
+    let conn = db.get().unwrap();
+    let mut succeeded = 0usize;
+
+    for (index, req_item) in req.items.into_iter().enumerate() {
+        let item_id = req_item.id;
+
+        match req_item.item {
+            BatchChainItem::Generate(mut item) => {
+                match resolve_batch_ref(&item.data, &outputs) {
+                    Ok(resolved) => item.data = resolved,
+                    Err(error) => {
+                        results.push(BatchItemResult::Skipped {
+                            index,
+                            id: item_id,
+                            error,
+                            code: "REF_UNRESOLVED".to_string(),
+                        });
+                        continue;
+                    }
+                }
+
+                if let Err((_, api_error)) =
+                    grpc_auth::check(config, key_id, &item.data, &client_ip.0, &user_agent.0)
+                {
+                    results.push(BatchItemResult::Error {
+                        index,
+                        id: item_id,
+                        error: api_error.error.clone(),
+                        code: api_error.code.clone(),
+                    });
+                    continue;
+                }
+
+                let fg_color = qr::parse_hex_color(&item.fg_color).unwrap_or([0, 0, 0, 255]);
+                let bg_color = qr::parse_hex_color(&item.bg_color).unwrap_or([255, 255, 255, 255]);
+
+                let options = qr::QrOptions {
+                    size: item.size.clamp(64, 4096),
+                    fg_color: qr::Fill::Solid(fg_color),
+                    bg_color,
+                    error_correction: qr::parse_ec_level(&item.error_correction),
+                    style: qr::QrStyle::parse(&item.style),
+                    antialias: item.antialias,
+                    version: item
+                        .version
+                        .as_deref()
+                        .and_then(|v| qr::parse_version(v).ok()),
+                    dot_fill_ratio: 0.85,
+                    square_finder_modules: true,
+                    rotation_degrees: 0.0,
+                    scale: 1.0,
+                };
+
+                let generated = match item.format.as_str() {
+                    "svg" => qr::generate_svg(&item.data, &options)
+                        .map(|svg| (svg.into_bytes(), "image/svg+xml")),
+                    "text" => qr::generate_text(&item.data, &options, item.invert, item.quiet_zone)
+                        .map(|text| (text.into_bytes(), "text/plain")),
+                    "pdf" => qr::generate_pdf(&item.data, &options, None).map(|data| (data, "application/pdf")),
+                    _ => qr::generate_png(&item.data, &options).map(|data| (data, "image/png")),
+                };
+
+                let (image_data, content_type) = match generated {
+                    Ok(v) => v,
+                    Err(e) => {
+                        results.push(BatchItemResult::Error {
+                            index,
+                            id: item_id,
+                            error: e,
+                            code: "GENERATION_FAILED".to_string(),
+                        });
+                        continue;
+                    }
+                };
+
+                let id = uuid::Uuid::new_v4().to_string();
+                let location = match storage.put(&id, content_type, &image_data) {
+                    Ok(location) => location,
+                    Err(e) => {
+                        results.push(BatchItemResult::Error {
+                            index,
+                            id: item_id,
+                            error: format!("Failed to store QR image: {}", e),
+                            code: "STORAGE_FAILED".to_string(),
+                        });
+                        continue;
+                    }
+                };
+
+                let image_base64 = format!(
+                    "data:{};base64,{}",
+                    content_type,
+                    BASE64.encode(&image_data)
+                );
+                let image_url = storage.public_url(&location);
+
+                if let Some(ref name) = item_id {
+                    outputs.insert(
+                        name.clone(),
+                        serde_json::json!({ "id": id, "data": item.data, "image_base64": image_base64 }),
+                    );
+                }
+
+                results.push(BatchItemResult::Success(QrResponse {
+                    id: id.clone(),
+                    data: item.data.clone(),
+                    format: item.format.clone(),
+                    size: item.size,
+                    image_base64,
+                    image_url,
+                    created_at: chrono::Utc::now().to_rfc3339(),
+                }));
+                pending_rows.push((
+                    id,
+                    item.data,
+                    item.format,
+                    item.size,
+                    item.fg_color,
+                    item.bg_color,
+                    item.error_correction,
+                    item.style,
+                    location,
+                ));
+                succeeded += 1;
+            }
+            BatchChainItem::Track(mut treq) => {
+                match resolve_batch_ref(&treq.target_url, &outputs) {
+                    Ok(resolved) => treq.target_url = resolved,
+                    Err(error) => {
+                        results.push(BatchItemResult::Skipped {
+                            index,
+                            id: item_id,
+                            error,
+                            code: "REF_UNRESOLVED".to_string(),
+                        });
+                        continue;
+                    }
+                }
+
+                let auth_key = match &key {
+                    Some(k) if k.has_scope(auth::Action::TrackedCreate) => k,
+                    Some(_) => {
+                        results.push(BatchItemResult::Error {
+                            index,
+                            id: item_id,
+                            error: "API key is missing the tracked.create scope".to_string(),
+                            code: "FORBIDDEN".to_string(),
+                        });
+                        continue;
+                    }
+                    None => {
+                        results.push(BatchItemResult::Error {
+                            index,
+                            id: item_id,
+                            error: "A 'track' batch item requires an API key".to_string(),
+                            code: "UNAUTHORIZED".to_string(),
+                        });
+                        continue;
+                    }
+                };
+
+                match build_tracked_qr(
+                    treq,
+                    &auth_key.id,
+                    &client_ip.0,
+                    &user_agent.0,
+                    &conn,
+                    config,
+                    storage,
+                    jwt_keys,
+                ) {
+                    Ok(resp) => {
+                        if let Some(ref name) = item_id {
+                            if let Ok(value) = serde_json::to_value(&resp) {
+                                outputs.insert(name.clone(), value);
+                            }
+                        }
+                        results.push(BatchItemResult::Tracked(resp));
+                        succeeded += 1;
+                    }
+                    Err((_, err)) => {
+                        results.push(BatchItemResult::Error {
+                            index,
+                            id: item_id,
+                            error: err.error,
+                            code: err.code,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    if !pending_rows.is_empty() {
+        let mut conn = conn;
+        let tx = conn.transaction().map_err(|e| {
+            (
+                Status::InternalServerError,
+                Json(ApiError {
+                    error: format!("Failed to start transaction: {}", e),
+                    code: "DB_ERROR".to_string(),
+                    status: 500,
+                }),
+            )
+        })?;
+        {
+            let mut stmt = tx
+                .prepare(
+                    "INSERT INTO qr_codes (id, api_key_id, data, format, size, fg_color, bg_color, error_correction, style, image_location)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                )
+                .map_err(|e| {
+                    (
+                        Status::InternalServerError,
+                        Json(ApiError {
+                            error: format!("Failed to prepare insert: {}", e),
+                            code: "DB_ERROR".to_string(),
+                            status: 500,
+                        }),
+                    )
+                })?;
+            for (id, data, format, size, fg_color, bg_color, error_correction, style, location) in
+                &pending_rows
+            {
+                let _ = stmt.execute(rusqlite::params![
+                    id,
+                    key_id,
+                    data,
+                    format,
+                    size,
+                    fg_color,
+                    bg_color,
+                    error_correction,
+                    style,
+                    location
+                ]);
+            }
+        }
+        tx.commit().map_err(|e| {
+            (
+                Status::InternalServerError,
+                Json(ApiError {
+                    error: format!("Failed to commit transaction: {}", e),
+                    code: "DB_ERROR".to_string(),
+                    status: 500,
+                }),
+            )
+        })?;
+    }
+
+    let total = results.len();
+    let failed = total - succeeded;
+
+    Ok(Json(BatchQrResponse {
+        results,
+        total,
+        succeeded,
+        failed,
+    }))
+}
+
+#[post("/qr/template/<template_type>", format = "json", data = "<body>")]
+pub fn generate_from_template(
+    template_type: &str,
+    body: Json<serde_json::Value>,
+    key: Option<AuthenticatedKey>,
+    _rate_gate: AnonymousRateLimit,
+    client_ip: ClientIp,
+    user_agent: UserAgent,
+    db: &State<DbPool>,
+    config: &State<Config>,
+    storage: &State<Arc<dyn StorageBackend>>,
+) -> Result<Json<QrResponse>, (Status, Json<ApiError>)> {
+    generate_from_template_body(
+        template_type,
+        body.into_inner(),
+        key,
+        client_ip,
+        user_agent,
+        db,
+        config,
+        storage,
+    )
+}
+
+/// `application/x-www-form-urlencoded` counterpart of
+/// [`generate_from_template`], so a plain HTML `<form>` submission works
+/// without constructing JSON. Parses the body into the same JSON shape
+/// [`generate_from_template_body`] matches `template_type` against, coercing
+/// `"true"`/`"false"` and integer-looking values so fields like `size` and
+/// `hidden` behave the same either way.
+#[post(
+    "/qr/template/<template_type>",
+    format = "application/x-www-form-urlencoded",
+    data = "<data>"
+)]
+pub async fn generate_from_template_form(
+    template_type: &str,
+    data: Data<'_>,
+    key: Option<AuthenticatedKey>,
+    _rate_gate: AnonymousRateLimit,
+    client_ip: ClientIp,
+    user_agent: UserAgent,
+    db: &State<DbPool>,
+    config: &State<Config>,
+    storage: &State<Arc<dyn StorageBackend>>,
+) -> Result<Json<QrResponse>, (Status, Json<ApiError>)> {
+    let body = read_form_body(data).await?;
+    generate_from_template_body(
+        template_type,
+        urlencoded_to_json(&body),
+        key,
+        client_ip,
+        user_agent,
+        db,
+        config,
+        storage,
+    )
+}
+
+fn generate_from_template_body(
+    template_type: &str,
+    body: serde_json::Value,
+    key: Option<AuthenticatedKey>,
+    client_ip: ClientIp,
+    user_agent: UserAgent,
+    db: &State<DbPool>,
+    config: &State<Config>,
+    storage: &State<Arc<dyn StorageBackend>>,
+) -> Result<Json<QrResponse>, (Status, Json<ApiError>)> {
+    if let Some(ref k) = key {
+        k.require(auth::Action::Generate)?;
+    }
+
+    let (data, format, size) = match template_type {
+        "wifi" => {
+            let ssid = body.get("ssid").and_then(|v| v.as_str()).ok_or_else(|| {
+                (
+                    Status::BadRequest,
+                    Json(ApiError {
+                        error: "Missing 'ssid' field".to_string(),
+                        code: "MISSING_FIELD".to_string(),
+                        status: 400,
+                    }),
+                )
+            })?;
+            let password = body.get("password").and_then(|v| v.as_str()).unwrap_or("");
+            let encryption = body
+                .get("encryption")
+                .and_then(|v| v.as_str())
+                .unwrap_or("WPA2");
+            let hidden = body
+                .get("hidden")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+            let format = body
+                .get("format")
+                .and_then(|v| v.as_str())
+                .unwrap_or("png")
+                .to_string();
+            let size = body.get("size").and_then(|v| v.as_u64()).unwrap_or(256) as u32;
+
+            (
+                qr::wifi_data(ssid, password, encryption, hidden),
+                format,
+                size,
+            )
+        }
+        "vcard" => {
+            let name = body.get("name").and_then(|v| v.as_str()).ok_or_else(|| {
+                (
+                    Status::BadRequest,
                     Json(ApiError {
                         error: "Missing 'name' field".to_string(),
                         code: "MISSING_FIELD".to_string(),
@@ -371,6 +1381,11 @@ pub fn generate_from_template(
                 .to_string();
             let size = body.get("size").and_then(|v| v.as_u64()).unwrap_or(256) as u32;
 
+            let version = body
+                .get("vcard_version")
+                .and_then(|v| v.as_str())
+                .unwrap_or("3.0");
+
             let data = qr::vcard_data(
                 name,
                 body.get("email").and_then(|v| v.as_str()),
@@ -378,6 +1393,34 @@ pub fn generate_from_template(
                 body.get("org").and_then(|v| v.as_str()),
                 body.get("title").and_then(|v| v.as_str()),
                 body.get("url").and_then(|v| v.as_str()),
+                version,
+            );
+            (data, format, size)
+        }
+        "mecard" => {
+            let name = body.get("name").and_then(|v| v.as_str()).ok_or_else(|| {
+                (
+                    Status::BadRequest,
+                    Json(ApiError {
+                        error: "Missing 'name' field".to_string(),
+                        code: "MISSING_FIELD".to_string(),
+                        status: 400,
+                    }),
+                )
+            })?;
+            let format = body
+                .get("format")
+                .and_then(|v| v.as_str())
+                .unwrap_or("png")
+                .to_string();
+            let size = body.get("size").and_then(|v| v.as_u64()).unwrap_or(256) as u32;
+
+            let data = qr::mecard_data(
+                name,
+                body.get("email").and_then(|v| v.as_str()),
+                body.get("phone").and_then(|v| v.as_str()),
+                body.get("org").and_then(|v| v.as_str()),
+                body.get("url").and_then(|v| v.as_str()),
             );
             (data, format, size)
         }
@@ -421,32 +1464,179 @@ pub fn generate_from_template(
             let size = body.get("size").and_then(|v| v.as_u64()).unwrap_or(256) as u32;
             (url, format, size)
         }
-        _ => {
-            return Err((
-                Status::BadRequest,
-                Json(ApiError {
-                    error: format!(
-                        "Unknown template type: '{}'. Available: wifi, vcard, url",
-                        template_type
-                    ),
-                    code: "UNKNOWN_TEMPLATE".to_string(),
-                    status: 400,
-                }),
-            ));
-        }
-    };
-
-    // Generate the QR code
-    let style_str = body
-        .get("style")
+        "geo" => {
+            let lat = body.get("lat").and_then(|v| v.as_f64()).ok_or_else(|| {
+                (
+                    Status::BadRequest,
+                    Json(ApiError {
+                        error: "Missing 'lat' field".to_string(),
+                        code: "MISSING_FIELD".to_string(),
+                        status: 400,
+                    }),
+                )
+            })?;
+            let lon = body.get("lon").and_then(|v| v.as_f64()).ok_or_else(|| {
+                (
+                    Status::BadRequest,
+                    Json(ApiError {
+                        error: "Missing 'lon' field".to_string(),
+                        code: "MISSING_FIELD".to_string(),
+                        status: 400,
+                    }),
+                )
+            })?;
+            let label = body.get("label").and_then(|v| v.as_str());
+            let format = body
+                .get("format")
+                .and_then(|v| v.as_str())
+                .unwrap_or("png")
+                .to_string();
+            let size = body.get("size").and_then(|v| v.as_u64()).unwrap_or(256) as u32;
+
+            (qr::geo_data(lat, lon, label), format, size)
+        }
+        "sms" => {
+            let number = body.get("number").and_then(|v| v.as_str()).ok_or_else(|| {
+                (
+                    Status::BadRequest,
+                    Json(ApiError {
+                        error: "Missing 'number' field".to_string(),
+                        code: "MISSING_FIELD".to_string(),
+                        status: 400,
+                    }),
+                )
+            })?;
+            let body_text = body.get("body").and_then(|v| v.as_str()).unwrap_or("");
+            let format = body
+                .get("format")
+                .and_then(|v| v.as_str())
+                .unwrap_or("png")
+                .to_string();
+            let size = body.get("size").and_then(|v| v.as_u64()).unwrap_or(256) as u32;
+
+            (qr::sms_data(number, body_text), format, size)
+        }
+        "mailto" => {
+            let address = body
+                .get("address")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| {
+                    (
+                        Status::BadRequest,
+                        Json(ApiError {
+                            error: "Missing 'address' field".to_string(),
+                            code: "MISSING_FIELD".to_string(),
+                            status: 400,
+                        }),
+                    )
+                })?;
+            let subject = body.get("subject").and_then(|v| v.as_str());
+            let mail_body = body.get("body").and_then(|v| v.as_str());
+            let format = body
+                .get("format")
+                .and_then(|v| v.as_str())
+                .unwrap_or("png")
+                .to_string();
+            let size = body.get("size").and_then(|v| v.as_u64()).unwrap_or(256) as u32;
+
+            (qr::mailto_data(address, subject, mail_body), format, size)
+        }
+        "calendar" => {
+            let summary = body
+                .get("summary")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| {
+                    (
+                        Status::BadRequest,
+                        Json(ApiError {
+                            error: "Missing 'summary' field".to_string(),
+                            code: "MISSING_FIELD".to_string(),
+                            status: 400,
+                        }),
+                    )
+                })?;
+            let start = body.get("start").and_then(|v| v.as_str()).ok_or_else(|| {
+                (
+                    Status::BadRequest,
+                    Json(ApiError {
+                        error: "Missing 'start' field".to_string(),
+                        code: "MISSING_FIELD".to_string(),
+                        status: 400,
+                    }),
+                )
+            })?;
+            let end = body.get("end").and_then(|v| v.as_str()).ok_or_else(|| {
+                (
+                    Status::BadRequest,
+                    Json(ApiError {
+                        error: "Missing 'end' field".to_string(),
+                        code: "MISSING_FIELD".to_string(),
+                        status: 400,
+                    }),
+                )
+            })?;
+            let location = body.get("location").and_then(|v| v.as_str());
+            let format = body
+                .get("format")
+                .and_then(|v| v.as_str())
+                .unwrap_or("png")
+                .to_string();
+            let size = body.get("size").and_then(|v| v.as_u64()).unwrap_or(256) as u32;
+
+            (qr::calendar_event(summary, start, end, location), format, size)
+        }
+        _ => {
+            return Err((
+                Status::BadRequest,
+                Json(ApiError {
+                    error: format!(
+                        "Unknown template type: '{}'. Available: wifi, vcard, mecard, url, geo, sms, mailto, calendar",
+                        template_type
+                    ),
+                    code: "UNKNOWN_TEMPLATE".to_string(),
+                    status: 400,
+                }),
+            ));
+        }
+    };
+
+    // Generate the QR code
+    let style_str = body
+        .get("style")
         .and_then(|v| v.as_str())
         .unwrap_or("square");
+    let antialias = body
+        .get("antialias")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let version = body
+        .get("version")
+        .and_then(|v| v.as_str())
+        .and_then(|v| qr::parse_version(v).ok());
+    let invert = body
+        .get("invert")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let quiet_zone = body
+        .get("quiet_zone")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+
+    let key_id = key_id_or_anonymous(&key);
+    grpc_auth::check(config, key_id, &data, &client_ip.0, &user_agent.0)?;
+
     let options = qr::QrOptions {
         size: size.clamp(64, 4096),
-        fg_color: [0, 0, 0, 255],
+        fg_color: qr::Fill::Solid([0, 0, 0, 255]),
         bg_color: [255, 255, 255, 255],
         error_correction: qr::parse_ec_level("M"),
         style: qr::QrStyle::parse(style_str),
+        antialias,
+        version,
+        dot_fill_ratio: 0.85,
+        square_finder_modules: true,
+        rotation_degrees: 0.0,
+        scale: 1.0,
     };
 
     let (image_data, content_type) = match format.as_str() {
@@ -463,6 +1653,19 @@ pub fn generate_from_template(
             })?;
             (svg.into_bytes(), "image/svg+xml")
         }
+        "text" => {
+            let text = qr::generate_text(&data, &options, invert, quiet_zone).map_err(|e| {
+                (
+                    Status::InternalServerError,
+                    Json(ApiError {
+                        error: e,
+                        code: "GENERATION_FAILED".to_string(),
+                        status: 500,
+                    }),
+                )
+            })?;
+            (text.into_bytes(), "text/plain")
+        }
         _ => {
             let png = qr::generate_png(&data, &options).map_err(|e| {
                 (
@@ -485,10 +1688,23 @@ pub fn generate_from_template(
         BASE64.encode(&image_data)
     );
 
-    let conn = db.lock().unwrap();
+    let location = storage.put(&id, content_type, &image_data).map_err(|e| {
+        (
+            Status::InternalServerError,
+            Json(ApiError {
+                error: format!("Failed to store QR image: {}", e),
+                code: "STORAGE_FAILED".to_string(),
+                status: 500,
+            }),
+        )
+    })?;
+
+    let image_url = storage.public_url(&location);
+
+    let conn = db.get().unwrap();
     let _ = conn.execute(
-        "INSERT INTO qr_codes (id, api_key_id, data, format, size, template, image_data) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-        rusqlite::params![id, key.id, data, format, size, template_type, image_data],
+        "INSERT INTO qr_codes (id, api_key_id, data, format, size, template, image_location) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        rusqlite::params![id, key_id, data, format, size, template_type, location],
     );
 
     Ok(Json(QrResponse {
@@ -497,6 +1713,7 @@ pub fn generate_from_template(
         format,
         size,
         image_base64,
+        image_url,
         created_at: chrono::Utc::now().to_rfc3339(),
     }))
 }
@@ -504,17 +1721,40 @@ pub fn generate_from_template(
 // ============ History ============
 
 #[get("/qr/history?<page>&<per_page>")]
-pub fn get_history(
+pub async fn get_history(
     page: Option<usize>,
     per_page: Option<usize>,
     key: AuthenticatedKey,
     db: &State<DbPool>,
+) -> Result<Json<HistoryResponse>, (Status, Json<ApiError>)> {
+    let pool = db.inner().clone();
+    rocket::tokio::task::spawn_blocking(move || get_history_blocking(page, per_page, key, &pool))
+        .await
+        .unwrap_or_else(|_| {
+            Err((
+                Status::InternalServerError,
+                Json(ApiError {
+                    error: "Background query task panicked".to_string(),
+                    code: "TASK_PANIC".to_string(),
+                    status: 500,
+                }),
+            ))
+        })
+}
+
+/// Blocking body of `get_history`, run via `spawn_blocking` like
+/// `get_qr_by_id_blocking`.
+fn get_history_blocking(
+    page: Option<usize>,
+    per_page: Option<usize>,
+    key: AuthenticatedKey,
+    db: &DbPool,
 ) -> Result<Json<HistoryResponse>, (Status, Json<ApiError>)> {
     let page = page.unwrap_or(1).max(1);
     let per_page = per_page.unwrap_or(20).clamp(1, 100);
     let offset = (page - 1) * per_page;
 
-    let conn = db.lock().unwrap();
+    let conn = db.get().unwrap();
 
     let total: usize = conn
         .query_row(
@@ -569,66 +1809,253 @@ pub fn get_history(
 }
 
 #[get("/qr/<id>")]
-pub fn get_qr_by_id(
+pub async fn get_qr_by_id(
     id: &str,
     key: AuthenticatedKey,
     db: &State<DbPool>,
+    enc: &State<db::DbEncryption>,
+    storage: &State<Arc<dyn StorageBackend>>,
+) -> Result<Json<QrResponse>, (Status, Json<ApiError>)> {
+    let id = id.to_string();
+    let pool = db.inner().clone();
+    let enc = enc.inner().clone();
+    let storage = storage.inner().clone();
+
+    rocket::tokio::task::spawn_blocking(move || get_qr_by_id_blocking(&id, key, &pool, &enc, &storage))
+        .await
+        .unwrap_or_else(|_| {
+            Err((
+                Status::InternalServerError,
+                Json(ApiError {
+                    error: "Background query task panicked".to_string(),
+                    code: "TASK_PANIC".to_string(),
+                    status: 500,
+                }),
+            ))
+        })
+}
+
+/// Blocking body of `get_qr_by_id`, run via `spawn_blocking` since it does a
+/// SQLite read plus either a storage-backend fetch or (for rows written
+/// before the pluggable storage backend existed) an AES-GCM-SIV decrypt of
+/// the image bytes still held inline in `image_data`.
+fn get_qr_by_id_blocking(
+    id: &str,
+    key: AuthenticatedKey,
+    db: &DbPool,
+    enc: &db::DbEncryption,
+    storage: &Arc<dyn StorageBackend>,
 ) -> Result<Json<QrResponse>, (Status, Json<ApiError>)> {
-    let conn = db.lock().unwrap();
+    let conn = db.get().unwrap();
 
-    conn.query_row(
-        "SELECT id, data, format, size, image_data, created_at FROM qr_codes WHERE id = ?1 AND api_key_id = ?2",
+    let (id, data, format, size, image_location, legacy_image_data, created_at) = conn.query_row(
+        "SELECT id, data, format, size, image_location, image_data, created_at FROM qr_codes WHERE id = ?1 AND api_key_id = ?2",
         rusqlite::params![id, key.id],
         |row| {
-            let image_data: Vec<u8> = row.get(4)?;
-            let format: String = row.get(2)?;
-            let content_type = if format == "svg" { "image/svg+xml" } else { "image/png" };
-            Ok(QrResponse {
-                id: row.get(0)?,
-                data: row.get(1)?,
-                format,
-                size: row.get::<_, i64>(3)? as u32,
-                image_base64: format!("data:{};base64,{}", content_type, BASE64.encode(&image_data)),
-                created_at: row.get(5)?,
-            })
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, i64>(3)? as u32,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, Option<Vec<u8>>>(5)?,
+                row.get::<_, String>(6)?,
+            ))
         },
-    ).map(Json).map_err(|_| {
+    ).map_err(|_| {
         (Status::NotFound, Json(ApiError {
             error: "QR code not found".to_string(),
             code: "NOT_FOUND".to_string(),
             status: 404,
         }))
-    })
+    })?;
+
+    let image_url = image_location
+        .as_deref()
+        .and_then(|location| storage.public_url(location));
+    let image_data = load_image(image_location, legacy_image_data, enc, storage)?;
+    let content_type = if format == "svg" {
+        "image/svg+xml"
+    } else if format == "text" {
+        "text/plain"
+    } else {
+        "image/png"
+    };
+
+    Ok(Json(QrResponse {
+        id,
+        data,
+        format,
+        size,
+        image_base64: format!("data:{};base64,{}", content_type, BASE64.encode(&image_data)),
+        image_url,
+        created_at,
+    }))
+}
+
+/// Loads the bytes for a row's image, regardless of whether it was written
+/// through a `StorageBackend` (`image_location` set) or, for rows written
+/// before the pluggable storage backend existed, still holds ciphertext
+/// directly in `image_data`. Follows a storage `Redirect` itself so callers
+/// that need inline bytes (e.g. to base64-embed them) don't have to care.
+fn load_image(
+    image_location: Option<String>,
+    legacy_image_data: Option<Vec<u8>>,
+    enc: &db::DbEncryption,
+    storage: &Arc<dyn StorageBackend>,
+) -> Result<Vec<u8>, (Status, Json<ApiError>)> {
+    match image_location {
+        Some(location) => {
+            let stored = storage.get(&location).map_err(|e| {
+                (
+                    Status::InternalServerError,
+                    Json(ApiError {
+                        error: format!("Failed to load QR image: {}", e),
+                        code: "STORAGE_FAILED".to_string(),
+                        status: 500,
+                    }),
+                )
+            })?;
+            storage::fetch_bytes(stored).map_err(|e| {
+                (
+                    Status::InternalServerError,
+                    Json(ApiError {
+                        error: format!("Failed to load QR image: {}", e),
+                        code: "STORAGE_FAILED".to_string(),
+                        status: 500,
+                    }),
+                )
+            })
+        }
+        None => {
+            let data = legacy_image_data.ok_or_else(|| {
+                (
+                    Status::NotFound,
+                    Json(ApiError {
+                        error: "QR code not found".to_string(),
+                        code: "NOT_FOUND".to_string(),
+                        status: 404,
+                    }),
+                )
+            })?;
+            db::decrypt(enc, &data).map_err(|e| {
+                (
+                    Status::InternalServerError,
+                    Json(ApiError {
+                        error: e,
+                        code: "DECRYPTION_FAILED".to_string(),
+                        status: 500,
+                    }),
+                )
+            })
+        }
+    }
+}
+
+/// Response for `get_qr_image`: either the raw bytes (the default SQLite
+/// backend, or a legacy row) or a redirect (the S3 backend hands back a
+/// presigned GET instead of this server proxying the download).
+#[derive(Responder)]
+pub enum ImageResponse {
+    Bytes((ContentType, Vec<u8>)),
+    Redirect(Redirect),
 }
 
-/// Returns the raw image bytes (PNG or SVG) with proper Content-Type header.
-/// Agents can fetch this directly to get the image without base64 overhead.
+/// Returns the raw image bytes (PNG, SVG, PDF or text) with proper Content-Type header,
+/// or redirects to where the storage backend serves them from. Agents can
+/// fetch this directly to get the image without base64 overhead.
 #[get("/qr/<id>/image")]
-pub fn get_qr_image(
+pub async fn get_qr_image(
     id: &str,
     key: AuthenticatedKey,
     db: &State<DbPool>,
-) -> Result<(ContentType, Vec<u8>), (Status, Json<ApiError>)> {
-    let conn = db.lock().unwrap();
+    enc: &State<db::DbEncryption>,
+    storage: &State<Arc<dyn StorageBackend>>,
+) -> Result<ImageResponse, (Status, Json<ApiError>)> {
+    let id = id.to_string();
+    let pool = db.inner().clone();
+    let enc = enc.inner().clone();
+    let storage = storage.inner().clone();
 
-    conn.query_row(
-        "SELECT format, image_data FROM qr_codes WHERE id = ?1 AND api_key_id = ?2",
-        rusqlite::params![id, key.id],
-        |row| {
-            let format: String = row.get(0)?;
-            let image_data: Vec<u8> = row.get(1)?;
-            Ok((format, image_data))
-        },
-    )
-    .map(|(format, data)| {
-        let ct = if format == "svg" {
-            ContentType::SVG
-        } else {
-            ContentType::PNG
-        };
-        (ct, data)
-    })
-    .map_err(|_| {
+    rocket::tokio::task::spawn_blocking(move || get_qr_image_blocking(&id, key, &pool, &enc, &storage))
+        .await
+        .unwrap_or_else(|_| {
+            Err((
+                Status::InternalServerError,
+                Json(ApiError {
+                    error: "Background query task panicked".to_string(),
+                    code: "TASK_PANIC".to_string(),
+                    status: 500,
+                }),
+            ))
+        })
+}
+
+/// Blocking body of `get_qr_image`, run via `spawn_blocking` for the same
+/// reason as `get_qr_by_id_blocking`.
+fn get_qr_image_blocking(
+    id: &str,
+    key: AuthenticatedKey,
+    db: &DbPool,
+    enc: &db::DbEncryption,
+    storage: &Arc<dyn StorageBackend>,
+) -> Result<ImageResponse, (Status, Json<ApiError>)> {
+    let conn = db.get().unwrap();
+
+    let (format, image_location, legacy_image_data) = conn
+        .query_row(
+            "SELECT format, image_location, image_data FROM qr_codes WHERE id = ?1 AND api_key_id = ?2",
+            rusqlite::params![id, key.id],
+            |row| {
+                let format: String = row.get(0)?;
+                let image_location: Option<String> = row.get(1)?;
+                let legacy_image_data: Option<Vec<u8>> = row.get(2)?;
+                Ok((format, image_location, legacy_image_data))
+            },
+        )
+        .map_err(|_| {
+            (
+                Status::NotFound,
+                Json(ApiError {
+                    error: "QR code not found".to_string(),
+                    code: "NOT_FOUND".to_string(),
+                    status: 404,
+                }),
+            )
+        })?;
+
+    resolve_image_response(&format, image_location, legacy_image_data, enc, storage)
+}
+
+/// Shared by `get_qr_image_blocking` and `get_qr_image_public_blocking`:
+/// resolves a row's `(format, image_location, image_data)` columns into the
+/// bytes/redirect to serve, once the caller has already established it's
+/// allowed to read this row.
+fn resolve_image_response(
+    format: &str,
+    image_location: Option<String>,
+    legacy_image_data: Option<Vec<u8>>,
+    enc: &db::DbEncryption,
+    storage: &Arc<dyn StorageBackend>,
+) -> Result<ImageResponse, (Status, Json<ApiError>)> {
+    if let Some(location) = image_location {
+        let stored = storage.get(&location).map_err(|e| {
+            (
+                Status::InternalServerError,
+                Json(ApiError {
+                    error: format!("Failed to load QR image: {}", e),
+                    code: "STORAGE_FAILED".to_string(),
+                    status: 500,
+                }),
+            )
+        })?;
+        return Ok(match stored {
+            StoredImage::Bytes(bytes) => ImageResponse::Bytes((content_type_for(format), bytes)),
+            StoredImage::Redirect(url) => ImageResponse::Redirect(Redirect::to(url)),
+        });
+    }
+
+    let legacy_image_data = legacy_image_data.ok_or_else(|| {
         (
             Status::NotFound,
             Json(ApiError {
@@ -637,16 +2064,274 @@ pub fn get_qr_image(
                 status: 404,
             }),
         )
-    })
-}
+    })?;
+
+    let image_data = db::decrypt(enc, &legacy_image_data).map_err(|e| {
+        (
+            Status::InternalServerError,
+            Json(ApiError {
+                error: e,
+                code: "DECRYPTION_FAILED".to_string(),
+                status: 500,
+            }),
+        )
+    })?;
+
+    Ok(ImageResponse::Bytes((content_type_for(format), image_data)))
+}
+
+/// Serves a QR image without an API key, given a link previously minted by
+/// `POST /qr/<id>/sign`. `exp` and `sig` together prove the link was issued
+/// by this server and hasn't expired, the same presigned-URL idea as
+/// `storage::S3Storage`'s `presigned_url` but signed with this server's own
+/// `image_signing_key` instead of an S3 credential.
+#[get("/qr/<id>/image/public?<exp>&<sig>")]
+pub async fn get_qr_image_public(
+    id: &str,
+    exp: i64,
+    sig: &str,
+    db: &State<DbPool>,
+    enc: &State<db::DbEncryption>,
+    storage: &State<Arc<dyn StorageBackend>>,
+    config: &State<Config>,
+) -> Result<ImageResponse, (Status, Json<ApiError>)> {
+    let id = id.to_string();
+    let sig = sig.to_string();
+    let pool = db.inner().clone();
+    let enc = enc.inner().clone();
+    let storage = storage.inner().clone();
+    let config = config.inner().clone();
+
+    rocket::tokio::task::spawn_blocking(move || {
+        get_qr_image_public_blocking(&id, exp, &sig, &pool, &enc, &storage, &config)
+    })
+    .await
+    .unwrap_or_else(|_| {
+        Err((
+            Status::InternalServerError,
+            Json(ApiError {
+                error: "Background query task panicked".to_string(),
+                code: "TASK_PANIC".to_string(),
+                status: 500,
+            }),
+        ))
+    })
+}
+
+/// Blocking body of `get_qr_image_public`, run via `spawn_blocking` for the
+/// same reason as `get_qr_image_blocking`.
+fn get_qr_image_public_blocking(
+    id: &str,
+    exp: i64,
+    sig: &str,
+    db: &DbPool,
+    enc: &db::DbEncryption,
+    storage: &Arc<dyn StorageBackend>,
+    config: &Config,
+) -> Result<ImageResponse, (Status, Json<ApiError>)> {
+    if config.image_signing_key.is_empty() {
+        return Err((
+            Status::NotFound,
+            Json(ApiError {
+                error: "Public image links are disabled".to_string(),
+                code: "NOT_FOUND".to_string(),
+                status: 404,
+            }),
+        ));
+    }
+
+    if exp < chrono::Utc::now().timestamp() {
+        return Err((
+            Status::Forbidden,
+            Json(ApiError {
+                error: "Image link has expired".to_string(),
+                code: "LINK_EXPIRED".to_string(),
+                status: 403,
+            }),
+        ));
+    }
+
+    if !db::verify_image_signature(&config.image_signing_key, id, exp, sig) {
+        return Err((
+            Status::Forbidden,
+            Json(ApiError {
+                error: "Invalid image link signature".to_string(),
+                code: "INVALID_SIGNATURE".to_string(),
+                status: 403,
+            }),
+        ));
+    }
+
+    let conn = db.get().unwrap();
+    let (format, image_location, legacy_image_data) = conn
+        .query_row(
+            "SELECT format, image_location, image_data FROM qr_codes WHERE id = ?1",
+            rusqlite::params![id],
+            |row| {
+                let format: String = row.get(0)?;
+                let image_location: Option<String> = row.get(1)?;
+                let legacy_image_data: Option<Vec<u8>> = row.get(2)?;
+                Ok((format, image_location, legacy_image_data))
+            },
+        )
+        .map_err(|_| {
+            (
+                Status::NotFound,
+                Json(ApiError {
+                    error: "QR code not found".to_string(),
+                    code: "NOT_FOUND".to_string(),
+                    status: 404,
+                }),
+            )
+        })?;
+
+    resolve_image_response(&format, image_location, legacy_image_data, enc, storage)
+}
+
+/// Mints a signed, time-limited public link to `GET /qr/<id>/image/public`
+/// that needs no API key to fetch — for handing a QR image to a browser or
+/// third party without sharing the caller's secret key.
+#[post("/qr/<id>/sign", format = "json", data = "<req>")]
+pub async fn sign_qr_image(
+    id: &str,
+    req: Json<SignImageRequest>,
+    key: AuthenticatedKey,
+    db: &State<DbPool>,
+    config: &State<Config>,
+) -> Result<Json<SignedImageUrlResponse>, (Status, Json<ApiError>)> {
+    let id = id.to_string();
+    let req = req.into_inner();
+    let pool = db.inner().clone();
+    let config = config.inner().clone();
+
+    rocket::tokio::task::spawn_blocking(move || sign_qr_image_blocking(&id, req, key, &pool, &config))
+        .await
+        .unwrap_or_else(|_| {
+            Err((
+                Status::InternalServerError,
+                Json(ApiError {
+                    error: "Background query task panicked".to_string(),
+                    code: "TASK_PANIC".to_string(),
+                    status: 500,
+                }),
+            ))
+        })
+}
+
+/// Blocking body of `sign_qr_image`, run via `spawn_blocking` since it does
+/// an ownership-check SQLite read.
+fn sign_qr_image_blocking(
+    id: &str,
+    req: SignImageRequest,
+    key: AuthenticatedKey,
+    db: &DbPool,
+    config: &Config,
+) -> Result<Json<SignedImageUrlResponse>, (Status, Json<ApiError>)> {
+    if config.image_signing_key.is_empty() {
+        return Err((
+            Status::NotFound,
+            Json(ApiError {
+                error: "Public image links are disabled".to_string(),
+                code: "NOT_FOUND".to_string(),
+                status: 404,
+            }),
+        ));
+    }
+
+    if req.ttl_secs <= 0 {
+        return Err((
+            Status::BadRequest,
+            Json(ApiError {
+                error: "ttl_secs must be positive".to_string(),
+                code: "INVALID_TTL".to_string(),
+                status: 400,
+            }),
+        ));
+    }
+
+    let conn = db.get().unwrap();
+    let exists: bool = conn
+        .query_row(
+            "SELECT COUNT(*) > 0 FROM qr_codes WHERE id = ?1 AND api_key_id = ?2",
+            rusqlite::params![id, key.id],
+            |row| row.get(0),
+        )
+        .unwrap_or(false);
+
+    if !exists {
+        return Err((
+            Status::NotFound,
+            Json(ApiError {
+                error: "QR code not found".to_string(),
+                code: "NOT_FOUND".to_string(),
+                status: 404,
+            }),
+        ));
+    }
+
+    let exp = chrono::Utc::now().timestamp() + req.ttl_secs;
+    let sig = db::sign_image_url(&config.image_signing_key, id, exp);
+    let base_url =
+        std::env::var("BASE_URL").unwrap_or_else(|_| "http://localhost:8000".to_string());
+    let url = format!(
+        "{}/api/v1/qr/{}/image/public?exp={}&sig={}",
+        base_url.trim_end_matches('/'),
+        id,
+        exp,
+        sig
+    );
+
+    Ok(Json(SignedImageUrlResponse {
+        url,
+        expires_at: exp,
+    }))
+}
+
+/// Maps a stored `format` string to its `Content-Type`, shared by
+/// `resolve_image_response`'s storage-backend and legacy inline-row paths.
+fn content_type_for(format: &str) -> ContentType {
+    if format == "svg" {
+        ContentType::SVG
+    } else if format == "text" {
+        ContentType::Plain
+    } else if format == "pdf" {
+        ContentType::PDF
+    } else {
+        ContentType::PNG
+    }
+}
 
 #[delete("/qr/<id>")]
-pub fn delete_qr(
+pub async fn delete_qr(
     id: &str,
     key: AuthenticatedKey,
     db: &State<DbPool>,
 ) -> Result<Json<serde_json::Value>, (Status, Json<ApiError>)> {
-    let conn = db.lock().unwrap();
+    let id = id.to_string();
+    let pool = db.inner().clone();
+
+    rocket::tokio::task::spawn_blocking(move || delete_qr_blocking(&id, key, &pool))
+        .await
+        .unwrap_or_else(|_| {
+            Err((
+                Status::InternalServerError,
+                Json(ApiError {
+                    error: "Background delete task panicked".to_string(),
+                    code: "TASK_PANIC".to_string(),
+                    status: 500,
+                }),
+            ))
+        })
+}
+
+/// Blocking body of `delete_qr`, run via `spawn_blocking` like
+/// `get_qr_by_id_blocking`.
+fn delete_qr_blocking(
+    id: &str,
+    key: AuthenticatedKey,
+    db: &DbPool,
+) -> Result<Json<serde_json::Value>, (Status, Json<ApiError>)> {
+    let conn = db.get().unwrap();
     let affected = conn
         .execute(
             "DELETE FROM qr_codes WHERE id = ?1 AND api_key_id = ?2",
@@ -672,21 +2357,83 @@ pub fn delete_qr(
 
 /// Create a tracked QR code that wraps a short URL for scan analytics.
 #[post("/qr/tracked", format = "json", data = "<req>")]
-pub fn create_tracked_qr(
+pub async fn create_tracked_qr(
     req: Json<CreateTrackedQrRequest>,
     key: AuthenticatedKey,
+    client_ip: ClientIp,
+    user_agent: UserAgent,
     db: &State<DbPool>,
+    config: &State<Config>,
+    storage: &State<Arc<dyn StorageBackend>>,
+    jwt_keys: &State<Arc<jwt_manage::JwtManageKeys>>,
 ) -> Result<Json<TrackedQrResponse>, (Status, Json<ApiError>)> {
     let req = req.into_inner();
+    let pool = db.inner().clone();
+    let config = config.inner().clone();
+    let storage = storage.inner().clone();
+    let jwt_keys = jwt_keys.inner().clone();
+
+    rocket::tokio::task::spawn_blocking(move || {
+        create_tracked_qr_blocking(req, key, client_ip, user_agent, &pool, &config, &storage, &jwt_keys)
+    })
+    .await
+    .unwrap_or_else(|_| {
+        Err((
+            Status::InternalServerError,
+            Json(ApiError {
+                error: "Background generation task panicked".to_string(),
+                code: "TASK_PANIC".to_string(),
+                status: 500,
+            }),
+        ))
+    })
+}
+
+/// Blocking body of `create_tracked_qr`, run via `spawn_blocking` since it
+/// renders a QR image and does several SQLite round trips.
+fn create_tracked_qr_blocking(
+    req: CreateTrackedQrRequest,
+    key: AuthenticatedKey,
+    client_ip: ClientIp,
+    user_agent: UserAgent,
+    db: &DbPool,
+    config: &Config,
+    storage: &Arc<dyn StorageBackend>,
+    jwt_keys: &jwt_manage::JwtManageKeys,
+) -> Result<Json<TrackedQrResponse>, (Status, Json<ApiError>)> {
+    key.require(auth::Action::TrackedCreate)?;
+
+    let conn = db.get().unwrap();
+    build_tracked_qr(req, &key.id, &client_ip.0, &user_agent.0, &conn, config, storage, jwt_keys)
+        .map(Json)
+        .map_err(|(status, err)| (status, Json(err)))
+}
 
+/// Does the actual work of creating one tracked QR code — validation, image
+/// rendering, storage, and the `qr_codes`/`tracked_qr` inserts — against a
+/// caller-supplied connection. Shared by `create_tracked_qr_blocking` (one
+/// pooled connection per request), `create_tracked_qr_batch_blocking` (one
+/// pooled connection for the whole batch) and `batch_generate_blocking`'s
+/// `track` items, so the rendering pipeline doesn't live in two places. It
+/// does no scope check itself: each caller checks `TrackedCreate` first.
+fn build_tracked_qr(
+    req: CreateTrackedQrRequest,
+    key_id: &str,
+    client_ip: &str,
+    user_agent: &str,
+    conn: &rusqlite::Connection,
+    config: &Config,
+    storage: &Arc<dyn StorageBackend>,
+    jwt_keys: &jwt_manage::JwtManageKeys,
+) -> Result<TrackedQrResponse, (Status, ApiError)> {
     if req.target_url.is_empty() {
         return Err((
             Status::BadRequest,
-            Json(ApiError {
+            ApiError {
                 error: "target_url cannot be empty".to_string(),
                 code: "EMPTY_TARGET_URL".to_string(),
                 status: 400,
-            }),
+            },
         ));
     }
 
@@ -694,25 +2441,28 @@ pub fn create_tracked_qr(
     if !req.target_url.starts_with("http://") && !req.target_url.starts_with("https://") {
         return Err((
             Status::BadRequest,
-            Json(ApiError {
+            ApiError {
                 error: "target_url must start with http:// or https://".to_string(),
                 code: "INVALID_URL".to_string(),
                 status: 400,
-            }),
+            },
         ));
     }
 
+    grpc_auth::check(config, key_id, &req.target_url, client_ip, user_agent)
+        .map_err(|(status, Json(err))| (status, err))?;
+
     // Generate or validate short code
     let short_code = match req.short_code {
         Some(ref code) => {
             if code.len() < 3 || code.len() > 32 {
                 return Err((
                     Status::BadRequest,
-                    Json(ApiError {
+                    ApiError {
                         error: "short_code must be 3-32 characters".to_string(),
                         code: "INVALID_SHORT_CODE".to_string(),
                         status: 400,
-                    }),
+                    },
                 ));
             }
             if !code
@@ -721,44 +2471,37 @@ pub fn create_tracked_qr(
             {
                 return Err((
                     Status::BadRequest,
-                    Json(ApiError {
+                    ApiError {
                         error: "short_code must be alphanumeric, hyphens, or underscores"
                             .to_string(),
                         code: "INVALID_SHORT_CODE".to_string(),
                         status: 400,
-                    }),
+                    },
                 ));
             }
             code.clone()
         }
-        None => {
-            // Generate a random 8-char code
-            let id = uuid::Uuid::new_v4().to_string().replace("-", "");
-            id[..8].to_string()
-        }
+        None => db::generate_short_code(&config.shortcode_signing_key),
     };
 
     // Check uniqueness
-    {
-        let conn = db.lock().unwrap();
-        let exists: bool = conn
-            .query_row(
-                "SELECT COUNT(*) > 0 FROM tracked_qr WHERE short_code = ?1",
-                rusqlite::params![short_code],
-                |row| row.get(0),
-            )
-            .unwrap_or(false);
+    let exists: bool = conn
+        .query_row(
+            "SELECT COUNT(*) > 0 FROM tracked_qr WHERE short_code = ?1",
+            rusqlite::params![short_code],
+            |row| row.get(0),
+        )
+        .unwrap_or(false);
 
-        if exists {
-            return Err((
-                Status::Conflict,
-                Json(ApiError {
-                    error: format!("Short code '{}' is already taken", short_code),
-                    code: "SHORT_CODE_TAKEN".to_string(),
-                    status: 409,
-                }),
-            ));
-        }
+    if exists {
+        return Err((
+            Status::Conflict,
+            ApiError {
+                error: format!("Short code '{}' is already taken", short_code),
+                code: "SHORT_CODE_TAKEN".to_string(),
+                status: 409,
+            },
+        ));
     }
 
     // Build the short URL that the QR code will encode.
@@ -773,30 +2516,52 @@ pub fn create_tracked_qr(
     let fg_color = qr::parse_hex_color(&req.fg_color).map_err(|e| {
         (
             Status::BadRequest,
-            Json(ApiError {
+            ApiError {
                 error: e,
                 code: "INVALID_FG_COLOR".to_string(),
                 status: 400,
-            }),
+            },
         )
     })?;
     let bg_color = qr::parse_hex_color(&req.bg_color).map_err(|e| {
         (
             Status::BadRequest,
-            Json(ApiError {
+            ApiError {
                 error: e,
                 code: "INVALID_BG_COLOR".to_string(),
                 status: 400,
-            }),
+            },
         )
     })?;
 
+    let version = req
+        .version
+        .as_deref()
+        .map(qr::parse_version)
+        .transpose()
+        .map_err(|e| {
+            (
+                Status::BadRequest,
+                ApiError {
+                    error: e,
+                    code: "INVALID_VERSION".to_string(),
+                    status: 400,
+                },
+            )
+        })?;
+
     let options = qr::QrOptions {
         size: req.size.clamp(64, 4096),
-        fg_color,
+        fg_color: qr::Fill::Solid(fg_color),
         bg_color,
         error_correction: qr::parse_ec_level(&req.error_correction),
         style: qr::QrStyle::parse(&req.style),
+        antialias: req.antialias,
+        version,
+        dot_fill_ratio: 0.85,
+        square_finder_modules: true,
+        rotation_degrees: 0.0,
+        scale: 1.0,
     };
 
     let (image_data, content_type) = match req.format.as_str() {
@@ -804,24 +2569,38 @@ pub fn create_tracked_qr(
             let svg = qr::generate_svg(&short_url, &options).map_err(|e| {
                 (
                     Status::InternalServerError,
-                    Json(ApiError {
+                    ApiError {
                         error: e,
                         code: "GENERATION_FAILED".to_string(),
                         status: 500,
-                    }),
+                    },
                 )
             })?;
             (svg.into_bytes(), "image/svg+xml")
         }
+        "text" => {
+            let text = qr::generate_text(&short_url, &options, req.invert, req.quiet_zone)
+                .map_err(|e| {
+                    (
+                        Status::InternalServerError,
+                        ApiError {
+                            error: e,
+                            code: "GENERATION_FAILED".to_string(),
+                            status: 500,
+                        },
+                    )
+                })?;
+            (text.into_bytes(), "text/plain")
+        }
         _ => {
             let png = qr::generate_png(&short_url, &options).map_err(|e| {
                 (
                     Status::InternalServerError,
-                    Json(ApiError {
+                    ApiError {
                         error: e,
                         code: "GENERATION_FAILED".to_string(),
                         status: 500,
-                    }),
+                    },
                 )
             })?;
             (png, "image/png")
@@ -836,15 +2615,29 @@ pub fn create_tracked_qr(
         BASE64.encode(&image_data)
     );
 
-    let conn = db.lock().unwrap();
+    let location = storage.put(&qr_id, content_type, &image_data).map_err(|e| {
+        (
+            Status::InternalServerError,
+            ApiError {
+                error: format!("Failed to store QR image: {}", e),
+                code: "STORAGE_FAILED".to_string(),
+                status: 500,
+            },
+        )
+    })?;
+    let image_url = storage.public_url(&location);
 
-    // Insert QR code record
-    conn.execute(
-        "INSERT INTO qr_codes (id, api_key_id, data, format, size, fg_color, bg_color, error_correction, style, image_data) 
+    // `prepare_cached` amortizes statement preparation across the many rows
+    // a batch-create call inserts on this same connection, instead of
+    // re-parsing identical SQL per item.
+    conn.prepare_cached(
+        "INSERT INTO qr_codes (id, api_key_id, data, format, size, fg_color, bg_color, error_correction, style, image_location)
          VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
-        rusqlite::params![
+    )
+    .and_then(|mut stmt| {
+        stmt.execute(rusqlite::params![
             qr_id,
-            key.id,
+            key_id,
             short_url,
             req.format,
             req.size,
@@ -852,31 +2645,40 @@ pub fn create_tracked_qr(
             req.bg_color,
             req.error_correction,
             req.style,
-            image_data,
-        ],
-    ).map_err(|e| {
+            location,
+        ])
+    })
+    .map_err(|e| {
         (
             Status::InternalServerError,
-            Json(ApiError {
+            ApiError {
                 error: format!("Failed to store QR code: {}", e),
                 code: "DB_ERROR".to_string(),
                 status: 500,
-            }),
+            },
         )
     })?;
 
-    // Insert tracked QR record
-    conn.execute(
+    conn.prepare_cached(
         "INSERT INTO tracked_qr (id, qr_id, short_code, target_url, expires_at) VALUES (?1, ?2, ?3, ?4, ?5)",
-        rusqlite::params![tracked_id, qr_id, short_code, req.target_url, req.expires_at],
-    ).map_err(|e| {
+    )
+    .and_then(|mut stmt| {
+        stmt.execute(rusqlite::params![
+            tracked_id,
+            qr_id,
+            short_code,
+            req.target_url,
+            req.expires_at
+        ])
+    })
+    .map_err(|e| {
         (
             Status::InternalServerError,
-            Json(ApiError {
+            ApiError {
                 error: format!("Failed to create tracked QR: {}", e),
                 code: "DB_ERROR".to_string(),
                 status: 500,
-            }),
+            },
         )
     })?;
 
@@ -888,7 +2690,19 @@ pub fn create_tracked_qr(
         )
         .unwrap_or_else(|_| chrono::Utc::now().to_rfc3339());
 
-    Ok(Json(TrackedQrResponse {
+    let manage_token = config.jwt_manage_tokens_enabled.then(|| {
+        let exp = req
+            .expires_at
+            .as_deref()
+            .and_then(db::parse_expiry)
+            .unwrap_or_else(|| {
+                chrono::Utc::now()
+                    + chrono::Duration::seconds(config.jwt_manage_token_default_ttl_secs as i64)
+            });
+        jwt_keys.issue(&tracked_id, &short_code, exp)
+    });
+
+    Ok(TrackedQrResponse {
         id: tracked_id,
         qr_id: qr_id.clone(),
         short_code: short_code.clone(),
@@ -903,54 +2717,193 @@ pub fn create_tracked_qr(
             format: req.format,
             size: req.size,
             image_base64,
+            image_url,
             created_at,
         },
-    }))
+        manage_token,
+    })
 }
 
-/// List all tracked QR codes for the authenticated user.
-#[get("/qr/tracked?<page>&<per_page>")]
-pub fn list_tracked_qr(
-    page: Option<usize>,
-    per_page: Option<usize>,
+const MAX_TRACKED_QR_BATCH: usize = 500;
+
+/// Create many tracked QR codes in one request (e.g. for a campaign minting
+/// hundreds of codes), instead of N round trips to `create_tracked_qr`.
+/// Per-item failures land in that item's result slot rather than failing the
+/// whole batch.
+#[post("/qr/tracked/batch", format = "json", data = "<req>")]
+pub async fn create_tracked_qr_batch(
+    req: Json<BatchCreateTrackedQrRequest>,
     key: AuthenticatedKey,
+    client_ip: ClientIp,
+    user_agent: UserAgent,
     db: &State<DbPool>,
-) -> Result<Json<TrackedQrListResponse>, (Status, Json<ApiError>)> {
-    let page = page.unwrap_or(1).max(1);
-    let per_page = per_page.unwrap_or(20).clamp(1, 100);
-    let offset = (page - 1) * per_page;
+    config: &State<Config>,
+    storage: &State<Arc<dyn StorageBackend>>,
+    jwt_keys: &State<Arc<jwt_manage::JwtManageKeys>>,
+) -> Result<Json<BatchTrackedQrResponse>, (Status, Json<ApiError>)> {
+    let req = req.into_inner();
+    let pool = db.inner().clone();
+    let config = config.inner().clone();
+    let storage = storage.inner().clone();
+    let jwt_keys = jwt_keys.inner().clone();
 
-    let conn = db.lock().unwrap();
+    rocket::tokio::task::spawn_blocking(move || {
+        create_tracked_qr_batch_blocking(req, key, client_ip, user_agent, &pool, &config, &storage, &jwt_keys)
+    })
+    .await
+    .unwrap_or_else(|_| {
+        Err((
+            Status::InternalServerError,
+            Json(ApiError {
+                error: "Background batch task panicked".to_string(),
+                code: "TASK_PANIC".to_string(),
+                status: 500,
+            }),
+        ))
+    })
+}
 
-    let total: usize = conn
-        .query_row(
-            "SELECT COUNT(*) FROM tracked_qr t JOIN qr_codes q ON t.qr_id = q.id WHERE q.api_key_id = ?1",
-            rusqlite::params![key.id],
-            |row| row.get(0),
-        )
-        .unwrap_or(0);
+/// Blocking body of `create_tracked_qr_batch`, run via `spawn_blocking` like
+/// `create_tracked_qr`. Shares one pooled connection (with
+/// cached prepared statements, see `build_tracked_qr`) across every item.
+fn create_tracked_qr_batch_blocking(
+    req: BatchCreateTrackedQrRequest,
+    key: AuthenticatedKey,
+    client_ip: ClientIp,
+    user_agent: UserAgent,
+    db: &DbPool,
+    config: &Config,
+    storage: &Arc<dyn StorageBackend>,
+    jwt_keys: &jwt_manage::JwtManageKeys,
+) -> Result<Json<BatchTrackedQrResponse>, (Status, Json<ApiError>)> {
+    key.require(auth::Action::TrackedCreate)?;
 
-    let mut stmt = conn
-        .prepare(
-            "SELECT t.id, t.short_code, t.target_url, t.scan_count, t.expires_at, t.created_at 
-         FROM tracked_qr t JOIN qr_codes q ON t.qr_id = q.id 
-         WHERE q.api_key_id = ?1 
-         ORDER BY t.created_at DESC LIMIT ?2 OFFSET ?3",
-        )
-        .map_err(|e| {
-            (
-                Status::InternalServerError,
-                Json(ApiError {
-                    error: format!("Database error: {}", e),
-                    code: "DB_ERROR".to_string(),
-                    status: 500,
-                }),
-            )
-        })?;
+    if req.items.len() > MAX_TRACKED_QR_BATCH {
+        return Err((
+            Status::PayloadTooLarge,
+            Json(ApiError {
+                error: format!(
+                    "Batch exceeds the {}-item limit",
+                    MAX_TRACKED_QR_BATCH
+                ),
+                code: "BATCH_TOO_LARGE".to_string(),
+                status: 413,
+            }),
+        ));
+    }
+
+    let conn = db.get().unwrap();
+    let mut results = Vec::with_capacity(req.items.len());
+    let mut succeeded = 0usize;
+    let mut failed = 0usize;
+
+    for (index, item) in req.items.into_iter().enumerate() {
+        match build_tracked_qr(item, &key.id, &client_ip.0, &user_agent.0, &conn, config, storage, jwt_keys) {
+            Ok(resp) => {
+                succeeded += 1;
+                results.push(TrackedQrBatchItemResult::Success(resp));
+            }
+            Err((_, err)) => {
+                failed += 1;
+                results.push(TrackedQrBatchItemResult::Error {
+                    index,
+                    error: err.error,
+                    code: err.code,
+                });
+            }
+        }
+    }
+
+    let total = results.len();
+    Ok(Json(BatchTrackedQrResponse {
+        results,
+        total,
+        succeeded,
+        failed,
+    }))
+}
+
+/// Max rows in `TrackedQrFacets::top_scanned`.
+const TOP_SCANNED_LIMIT: i64 = 5;
+
+/// List tracked QR codes for the authenticated user, with optional full-text
+/// search over `target_url`/`short_code`, `min_scan_count`/`created_after`
+/// filters, and sorting by `scan_count` or `created_at` (default) —
+/// alongside `limit`/`offset`-style `page`/`per_page` pagination and a
+/// `facets` summary computed over the same filtered set.
+#[get("/qr/tracked?<page>&<per_page>&<q>&<min_scan_count>&<created_after>&<sort_by>&<order>")]
+pub fn list_tracked_qr(
+    page: Option<usize>,
+    per_page: Option<usize>,
+    q: Option<String>,
+    min_scan_count: Option<i64>,
+    created_after: Option<String>,
+    sort_by: Option<String>,
+    order: Option<String>,
+    key: AuthenticatedKey,
+    db: &State<DbPool>,
+) -> Result<Json<TrackedQrListResponse>, (Status, Json<ApiError>)> {
+    key.require(auth::Action::TrackedList)?;
+
+    let page = page.unwrap_or(1).max(1);
+    let per_page = per_page.unwrap_or(20).clamp(1, 100);
+    let offset = (page - 1) * per_page;
+    // `search` is the LIKE pattern; `None` stays `NULL` so the `IS NULL`
+    // branch below short-circuits to "no filter" instead of matching nothing.
+    let search = q.as_ref().map(|s| format!("%{}%", s));
+    let sort_column = match sort_by.as_deref() {
+        Some("scan_count") => "scan_count",
+        _ => "created_at",
+    };
+    let sort_order = match order.as_deref() {
+        Some("asc") => "ASC",
+        _ => "DESC",
+    };
+
+    let conn = db.get().unwrap();
+
+    // Shared by the count, list, and facet queries below, so a search term
+    // or filter narrows all three consistently.
+    const FILTER_SQL: &str = "q.api_key_id = ?1
+           AND (?2 IS NULL OR t.target_url LIKE ?2 OR t.short_code LIKE ?2)
+           AND (?3 IS NULL OR t.scan_count >= ?3)
+           AND (?4 IS NULL OR t.created_at >= ?4)";
+
+    let db_error = |e: rusqlite::Error| {
+        (
+            Status::InternalServerError,
+            Json(ApiError {
+                error: format!("Database error: {}", e),
+                code: "DB_ERROR".to_string(),
+                status: 500,
+            }),
+        )
+    };
+
+    let total: usize = conn
+        .query_row(
+            &format!(
+                "SELECT COUNT(*) FROM tracked_qr t JOIN qr_codes q ON t.qr_id = q.id WHERE {}",
+                FILTER_SQL
+            ),
+            rusqlite::params![key.id, search, min_scan_count, created_after],
+            |row| row.get(0),
+        )
+        .unwrap_or(0);
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT t.id, t.short_code, t.target_url, t.scan_count, t.expires_at, t.created_at
+         FROM tracked_qr t JOIN qr_codes q ON t.qr_id = q.id
+         WHERE {}
+         ORDER BY t.{} {} LIMIT ?5 OFFSET ?6",
+            FILTER_SQL, sort_column, sort_order
+        ))
+        .map_err(db_error)?;
 
     let items = stmt
         .query_map(
-            rusqlite::params![key.id, per_page as i64, offset as i64],
+            rusqlite::params![key.id, search, min_scan_count, created_after, per_page as i64, offset as i64],
             |row| {
                 Ok(TrackedQrListItem {
                     id: row.get(0)?,
@@ -962,6 +2915,274 @@ pub fn list_tracked_qr(
                 })
             },
         )
+        .map_err(db_error)?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let (total_scans, distinct_short_codes): (i64, i64) = conn
+        .query_row(
+            &format!(
+                "SELECT COALESCE(SUM(t.scan_count), 0), COUNT(DISTINCT t.short_code)
+                 FROM tracked_qr t JOIN qr_codes q ON t.qr_id = q.id WHERE {}",
+                FILTER_SQL
+            ),
+            rusqlite::params![key.id, search, min_scan_count, created_after],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap_or((0, 0));
+
+    let mut top_stmt = conn
+        .prepare(&format!(
+            "SELECT t.short_code, t.scan_count
+             FROM tracked_qr t JOIN qr_codes q ON t.qr_id = q.id
+             WHERE {}
+             ORDER BY t.scan_count DESC LIMIT ?5",
+            FILTER_SQL
+        ))
+        .map_err(db_error)?;
+
+    let top_scanned = top_stmt
+        .query_map(
+            rusqlite::params![key.id, search, min_scan_count, created_after, TOP_SCANNED_LIMIT],
+            |row| {
+                Ok(TrackedQrTopItem {
+                    short_code: row.get(0)?,
+                    scan_count: row.get(1)?,
+                })
+            },
+        )
+        .map_err(db_error)?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(Json(TrackedQrListResponse {
+        items,
+        total,
+        facets: TrackedQrFacets {
+            total_scans,
+            distinct_short_codes,
+            top_scanned,
+        },
+    }))
+}
+
+/// Default/max page size for `recent_scans`, matching the old hardcoded
+/// "last 100" behavior when `limit` is omitted.
+const RECENT_SCANS_DEFAULT_LIMIT: u32 = 100;
+const RECENT_SCANS_MAX_LIMIT: u32 = 100;
+
+/// Get scan analytics for a tracked QR code. `recent_scans` is paginated via
+/// an opaque `before` cursor (a previously-returned scan `id`): omit it for
+/// the newest page, then pass back the `next_cursor` from the prior response
+/// to page further back in history. Omitting both `limit` and `before`
+/// reproduces the old fixed "last 100" response for backward compatibility.
+///
+/// Ownership is proven either the normal way (an `AuthenticatedKey`/
+/// `AuthenticatedSession` via `Principal`) or, when
+/// `Config::jwt_manage_tokens_enabled` is on, by the signed manage-token JWT
+/// handed back at creation time (see `jwt_manage` and `delete_tracked_qr`,
+/// which accepts the same token).
+#[get("/qr/tracked/<id>/stats?<limit>&<before>")]
+pub async fn get_tracked_qr_stats(
+    id: &str,
+    limit: Option<u32>,
+    before: Option<String>,
+    principal: Option<Principal>,
+    bearer: auth::BearerToken,
+    db: &State<DbPool>,
+    enc: &State<db::DbEncryption>,
+    config: &State<Config>,
+    jwt_keys: &State<Arc<jwt_manage::JwtManageKeys>>,
+) -> Result<Json<TrackedQrStatsResponse>, (Status, Json<ApiError>)> {
+    let id = id.to_string();
+    let pool = db.inner().clone();
+    let enc = enc.inner().clone();
+    let config = config.inner().clone();
+    let jwt_keys = jwt_keys.inner().clone();
+
+    rocket::tokio::task::spawn_blocking(move || {
+        get_tracked_qr_stats_blocking(
+            &id,
+            limit,
+            before.as_deref(),
+            principal,
+            bearer,
+            &pool,
+            &enc,
+            &config,
+            &jwt_keys,
+        )
+    })
+    .await
+    .unwrap_or_else(|_| {
+        Err((
+            Status::InternalServerError,
+            Json(ApiError {
+                error: "Background stats task panicked".to_string(),
+                code: "TASK_PANIC".to_string(),
+                status: 500,
+            }),
+        ))
+    })
+}
+
+/// Blocking body of `get_tracked_qr_stats`, run via `spawn_blocking` like
+/// `create_tracked_qr`.
+#[allow(clippy::too_many_arguments)]
+fn get_tracked_qr_stats_blocking(
+    id: &str,
+    limit: Option<u32>,
+    before: Option<&str>,
+    principal: Option<Principal>,
+    bearer: auth::BearerToken,
+    db: &DbPool,
+    enc: &db::DbEncryption,
+    config: &Config,
+    jwt_keys: &jwt_manage::JwtManageKeys,
+) -> Result<Json<TrackedQrStatsResponse>, (Status, Json<ApiError>)> {
+    let conn = db.get().unwrap();
+
+    let not_found = || {
+        (
+            Status::NotFound,
+            Json(ApiError {
+                error: "Tracked QR code not found".to_string(),
+                code: "NOT_FOUND".to_string(),
+                status: 404,
+            }),
+        )
+    };
+
+    // A short_code lookup is needed either way: to check a manage-token
+    // JWT's `short_code` claim, or (below) to scope the query by API key.
+    let short_code: String = conn
+        .query_row(
+            "SELECT short_code FROM tracked_qr WHERE id = ?1",
+            rusqlite::params![id],
+            |row| row.get(0),
+        )
+        .map_err(|_| not_found())?;
+
+    let jwt_authorized = config.jwt_manage_tokens_enabled
+        && bearer
+            .0
+            .as_deref()
+            .is_some_and(|token| jwt_keys.verify(token, id, &short_code, jwt_manage::MANAGE_SCOPE).is_ok());
+
+    // API keys only see their own tracked QR codes; an admin dashboard
+    // session, or a manage-token holder, isn't scoped to a single key. A
+    // non-admin session gets neither — it has no key of its own to scope
+    // by and isn't cleared to see every tenant's rows (see
+    // `Principal::ownership_scope`).
+    let key_scope = if jwt_authorized {
+        None
+    } else {
+        let principal = principal.ok_or_else(|| {
+            (
+                Status::Unauthorized,
+                Json(ApiError {
+                    error: "Missing API key or session".to_string(),
+                    code: "UNAUTHORIZED".to_string(),
+                    status: 401,
+                }),
+            )
+        })?;
+        if let Principal::ApiKey(ref k) = principal {
+            k.require(auth::Action::TrackedStats)?;
+        }
+        principal.ownership_scope()?.map(|s| s.to_string())
+    };
+
+    let tracked = match key_scope {
+        Some(key_id) => conn.query_row(
+            "SELECT t.id, t.short_code, t.target_url, t.scan_count, t.expires_at, t.created_at
+         FROM tracked_qr t JOIN qr_codes q ON t.qr_id = q.id
+         WHERE t.id = ?1 AND q.api_key_id = ?2",
+            rusqlite::params![id, key_id],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, i64>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, String>(5)?,
+                ))
+            },
+        ),
+        None => conn.query_row(
+            "SELECT id, short_code, target_url, scan_count, expires_at, created_at
+         FROM tracked_qr WHERE id = ?1",
+            rusqlite::params![id],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, i64>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, String>(5)?,
+                ))
+            },
+        ),
+    }
+        .map_err(|_| not_found())?;
+
+    // `before` is a previously-returned scan id; translate it to that row's
+    // rowid so the page query can do a simple keyset comparison. `rowid`
+    // (not `scanned_at`, which only has second resolution) is the tie-break
+    // that matches insertion order exactly.
+    let before_rowid: i64 = match before {
+        Some(cursor) => conn
+            .query_row(
+                "SELECT rowid FROM scan_events WHERE id = ?1 AND tracked_qr_id = ?2",
+                rusqlite::params![cursor, id],
+                |row| row.get(0),
+            )
+            .map_err(|_| {
+                (
+                    Status::BadRequest,
+                    Json(ApiError {
+                        error: "Invalid or unknown 'before' cursor".to_string(),
+                        code: "INVALID_CURSOR".to_string(),
+                        status: 400,
+                    }),
+                )
+            })?,
+        None => i64::MAX,
+    };
+    let limit = limit
+        .unwrap_or(RECENT_SCANS_DEFAULT_LIMIT)
+        .clamp(1, RECENT_SCANS_MAX_LIMIT) as i64;
+
+    // Fetch one extra row past `limit` so we know whether a next page exists
+    // without a separate COUNT query.
+    let mut stmt = conn
+        .prepare(
+            "SELECT rowid, id, scanned_at, user_agent, referrer FROM scan_events
+         WHERE tracked_qr_id = ?1 AND rowid < ?2 ORDER BY rowid DESC LIMIT ?3",
+        )
+        .map_err(|e| {
+            (
+                Status::InternalServerError,
+                Json(ApiError {
+                    error: format!("Database error: {}", e),
+                    code: "DB_ERROR".to_string(),
+                    status: 500,
+                }),
+            )
+        })?;
+
+    let mut rows: Vec<(i64, String, String, Option<Vec<u8>>, Option<Vec<u8>>)> = stmt
+        .query_map(rusqlite::params![id, before_rowid, limit + 1], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<Vec<u8>>>(3)?,
+                row.get::<_, Option<Vec<u8>>>(4)?,
+            ))
+        })
         .map_err(|e| {
             (
                 Status::InternalServerError,
@@ -975,71 +3196,450 @@ pub fn list_tracked_qr(
         .filter_map(|r| r.ok())
         .collect();
 
-    Ok(Json(TrackedQrListResponse { items, total }))
+    let has_more = rows.len() as i64 > limit;
+    if has_more {
+        rows.truncate(limit as usize);
+    }
+    let next_cursor = if has_more {
+        rows.last().map(|(_, id, ..)| id.clone())
+    } else {
+        None
+    };
+
+    let recent_scans: Vec<ScanEventResponse> = rows
+        .into_iter()
+        .map(|(_, id, scanned_at, user_agent, referrer)| ScanEventResponse {
+            id,
+            scanned_at,
+            user_agent: db::decrypt_opt_string(enc, user_agent).unwrap_or(None),
+            referrer: db::decrypt_opt_string(enc, referrer).unwrap_or(None),
+        })
+        .collect();
+
+    Ok(Json(TrackedQrStatsResponse {
+        id: tracked.0,
+        short_code: tracked.1,
+        target_url: tracked.2,
+        scan_count: tracked.3,
+        expires_at: tracked.4,
+        created_at: tracked.5,
+        recent_scans,
+        next_cursor,
+    }))
+}
+
+/// Default/max page size for `GET /qr/tracked/{id}/scans`.
+const SCAN_HISTORY_DEFAULT_LIMIT: u32 = 50;
+const SCAN_HISTORY_MAX_LIMIT: u32 = 200;
+
+/// Encodes a `scan_events` row's `(scanned_at, rowid)` as the opaque
+/// `?after=` cursor for `get_tracked_qr_scans`. `rowid` (assigned in
+/// insertion order) is the real tie-break; `scanned_at` only has
+/// second resolution and rides along so the cursor is self-describing.
+fn encode_scan_cursor(scanned_at: &str, rowid: i64) -> String {
+    URL_SAFE_NO_PAD.encode(format!("{}:{}", scanned_at, rowid))
+}
+
+/// Decodes a `?after=` cursor back to the `rowid` boundary for the next
+/// page. Returns `None` on anything malformed so callers can report a 400
+/// rather than panic.
+fn decode_scan_cursor(cursor: &str) -> Option<i64> {
+    let decoded = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+    let decoded = String::from_utf8(decoded).ok()?;
+    let (_, rowid) = decoded.rsplit_once(':')?;
+    rowid.parse().ok()
+}
+
+/// Full scan-event history for a tracked QR code, paginated newest-first via
+/// an opaque `after` cursor: omit it for the newest page, then pass back the
+/// previous response's `next_cursor` to page further back in history.
+/// Distinct from `get_tracked_qr_stats`'s `recent_scans`, which is capped at
+/// `RECENT_SCANS_MAX_LIMIT` and meant for the stats dashboard rather than
+/// exhaustive export.
+///
+/// Ownership is proven the same way as `get_tracked_qr_stats`: an
+/// `AuthenticatedKey`/`AuthenticatedSession` via `Principal`, or a signed
+/// manage-token JWT.
+#[get("/qr/tracked/<id>/scans?<after>&<limit>")]
+pub async fn get_tracked_qr_scans(
+    id: &str,
+    after: Option<String>,
+    limit: Option<u32>,
+    principal: Option<Principal>,
+    bearer: auth::BearerToken,
+    db: &State<DbPool>,
+    enc: &State<db::DbEncryption>,
+    config: &State<Config>,
+    jwt_keys: &State<Arc<jwt_manage::JwtManageKeys>>,
+) -> Result<Json<ScanHistoryResponse>, (Status, Json<ApiError>)> {
+    let id = id.to_string();
+    let pool = db.inner().clone();
+    let enc = enc.inner().clone();
+    let config = config.inner().clone();
+    let jwt_keys = jwt_keys.inner().clone();
+
+    rocket::tokio::task::spawn_blocking(move || {
+        get_tracked_qr_scans_blocking(
+            &id,
+            after.as_deref(),
+            limit,
+            principal,
+            bearer,
+            &pool,
+            &enc,
+            &config,
+            &jwt_keys,
+        )
+    })
+    .await
+    .unwrap_or_else(|_| {
+        Err((
+            Status::InternalServerError,
+            Json(ApiError {
+                error: "Background scan history task panicked".to_string(),
+                code: "TASK_PANIC".to_string(),
+                status: 500,
+            }),
+        ))
+    })
+}
+
+/// Blocking body of `get_tracked_qr_scans`, run via `spawn_blocking` like
+/// `create_tracked_qr`.
+#[allow(clippy::too_many_arguments)]
+fn get_tracked_qr_scans_blocking(
+    id: &str,
+    after: Option<&str>,
+    limit: Option<u32>,
+    principal: Option<Principal>,
+    bearer: auth::BearerToken,
+    db: &DbPool,
+    enc: &db::DbEncryption,
+    config: &Config,
+    jwt_keys: &jwt_manage::JwtManageKeys,
+) -> Result<Json<ScanHistoryResponse>, (Status, Json<ApiError>)> {
+    let conn = db.get().unwrap();
+
+    let not_found = || {
+        (
+            Status::NotFound,
+            Json(ApiError {
+                error: "Tracked QR code not found".to_string(),
+                code: "NOT_FOUND".to_string(),
+                status: 404,
+            }),
+        )
+    };
+
+    let short_code: String = conn
+        .query_row(
+            "SELECT short_code FROM tracked_qr WHERE id = ?1",
+            rusqlite::params![id],
+            |row| row.get(0),
+        )
+        .map_err(|_| not_found())?;
+
+    let jwt_authorized = config.jwt_manage_tokens_enabled
+        && bearer
+            .0
+            .as_deref()
+            .is_some_and(|token| jwt_keys.verify(token, id, &short_code, jwt_manage::MANAGE_SCOPE).is_ok());
+
+    // Same ownership rule as `get_tracked_qr_stats`: see
+    // `Principal::ownership_scope`.
+    let key_scope = if jwt_authorized {
+        None
+    } else {
+        let principal = principal.ok_or_else(|| {
+            (
+                Status::Unauthorized,
+                Json(ApiError {
+                    error: "Missing API key or session".to_string(),
+                    code: "UNAUTHORIZED".to_string(),
+                    status: 401,
+                }),
+            )
+        })?;
+        if let Principal::ApiKey(ref k) = principal {
+            k.require(auth::Action::TrackedStats)?;
+        }
+        principal.ownership_scope()?.map(|s| s.to_string())
+    };
+
+    let owned = match key_scope {
+        Some(key_id) => conn
+            .query_row(
+                "SELECT 1 FROM tracked_qr t JOIN qr_codes q ON t.qr_id = q.id
+             WHERE t.id = ?1 AND q.api_key_id = ?2",
+                rusqlite::params![id, key_id],
+                |row| row.get::<_, i64>(0),
+            )
+            .is_ok(),
+        None => true,
+    };
+    if !owned {
+        return Err(not_found());
+    }
+
+    let after_rowid: i64 = match after {
+        Some(cursor) => decode_scan_cursor(cursor).ok_or_else(|| {
+            (
+                Status::BadRequest,
+                Json(ApiError {
+                    error: "Invalid or unknown 'after' cursor".to_string(),
+                    code: "INVALID_CURSOR".to_string(),
+                    status: 400,
+                }),
+            )
+        })?,
+        None => i64::MAX,
+    };
+    let limit = limit
+        .unwrap_or(SCAN_HISTORY_DEFAULT_LIMIT)
+        .clamp(1, SCAN_HISTORY_MAX_LIMIT) as i64;
+
+    // Fetch one extra row past `limit` so we know whether a next page exists
+    // without a separate COUNT query.
+    let mut stmt = conn
+        .prepare(
+            "SELECT rowid, id, scanned_at, user_agent, referrer FROM scan_events
+         WHERE tracked_qr_id = ?1 AND rowid < ?2 ORDER BY rowid DESC LIMIT ?3",
+        )
+        .map_err(|e| {
+            (
+                Status::InternalServerError,
+                Json(ApiError {
+                    error: format!("Database error: {}", e),
+                    code: "DB_ERROR".to_string(),
+                    status: 500,
+                }),
+            )
+        })?;
+
+    let mut rows: Vec<(i64, String, String, Option<Vec<u8>>, Option<Vec<u8>>)> = stmt
+        .query_map(rusqlite::params![id, after_rowid, limit + 1], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<Vec<u8>>>(3)?,
+                row.get::<_, Option<Vec<u8>>>(4)?,
+            ))
+        })
+        .map_err(|e| {
+            (
+                Status::InternalServerError,
+                Json(ApiError {
+                    error: format!("Query error: {}", e),
+                    code: "DB_ERROR".to_string(),
+                    status: 500,
+                }),
+            )
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let has_more = rows.len() as i64 > limit;
+    if has_more {
+        rows.truncate(limit as usize);
+    }
+    let next_cursor = if has_more {
+        rows.last()
+            .map(|(rowid, _, scanned_at, ..)| encode_scan_cursor(scanned_at, *rowid))
+    } else {
+        None
+    };
+
+    let scans: Vec<ScanEventResponse> = rows
+        .into_iter()
+        .map(|(_, id, scanned_at, user_agent, referrer)| ScanEventResponse {
+            id,
+            scanned_at,
+            user_agent: db::decrypt_opt_string(enc, user_agent).unwrap_or(None),
+            referrer: db::decrypt_opt_string(enc, referrer).unwrap_or(None),
+        })
+        .collect();
+
+    Ok(Json(ScanHistoryResponse { scans, next_cursor }))
+}
+
+/// Scan counts bucketed by hour/day, plus top-10 breakdowns by country,
+/// device, and referrer, for a tracked QR code. Aggregated in SQL (`GROUP BY
+/// strftime(...)`/column) rather than loading `scan_events` rows into
+/// memory, so this stays cheap as scan volume grows.
+#[get("/qr/tracked/<id>/stats/timeseries?<bucket>&<from>&<to>")]
+pub async fn get_tracked_qr_timeseries(
+    id: &str,
+    bucket: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    principal: Principal,
+    db: &State<DbPool>,
+) -> Result<Json<TrackedQrTimeseriesResponse>, (Status, Json<ApiError>)> {
+    let id = id.to_string();
+    let pool = db.inner().clone();
+
+    rocket::tokio::task::spawn_blocking(move || {
+        get_tracked_qr_timeseries_blocking(&id, bucket, from, to, principal, &pool)
+    })
+    .await
+    .unwrap_or_else(|_| {
+        Err((
+            Status::InternalServerError,
+            Json(ApiError {
+                error: "Background stats task panicked".to_string(),
+                code: "TASK_PANIC".to_string(),
+                status: 500,
+            }),
+        ))
+    })
+}
+
+/// Runs a top-N (by count, descending, capped at 10) breakdown of
+/// non-bot scans for a tracked QR code over `column`, with NULLs folded into
+/// `fallback` (e.g. `referrer_host` -> `"direct"` for scans with no
+/// referrer). `column`/`fallback` are always internal literals, never
+/// request input, so interpolating them into the query text is safe.
+fn scan_breakdown(
+    conn: &rusqlite::Connection,
+    id: &str,
+    from: &Option<String>,
+    to: &Option<String>,
+    column: &str,
+    fallback: &str,
+) -> Result<Vec<ScanBreakdownItem>, (Status, Json<ApiError>)> {
+    let sql = format!(
+        "SELECT COALESCE({column}, '{fallback}') AS bucket_key, COUNT(*) AS cnt
+         FROM scan_events
+         WHERE tracked_qr_id = ?1 AND is_bot = 0
+           AND (?2 IS NULL OR scanned_at >= ?2)
+           AND (?3 IS NULL OR scanned_at <= ?3)
+         GROUP BY bucket_key ORDER BY cnt DESC LIMIT 10",
+        column = column,
+        fallback = fallback,
+    );
+
+    let mut stmt = conn.prepare(&sql).map_err(|e| {
+        (
+            Status::InternalServerError,
+            Json(ApiError {
+                error: format!("Database error: {}", e),
+                code: "DB_ERROR".to_string(),
+                status: 500,
+            }),
+        )
+    })?;
+
+    stmt.query_map(rusqlite::params![id, from, to], |row| {
+        Ok(ScanBreakdownItem {
+            key: row.get(0)?,
+            count: row.get(1)?,
+        })
+    })
+    .map_err(|e| {
+        (
+            Status::InternalServerError,
+            Json(ApiError {
+                error: format!("Query error: {}", e),
+                code: "DB_ERROR".to_string(),
+                status: 500,
+            }),
+        )
+    })?
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(|e| {
+        (
+            Status::InternalServerError,
+            Json(ApiError {
+                error: format!("Query error: {}", e),
+                code: "DB_ERROR".to_string(),
+                status: 500,
+            }),
+        )
+    })
 }
 
-/// Get scan analytics for a tracked QR code.
-#[get("/qr/tracked/<id>/stats")]
-pub fn get_tracked_qr_stats(
+/// Blocking body of `get_tracked_qr_timeseries`, run via `spawn_blocking`
+/// like `create_tracked_qr`.
+fn get_tracked_qr_timeseries_blocking(
     id: &str,
-    key: AuthenticatedKey,
-    db: &State<DbPool>,
-) -> Result<Json<TrackedQrStatsResponse>, (Status, Json<ApiError>)> {
-    let conn = db.lock().unwrap();
-
-    // Verify ownership via the linked qr_codes record
-    let tracked = conn
-        .query_row(
-            "SELECT t.id, t.short_code, t.target_url, t.scan_count, t.expires_at, t.created_at 
-         FROM tracked_qr t JOIN qr_codes q ON t.qr_id = q.id 
-         WHERE t.id = ?1 AND q.api_key_id = ?2",
-            rusqlite::params![id, key.id],
-            |row| {
-                Ok((
-                    row.get::<_, String>(0)?,
-                    row.get::<_, String>(1)?,
-                    row.get::<_, String>(2)?,
-                    row.get::<_, i64>(3)?,
-                    row.get::<_, Option<String>>(4)?,
-                    row.get::<_, String>(5)?,
-                ))
-            },
-        )
-        .map_err(|_| {
-            (
-                Status::NotFound,
+    bucket: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    principal: Principal,
+    db: &DbPool,
+) -> Result<Json<TrackedQrTimeseriesResponse>, (Status, Json<ApiError>)> {
+    let bucket = bucket.unwrap_or_else(|| "day".to_string());
+    let bucket_expr = match bucket.as_str() {
+        "hour" => "strftime('%Y-%m-%dT%H:00:00', scanned_at)",
+        "day" => "strftime('%Y-%m-%d', scanned_at)",
+        _ => {
+            return Err((
+                Status::BadRequest,
                 Json(ApiError {
-                    error: "Tracked QR code not found".to_string(),
-                    code: "NOT_FOUND".to_string(),
-                    status: 404,
+                    error: "bucket must be 'hour' or 'day'".to_string(),
+                    code: "INVALID_BUCKET".to_string(),
+                    status: 400,
                 }),
-            )
-        })?;
+            ));
+        }
+    };
 
-    // Get recent scan events (last 100)
-    let mut stmt = conn
-        .prepare(
-            "SELECT id, scanned_at, user_agent, referrer FROM scan_events 
-         WHERE tracked_qr_id = ?1 ORDER BY scanned_at DESC LIMIT 100",
-        )
-        .map_err(|e| {
-            (
-                Status::InternalServerError,
-                Json(ApiError {
-                    error: format!("Database error: {}", e),
-                    code: "DB_ERROR".to_string(),
-                    status: 500,
-                }),
-            )
-        })?;
+    let conn = db.get().unwrap();
 
-    let recent_scans = stmt
-        .query_map(rusqlite::params![id], |row| {
-            Ok(ScanEventResponse {
-                id: row.get(0)?,
-                scanned_at: row.get(1)?,
-                user_agent: row.get(2)?,
-                referrer: row.get(3)?,
+    if let Principal::ApiKey(ref k) = principal {
+        k.require(auth::Action::TrackedStats)?;
+    }
+
+    // Same ownership rule as `get_tracked_qr_stats`: see
+    // `Principal::ownership_scope`.
+    let owned: rusqlite::Result<i32> = match principal.ownership_scope()? {
+        Some(key_id) => conn.query_row(
+            "SELECT 1 FROM tracked_qr t JOIN qr_codes q ON t.qr_id = q.id WHERE t.id = ?1 AND q.api_key_id = ?2",
+            rusqlite::params![id, key_id],
+            |row| row.get(0),
+        ),
+        None => conn.query_row(
+            "SELECT 1 FROM tracked_qr WHERE id = ?1",
+            rusqlite::params![id],
+            |row| row.get(0),
+        ),
+    };
+    if owned.is_err() {
+        return Err((
+            Status::NotFound,
+            Json(ApiError {
+                error: "Tracked QR code not found".to_string(),
+                code: "NOT_FOUND".to_string(),
+                status: 404,
+            }),
+        ));
+    }
+
+    let series_sql = format!(
+        "SELECT {bucket_expr} AS bucket_key, COUNT(*) AS cnt
+         FROM scan_events
+         WHERE tracked_qr_id = ?1 AND is_bot = 0
+           AND (?2 IS NULL OR scanned_at >= ?2)
+           AND (?3 IS NULL OR scanned_at <= ?3)
+         GROUP BY bucket_key ORDER BY bucket_key",
+        bucket_expr = bucket_expr,
+    );
+    let mut stmt = conn.prepare(&series_sql).map_err(|e| {
+        (
+            Status::InternalServerError,
+            Json(ApiError {
+                error: format!("Database error: {}", e),
+                code: "DB_ERROR".to_string(),
+                status: 500,
+            }),
+        )
+    })?;
+    let series: Vec<ScanTimeseriesBucket> = stmt
+        .query_map(rusqlite::params![id, from, to], |row| {
+            Ok(ScanTimeseriesBucket {
+                bucket: row.get(0)?,
+                count: row.get(1)?,
             })
         })
         .map_err(|e| {
@@ -1054,39 +3654,108 @@ pub fn get_tracked_qr_stats(
         })?
         .filter_map(|r| r.ok())
         .collect();
+    drop(stmt);
 
-    Ok(Json(TrackedQrStatsResponse {
-        id: tracked.0,
-        short_code: tracked.1,
-        target_url: tracked.2,
-        scan_count: tracked.3,
-        expires_at: tracked.4,
-        created_at: tracked.5,
-        recent_scans,
+    let bot_count: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM scan_events
+             WHERE tracked_qr_id = ?1 AND is_bot = 1
+               AND (?2 IS NULL OR scanned_at >= ?2)
+               AND (?3 IS NULL OR scanned_at <= ?3)",
+            rusqlite::params![id, from, to],
+            |row| row.get(0),
+        )
+        .unwrap_or(0);
+
+    let by_country = scan_breakdown(&conn, id, &from, &to, "country", "unknown")?;
+    let by_device = scan_breakdown(&conn, id, &from, &to, "device_type", "unknown")?;
+    let by_referrer = scan_breakdown(&conn, id, &from, &to, "referrer_host", "direct")?;
+
+    Ok(Json(TrackedQrTimeseriesResponse {
+        id: id.to_string(),
+        bucket,
+        from,
+        to,
+        series,
+        bot_count,
+        by_country,
+        by_device,
+        by_referrer,
     }))
 }
 
-/// Delete a tracked QR code (and its scan events).
+/// Delete a tracked QR code (and its scan events). Ownership is proven
+/// either the normal way (an `AuthenticatedKey`/`AuthenticatedSession` via
+/// `Principal`) or, when `Config::jwt_manage_tokens_enabled` is on, by a
+/// signed manage-token JWT presented as `Authorization: Bearer <token>` (see
+/// `jwt_manage`) — so a holder of the token can delete the QR without ever
+/// authenticating as the key that created it.
 #[delete("/qr/tracked/<id>")]
 pub fn delete_tracked_qr(
     id: &str,
-    key: AuthenticatedKey,
+    principal: Option<Principal>,
+    bearer: auth::BearerToken,
     db: &State<DbPool>,
+    config: &State<Config>,
+    jwt_keys: &State<Arc<jwt_manage::JwtManageKeys>>,
 ) -> Result<Json<serde_json::Value>, (Status, Json<ApiError>)> {
-    let conn = db.lock().unwrap();
+    let conn = db.get().unwrap();
 
-    // Verify ownership
-    let qr_id: String = conn.query_row(
-        "SELECT t.qr_id FROM tracked_qr t JOIN qr_codes q ON t.qr_id = q.id WHERE t.id = ?1 AND q.api_key_id = ?2",
-        rusqlite::params![id, key.id],
-        |row| row.get(0),
-    ).map_err(|_| {
-        (Status::NotFound, Json(ApiError {
-            error: "Tracked QR code not found".to_string(),
-            code: "NOT_FOUND".to_string(),
-            status: 404,
-        }))
-    })?;
+    let not_found = || {
+        (
+            Status::NotFound,
+            Json(ApiError {
+                error: "Tracked QR code not found".to_string(),
+                code: "NOT_FOUND".to_string(),
+                status: 404,
+            }),
+        )
+    };
+
+    let (qr_id, short_code): (String, String) = conn
+        .query_row(
+            "SELECT qr_id, short_code FROM tracked_qr WHERE id = ?1",
+            rusqlite::params![id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|_| not_found())?;
+
+    let jwt_authorized = config.jwt_manage_tokens_enabled
+        && bearer
+            .0
+            .as_deref()
+            .is_some_and(|token| {
+                jwt_keys
+                    .verify(token, id, &short_code, jwt_manage::MANAGE_SCOPE)
+                    .is_ok()
+            });
+
+    if !jwt_authorized {
+        // Fall back to the normal ownership check: see
+        // `Principal::ownership_scope` (API keys scoped to their own rows,
+        // admin sessions unrestricted, non-admin sessions forbidden).
+        let principal = principal.ok_or((
+            Status::Unauthorized,
+            Json(ApiError {
+                error: "Missing API key or session".to_string(),
+                code: "UNAUTHORIZED".to_string(),
+                status: 401,
+            }),
+        ))?;
+
+        if let Some(key_id) = principal.ownership_scope()? {
+            let owned: bool = conn
+                .query_row(
+                    "SELECT COUNT(*) > 0 FROM tracked_qr t JOIN qr_codes q ON t.qr_id = q.id WHERE t.id = ?1 AND q.api_key_id = ?2",
+                    rusqlite::params![id, key_id],
+                    |row| row.get(0),
+                )
+                .unwrap_or(false);
+            if !owned {
+                return Err(not_found());
+            }
+        }
+    }
 
     // Delete scan events first (FK constraint)
     conn.execute(
@@ -1116,23 +3785,34 @@ pub fn delete_tracked_qr(
 
 #[get("/keys")]
 pub fn list_keys(
-    key: AuthenticatedKey,
+    principal: Principal,
     db: &State<DbPool>,
 ) -> Result<Json<Vec<KeyResponse>>, (Status, Json<ApiError>)> {
-    if !key.is_admin {
-        return Err((
-            Status::Forbidden,
-            Json(ApiError {
-                error: "Admin access required".to_string(),
-                code: "FORBIDDEN".to_string(),
-                status: 403,
-            }),
-        ));
+    // An API key caller goes through the same `KeysManage` scope check as
+    // `create_key`/`rotate_key`/`delete_key`, rather than the standalone
+    // `is_admin` flag those routes dropped — a legacy key with no scopes
+    // data (`scopes_source_empty`) can already create/rotate/delete keys
+    // via that check, so gating *this* route on `is_admin` alone let it
+    // manage every key but not list them. A dashboard session has no
+    // scopes of its own to check, so it still falls back to `is_admin`.
+    match &principal {
+        Principal::ApiKey(k) => k.require(auth::Action::KeysManage)?,
+        Principal::Session(_) if principal.is_admin() => {}
+        Principal::Session(_) => {
+            return Err((
+                Status::Forbidden,
+                Json(ApiError {
+                    error: "Admin access required".to_string(),
+                    code: "FORBIDDEN".to_string(),
+                    status: 403,
+                }),
+            ));
+        }
     }
 
-    let conn = db.lock().unwrap();
+    let conn = db.get().unwrap();
     let mut stmt = conn.prepare(
-        "SELECT id, name, created_at, last_used_at, requests_count, rate_limit, active FROM api_keys ORDER BY created_at DESC"
+        "SELECT id, name, created_at, last_used_at, requests_count, rate_limit, active, scopes, valid_from, valid_until FROM api_keys ORDER BY created_at DESC"
     ).map_err(|e| {
         (Status::InternalServerError, Json(ApiError {
             error: format!("Database error: {}", e),
@@ -1152,6 +3832,9 @@ pub fn list_keys(
                 requests_count: row.get(4)?,
                 rate_limit: row.get(5)?,
                 active: row.get::<_, i32>(6)? == 1,
+                scopes: parse_scopes(&row.get::<_, String>(7)?),
+                valid_from: row.get(8)?,
+                valid_until: row.get(9)?,
             })
         })
         .map_err(|e| {
@@ -1176,26 +3859,39 @@ pub fn create_key(
     key: AuthenticatedKey,
     db: &State<DbPool>,
 ) -> Result<Json<KeyResponse>, (Status, Json<ApiError>)> {
-    if !key.is_admin {
+    key.require(auth::Action::KeysManage)?;
+
+    let req = req.into_inner();
+
+    // An unrecognized scope string would otherwise silently disappear in
+    // `Action::parse` and the minted key would come out *more* privileged
+    // than asked (an empty `scopes` column reads as unrestricted — see
+    // `AuthenticatedKey::has_scope`), not less. Reject it up front instead.
+    let unknown_scopes: Vec<&String> = req
+        .scopes
+        .iter()
+        .filter(|s| auth::Action::parse(s).is_empty())
+        .collect();
+    if !unknown_scopes.is_empty() {
         return Err((
-            Status::Forbidden,
+            Status::BadRequest,
             Json(ApiError {
-                error: "Admin access required".to_string(),
-                code: "FORBIDDEN".to_string(),
-                status: 403,
+                error: format!("Unknown scope(s): {}", unknown_scopes.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(", ")),
+                code: "INVALID_SCOPE".to_string(),
+                status: 400,
             }),
         ));
     }
 
-    let req = req.into_inner();
     let new_key = format!("qrs_{}", uuid::Uuid::new_v4().to_string().replace("-", ""));
     let key_hash_val = hash_key(&new_key);
     let id = uuid::Uuid::new_v4().to_string();
+    let scopes_val = req.scopes.join(",");
 
-    let conn = db.lock().unwrap();
+    let conn = db.get().unwrap();
     conn.execute(
-        "INSERT INTO api_keys (id, name, key_hash, rate_limit) VALUES (?1, ?2, ?3, ?4)",
-        rusqlite::params![id, req.name, key_hash_val, req.rate_limit],
+        "INSERT INTO api_keys (id, name, key_hash, rate_limit, scopes, valid_from, valid_until) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        rusqlite::params![id, req.name, key_hash_val, req.rate_limit, scopes_val, req.valid_from, req.valid_until],
     )
     .map_err(|e| {
         (
@@ -1217,6 +3913,105 @@ pub fn create_key(
         requests_count: 0,
         rate_limit: req.rate_limit,
         active: true,
+        scopes: req.scopes,
+        valid_from: req.valid_from,
+        valid_until: req.valid_until,
+    }))
+}
+
+/// Parses `api_keys.scopes`'s comma-separated storage format, dropping empty
+/// entries (an unset/legacy key stores `""`, which should parse to `vec![]`
+/// rather than `vec![""]`).
+fn parse_scopes(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Issues a fresh secret for an existing key while preserving its name,
+/// scopes, and validity window — so a leaked or rotated-on-schedule key can
+/// be replaced without clients needing to re-request scopes. The displaced
+/// hash moves to `previous_key_hash` rather than being discarded, so it
+/// keeps authenticating for `Config::key_rotation_grace_secs` (see
+/// `auth::lookup_and_touch_key`) while callers pick up the new secret.
+#[post("/keys/<id>/rotate")]
+pub fn rotate_key(
+    id: &str,
+    key: AuthenticatedKey,
+    db: &State<DbPool>,
+    config: &State<Config>,
+) -> Result<Json<KeyResponse>, (Status, Json<ApiError>)> {
+    key.require(auth::Action::KeysManage)?;
+
+    let conn = db.get().unwrap();
+    let row = conn
+        .query_row(
+            "SELECT name, rate_limit, active, scopes, valid_from, valid_until, key_hash FROM api_keys WHERE id = ?1",
+            rusqlite::params![id],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, i32>(2)? == 1,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, Option<String>>(5)?,
+                    row.get::<_, String>(6)?,
+                ))
+            },
+        )
+        .map_err(|_| {
+            (
+                Status::NotFound,
+                Json(ApiError {
+                    error: "API key not found".to_string(),
+                    code: "NOT_FOUND".to_string(),
+                    status: 404,
+                }),
+            )
+        })?;
+    let (name, rate_limit, active, scopes_raw, valid_from, valid_until, old_hash) = row;
+
+    let new_key = format!("qrs_{}", uuid::Uuid::new_v4().to_string().replace("-", ""));
+    let new_hash = hash_key(&new_key);
+    let grace_expires_at = if config.key_rotation_grace_secs > 0 {
+        Some(
+            (chrono::Utc::now() + chrono::Duration::seconds(config.key_rotation_grace_secs as i64))
+                .to_rfc3339(),
+        )
+    } else {
+        None
+    };
+
+    conn.execute(
+        "UPDATE api_keys SET key_hash = ?1, previous_key_hash = ?2, previous_key_hash_expires_at = ?3 WHERE id = ?4",
+        rusqlite::params![new_hash, old_hash, grace_expires_at, id],
+    )
+    .map_err(|e| {
+        (
+            Status::InternalServerError,
+            Json(ApiError {
+                error: format!("Failed to rotate key: {}", e),
+                code: "DB_ERROR".to_string(),
+                status: 500,
+            }),
+        )
+    })?;
+
+    Ok(Json(KeyResponse {
+        id: id.to_string(),
+        name,
+        key: Some(new_key),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        last_used_at: None,
+        requests_count: 0,
+        rate_limit,
+        active,
+        scopes: parse_scopes(&scopes_raw),
+        valid_from,
+        valid_until,
     }))
 }
 
@@ -1226,18 +4021,9 @@ pub fn delete_key(
     key: AuthenticatedKey,
     db: &State<DbPool>,
 ) -> Result<Json<serde_json::Value>, (Status, Json<ApiError>)> {
-    if !key.is_admin {
-        return Err((
-            Status::Forbidden,
-            Json(ApiError {
-                error: "Admin access required".to_string(),
-                code: "FORBIDDEN".to_string(),
-                status: 403,
-            }),
-        ));
-    }
+    key.require(auth::Action::KeysManage)?;
 
-    let conn = db.lock().unwrap();
+    let conn = db.get().unwrap();
     let affected = conn
         .execute(
             "UPDATE api_keys SET active = 0 WHERE id = ?1",
@@ -1261,10 +4047,18 @@ pub fn delete_key(
 
 // ============ Short URL Redirect (mounted at root, not /api/v1) ============
 
-/// Captures optional scan metadata from request headers.
+/// Captures scan metadata from request headers: User-Agent/Referer (as
+/// before), plus the client IP (same precedence as `AuthenticatedKey`'s
+/// `ClientIp` guard), `Accept-Language`, and whether `DNT: 1` was sent.
+/// `accept_language`/`dnt` aren't persisted yet — they're captured here so a
+/// future consent-aware enrichment step has them without another round of
+/// guard plumbing.
 pub struct ScanMeta {
     pub user_agent: Option<String>,
     pub referrer: Option<String>,
+    pub client_ip: String,
+    pub accept_language: Option<String>,
+    pub dnt: bool,
 }
 
 #[rocket::async_trait]
@@ -1279,9 +4073,18 @@ impl<'r> rocket::request::FromRequest<'r> for ScanMeta {
             .get_one("User-Agent")
             .map(|s| s.to_string());
         let referrer = request.headers().get_one("Referer").map(|s| s.to_string());
+        let client_ip = auth::client_ip_from_headers(request);
+        let accept_language = request
+            .headers()
+            .get_one("Accept-Language")
+            .map(|s| s.to_string());
+        let dnt = request.headers().get_one("DNT") == Some("1");
         rocket::request::Outcome::Success(ScanMeta {
             user_agent,
             referrer,
+            client_ip,
+            accept_language,
+            dnt,
         })
     }
 }
@@ -1290,32 +4093,104 @@ impl<'r> rocket::request::FromRequest<'r> for ScanMeta {
 /// When someone scans a tracked QR code, they hit /r/<code> which redirects
 /// to the target URL while recording the scan event.
 #[get("/r/<code>")]
-pub fn redirect_short_url(
+pub async fn redirect_short_url(
     code: &str,
+    client_ip: ClientIp,
     db: &State<DbPool>,
+    config: &State<Config>,
+    enc: &State<db::DbEncryption>,
+    geoip: &State<Arc<dyn geoip::GeoIpLookup>>,
+    meta: ScanMeta,
+) -> Result<Redirect, (Status, Json<ApiError>)> {
+    let code = code.to_string();
+    let pool = db.inner().clone();
+    let config = config.inner().clone();
+    let enc = enc.inner().clone();
+    let geoip = geoip.inner().clone();
+
+    rocket::tokio::task::spawn_blocking(move || {
+        redirect_short_url_blocking(&code, client_ip, &pool, &config, &enc, &geoip, meta)
+    })
+    .await
+    .unwrap_or_else(|_| {
+        Err((
+            Status::InternalServerError,
+            Json(ApiError {
+                error: "Background redirect task panicked".to_string(),
+                code: "TASK_PANIC".to_string(),
+                status: 500,
+            }),
+        ))
+    })
+}
+
+/// Blocking body of `redirect_short_url`, run via `spawn_blocking` since it
+/// does a pooled-connection lookup, a scan-event insert, and (via
+/// `grpc_auth::check`) a synchronous gRPC round trip.
+fn redirect_short_url_blocking(
+    code: &str,
+    client_ip: ClientIp,
+    db: &DbPool,
+    config: &Config,
+    enc: &db::DbEncryption,
+    geoip: &Arc<dyn geoip::GeoIpLookup>,
     meta: ScanMeta,
 ) -> Result<Redirect, (Status, Json<ApiError>)> {
-    let conn = db.lock().unwrap();
+    // Reject a forged/tampered signature before ever touching the database,
+    // so scanning random or bit-flipped codes can't be used to enumerate
+    // `tracked_qr` rows.
+    if !db::verify_short_code(&config.shortcode_signing_key, code) {
+        return Err((
+            Status::NotFound,
+            Json(ApiError {
+                error: "Short URL not found".to_string(),
+                code: "NOT_FOUND".to_string(),
+                status: 404,
+            }),
+        ));
+    }
+
+    let conn = db.get().unwrap();
 
     // Look up the tracked QR by short code
     let result = conn.query_row(
-        "SELECT id, target_url, expires_at FROM tracked_qr WHERE short_code = ?1",
+        "SELECT id, target_url, expires_at, tombstoned_at FROM tracked_qr WHERE short_code = ?1",
         rusqlite::params![code],
         |row| {
             Ok((
                 row.get::<_, String>(0)?,
                 row.get::<_, String>(1)?,
                 row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
             ))
         },
     );
 
     match result {
-        Ok((tracked_id, target_url, expires_at)) => {
-            // Check expiry
+        Ok((tracked_id, target_url, expires_at, tombstoned_at)) => {
+            // Tombstoned by the reaper (see `reaper`) — the row stays around
+            // for historical stats, but no longer redirects.
+            if tombstoned_at.is_some() {
+                return Err((
+                    Status::Gone,
+                    Json(ApiError {
+                        error: "This short URL has expired".to_string(),
+                        code: "EXPIRED".to_string(),
+                        status: 410,
+                    }),
+                ));
+            }
+
+            // Belt-and-suspenders check for a row the reaper hasn't swept
+            // yet: parse both sides into `DateTime<Utc>` rather than
+            // comparing `expires_at`'s stored representation against
+            // `now`'s as strings, which silently mis-orders once the two
+            // formats differ (RFC3339 vs SQLite's `datetime('now')`).
             if let Some(ref exp) = expires_at {
-                let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
-                if now > *exp {
+                let expired = db::parse_expiry(exp)
+                    .map(|dt| dt <= chrono::Utc::now())
+                    .unwrap_or(false);
+                if expired {
                     return Err((
                         Status::Gone,
                         Json(ApiError {
@@ -1327,11 +4202,38 @@ pub fn redirect_short_url(
                 }
             }
 
-            // Record scan event
+            grpc_auth::check(
+                config,
+                "",
+                &target_url,
+                &client_ip.0,
+                meta.user_agent.as_deref().unwrap_or(""),
+            )?;
+
+            // Record scan event. `browser`/`os`/`device_type`/`is_bot`/
+            // `country`/`referrer_host` are parsed/resolved here, at write
+            // time, and stored unencrypted so the timeseries/breakdown
+            // endpoint can `GROUP BY` them in SQL; the raw `user_agent`/
+            // `referrer` stay encrypted as before.
             let scan_id = uuid::Uuid::new_v4().to_string();
+            let parsed_ua = ua::parse(meta.user_agent.as_deref().unwrap_or(""));
+            let country = geoip.lookup_country(&meta.client_ip);
+            let referrer_host = meta.referrer.as_deref().and_then(ua::referrer_host);
             let _ = conn.execute(
-                "INSERT INTO scan_events (id, tracked_qr_id, user_agent, referrer) VALUES (?1, ?2, ?3, ?4)",
-                rusqlite::params![scan_id, tracked_id, meta.user_agent, meta.referrer],
+                "INSERT INTO scan_events (id, tracked_qr_id, user_agent, referrer, browser, os, device_type, is_bot, country, referrer_host)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                rusqlite::params![
+                    scan_id,
+                    tracked_id,
+                    meta.user_agent.map(|s| db::encrypt(enc, s.as_bytes())),
+                    meta.referrer.map(|s| db::encrypt(enc, s.as_bytes())),
+                    parsed_ua.browser,
+                    parsed_ua.os,
+                    parsed_ua.device_type.as_str(),
+                    parsed_ua.device_type == ua::DeviceType::Bot,
+                    country,
+                    referrer_host,
+                ],
             );
 
             // Increment scan count
@@ -1368,3 +4270,20 @@ pub fn spa_fallback(_path: PathBuf) -> Option<(ContentType, Vec<u8>)> {
         .ok()
         .map(|bytes| (ContentType::HTML, bytes))
 }
+
+// ============ Catchers ============
+
+/// Renders a denied `AnonymousRateLimit`/`AuthenticatedKey` guard (or any
+/// other 429) as the same structured `ApiError` envelope every in-handler
+/// error already uses, instead of Rocket's default HTML error page. The
+/// `X-RateLimit-*`/`Retry-After` headers are added to this response by the
+/// `rate_limit::RateLimitHeaders` response fairing after this catcher runs —
+/// it only supplies the body.
+#[catch(429)]
+pub fn rate_limited() -> Json<ApiError> {
+    Json(ApiError {
+        error: "Rate limit exceeded".to_string(),
+        code: "RATE_LIMITED".to_string(),
+        status: 429,
+    })
+}
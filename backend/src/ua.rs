@@ -0,0 +1,135 @@
+//! Minimal User-Agent parsing for scan analytics. This deliberately isn't a
+//! full UA-parser replacement (no regex database to keep updated) — just
+//! enough signal to bucket `/qr/tracked/<id>/stats/timeseries` results by
+//! browser, OS, and device class, and to keep bot traffic out of human scan
+//! counts.
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// Device class inferred from a scan's User-Agent string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceType {
+    Mobile,
+    Tablet,
+    Desktop,
+    Bot,
+}
+
+impl DeviceType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DeviceType::Mobile => "mobile",
+            DeviceType::Tablet => "tablet",
+            DeviceType::Desktop => "desktop",
+            DeviceType::Bot => "bot",
+        }
+    }
+}
+
+/// Parsed signal stored alongside each scan event in `scan_events`.
+pub struct ParsedUserAgent {
+    pub browser: Option<String>,
+    pub os: Option<String>,
+    pub device_type: DeviceType,
+}
+
+/// Substrings that mark a User-Agent as a crawler/bot/script rather than a
+/// human browser, so scan totals aren't inflated by link-preview fetchers,
+/// uptime monitors, and the like.
+const BOT_MARKERS: &[&str] = &[
+    "bot",
+    "crawl",
+    "spider",
+    "slurp",
+    "facebookexternalhit",
+    "curl/",
+    "wget/",
+    "python-requests",
+    "axios/",
+    "preview",
+    "headlesschrome",
+];
+
+/// Classifies a scan's User-Agent header. An empty/missing UA is treated as
+/// a bot, since no real browser omits the header.
+pub fn parse(user_agent: &str) -> ParsedUserAgent {
+    let ua = user_agent.trim();
+    let lower = ua.to_ascii_lowercase();
+
+    if ua.is_empty() || BOT_MARKERS.iter().any(|marker| lower.contains(marker)) {
+        return ParsedUserAgent {
+            browser: None,
+            os: None,
+            device_type: DeviceType::Bot,
+        };
+    }
+
+    let os = if lower.contains("windows") {
+        Some("Windows".to_string())
+    } else if lower.contains("mac os x") || lower.contains("macintosh") {
+        Some("macOS".to_string())
+    } else if lower.contains("android") {
+        Some("Android".to_string())
+    } else if lower.contains("iphone") || lower.contains("ipad") || lower.contains("ios ") {
+        Some("iOS".to_string())
+    } else if lower.contains("linux") {
+        Some("Linux".to_string())
+    } else {
+        None
+    };
+
+    let browser = if lower.contains("edg/") {
+        Some("Edge".to_string())
+    } else if lower.contains("chrome/") && !lower.contains("chromium") {
+        Some("Chrome".to_string())
+    } else if lower.contains("firefox/") {
+        Some("Firefox".to_string())
+    } else if lower.contains("safari/") && !lower.contains("chrome/") {
+        Some("Safari".to_string())
+    } else {
+        None
+    };
+
+    let device_type = if lower.contains("ipad")
+        || lower.contains("tablet")
+        || (lower.contains("android") && !lower.contains("mobile"))
+    {
+        DeviceType::Tablet
+    } else if lower.contains("mobi") || lower.contains("iphone") {
+        DeviceType::Mobile
+    } else if lower.contains("android") {
+        DeviceType::Mobile
+    } else {
+        DeviceType::Desktop
+    };
+
+    ParsedUserAgent {
+        browser,
+        os,
+        device_type,
+    }
+}
+
+/// Extracts just the host from a referrer URL (e.g. `https://x.com/a?b=c` ->
+/// `x.com`), so `scan_events.referrer_host` can stay unencrypted and safe to
+/// `GROUP BY` for the top-referrers breakdown without keeping the full,
+/// potentially sensitive referrer URL (path/query) in the clear — that stays
+/// encrypted in `scan_events.referrer`, same as today.
+pub fn referrer_host(referrer: &str) -> Option<String> {
+    let without_scheme = referrer
+        .split_once("://")
+        .map(|(_, rest)| rest)
+        .unwrap_or(referrer);
+    let host = without_scheme
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or(without_scheme);
+    let host = host.rsplit_once('@').map(|(_, h)| h).unwrap_or(host);
+    if host.is_empty() {
+        None
+    } else {
+        Some(host.to_string())
+    }
+}